@@ -0,0 +1,110 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::{json, Map, Value};
+
+use balena_temen::{ast::Identifier, evaluate, CompiledExpression, Context, Engine};
+
+const EXPRESSION: &str = "super.ssid ~ ` ` ~ super.index | SLUGIFY | UPPER";
+
+// Large template with `count` independent networks, each of them with two formulas
+fn template(count: usize) -> Value {
+    let mut networks = Map::new();
+
+    for index in 0..count {
+        networks.insert(
+            format!("network{}", index),
+            json!({
+                "ssid": format!("Balena {}", index),
+                "index": index,
+                "id": {
+                    "$$formula": EXPRESSION
+                },
+                "upperId": {
+                    "$$formula": "super.id | UPPER"
+                }
+            }),
+        );
+    }
+
+    Value::Object(networks)
+}
+
+fn eval_vs_eval_compiled(c: &mut Criterion) {
+    let engine = Engine::default();
+    let mut context = Context::default();
+    let position = Identifier::default().name("id");
+    let data: Vec<Value> = (0..100)
+        .map(|index| json!({"ssid": format!("Balena {}", index), "index": index}))
+        .collect();
+
+    let mut group = c.benchmark_group("expression");
+
+    group.bench_function("eval", |b| {
+        b.iter(|| {
+            for data in &data {
+                black_box(engine.eval(EXPRESSION, &position, data, &mut context).unwrap());
+            }
+        })
+    });
+
+    let compiled = CompiledExpression::new(EXPRESSION).unwrap();
+    group.bench_function("eval_compiled", |b| {
+        b.iter(|| {
+            for data in &data {
+                black_box(engine.eval_compiled(&compiled, &position, data, &mut context).unwrap());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn evaluate_large_template(c: &mut Criterion) {
+    let engine = Engine::default();
+    let mut context = Context::default();
+    let data = template(500);
+    let networks: Vec<String> = data.as_object().unwrap().keys().cloned().collect();
+
+    let mut group = c.benchmark_group("evaluate 500 networks");
+
+    group.bench_function("evaluate", |b| b.iter(|| evaluate(black_box(data.clone())).unwrap()));
+
+    // Formulas are evaluated in the dependency order, `id` first and then `upperId`
+    group.bench_function("eval", |b| {
+        b.iter(|| {
+            let mut data = data.clone();
+            for network in &networks {
+                let position = Identifier::default().name(network.as_str()).name("id");
+                let id = engine.eval(EXPRESSION, &position, &data, &mut context).unwrap();
+                data[network]["id"] = id;
+
+                let position = Identifier::default().name(network.as_str()).name("upperId");
+                let upper_id = engine.eval("super.id | UPPER", &position, &data, &mut context).unwrap();
+                data[network]["upperId"] = upper_id;
+            }
+            black_box(data)
+        })
+    });
+
+    let id = CompiledExpression::new(EXPRESSION).unwrap();
+    let upper_id = CompiledExpression::new("super.id | UPPER").unwrap();
+    group.bench_function("eval_compiled", |b| {
+        b.iter(|| {
+            let mut data = data.clone();
+            for network in &networks {
+                let position = Identifier::default().name(network.as_str()).name("id");
+                let value = engine.eval_compiled(&id, &position, &data, &mut context).unwrap();
+                data[network]["id"] = value;
+
+                let position = Identifier::default().name(network.as_str()).name("upperId");
+                let value = engine.eval_compiled(&upper_id, &position, &data, &mut context).unwrap();
+                data[network]["upperId"] = value;
+            }
+            black_box(data)
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, eval_vs_eval_compiled, evaluate_large_template);
+criterion_main!(benches);
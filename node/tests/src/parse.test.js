@@ -0,0 +1,21 @@
+const bt = require('balena-temen');
+
+test('parse fn succeeds', () => {
+    expect(
+        bt.parse("1")
+    ).toEqual(
+        {
+            "value": { "type": "integer", "value": 1 },
+            "negated": false,
+            "filters": []
+        }
+    );
+});
+
+test('parse fn throws', () => {
+    expect(
+        () => {
+            bt.parse("1 +");
+        }
+    ).toThrow();
+});
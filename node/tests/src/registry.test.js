@@ -0,0 +1,33 @@
+const bt = require('balena-temen');
+
+test('functions fn lists builtin functions', () => {
+    const functions = bt.functions();
+
+    expect(functions.map((f) => f.name)).toContain("UUIDV4");
+    expect(
+        functions.find((f) => f.name === "POW")
+    ).toEqual(
+        {
+            "name": "POW",
+            "determinism": "pure",
+            "signature": {
+                "input": "any",
+                "args": [
+                    { "name": "base", "valueType": "number", "required": true },
+                    { "name": "exponent", "valueType": "number", "required": true }
+                ],
+                "variadic": null,
+                "returnType": "number"
+            },
+            "description": "Raises `base` to the power of `exponent`"
+        }
+    );
+});
+
+test('filters fn lists builtin filters', () => {
+    expect(
+        bt.filters().map((f) => f.name)
+    ).toEqual(
+        ["DATE", "DATETIME", "LOWER", "SLUGIFY", "TIME", "TRIM", "UPPER"]
+    );
+});
@@ -54,10 +54,10 @@ fi
 mkdir -p "${TARGET_DIR}"
 
 echo "Packing NodeJS NPM package..."
-wasm-pack build --target nodejs  --out-dir "${NODE_PKG_DIR}"
+wasm-pack build --target nodejs  --out-dir "${NODE_PKG_DIR}" -- --features serde
 
 echo "Packing browser NPM package..."
-wasm-pack build --target browser --out-dir "${BROWSER_PKG_DIR}"
+wasm-pack build --target browser --out-dir "${BROWSER_PKG_DIR}" -- --features serde
 
 echo "Building isomorphic NPM package..."
 cp -r "${BROWSER_PKG_DIR}" "${PKG_DIR}/"
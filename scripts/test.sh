@@ -29,6 +29,12 @@ cargo clippy --all-targets --all-features -- -D warnings
 echo "Testing Rust crate..."
 cargo test
 
+echo "Testing Rust crate without default features..."
+cargo test --no-default-features
+
+echo "Testing Rust crate with all features..."
+cargo test --all-features
+
 echo "Trying to package Rust crate..."
 CARGO_PACKAGE_ARGS=''
 if [ -z "${CI}" ]; then
@@ -20,14 +20,96 @@
 //! assert_eq!(parsed, manual);
 //! ```
 //!
+//! # Serialization
+//!
+//! All AST types implement `Serialize` and `Deserialize` if the `serde` feature is enabled.
+//! The JSON shape is stable:
+//!
+//! * `Expression` - `{"value": ExpressionValue, "negated": bool, "filters": [FunctionCall]}`,
+//!   `negated` and `filters` are optional when deserializing
+//! * `ExpressionValue` - `{"type": string, "value": any}`, where `type` is one of
+//!   * `integer`, `float`, `boolean`, `string` - `value` is a JSON number, boolean or string
+//!   * `identifier` - `value` is an `Identifier`
+//!   * `math` - `value` is `{"lhs": Expression, "rhs": Expression, "operator": MathOperator}`
+//!   * `logical` - `value` is `{"lhs": Expression, "rhs": Expression, "operator": LogicalOperator}`
+//!   * `functionCall` - `value` is a `FunctionCall`
+//!   * `stringConcat` - `value` is `{"values": [ExpressionValue]}`
+//!   * `ternary` - `value` is `{"condition": Expression, "truthy": Expression, "falsy": Expression}`
+//!   * `placeholder` - no `value`
+//! * `FunctionCall` - `{"name": string, "args": [Expression], "kwargs": {string: Expression}}`,
+//!   `args` and `kwargs` are optional when deserializing
+//! * `Identifier` - `{"values": [IdentifierValue]}`
+//! * `IdentifierValue` - `{"type": string, "value": any}`, where `type` is one of
+//!   * `name` - `value` is a string
+//!   * `index` - `value` is an integer
+//!   * `identifier` - `value` is an `Identifier` (indirect lookup)
+//!   * `this`, `super` - no `value`
+//! * `MathOperator` - `addition`, `subtraction`, `multiplication`, `division`, `modulo`
+//! * `LogicalOperator` - `equal`, `notEqual`, `greaterThan`, `greaterThanOrEqual`, `lowerThan`,
+//!   `lowerThanOrEqual`, `and`, `or`
+//!
+//! ```rust
+//! # #[cfg(feature = "serde")]
+//! # {
+//! use balena_temen::ast::*;
+//! use serde_json::json;
+//!
+//! let expression: Expression = "a.b + 1".parse().unwrap();
+//!
+//! assert_eq!(
+//!     serde_json::to_value(&expression).unwrap(),
+//!     json!({
+//!         "value": {
+//!             "type": "math",
+//!             "value": {
+//!                 "lhs": {
+//!                     "value": {
+//!                         "type": "identifier",
+//!                         "value": {
+//!                             "values": [
+//!                                 { "type": "name", "value": "a" },
+//!                                 { "type": "name", "value": "b" }
+//!                             ]
+//!                         }
+//!                     },
+//!                     "negated": false,
+//!                     "filters": []
+//!                 },
+//!                 "rhs": {
+//!                     "value": { "type": "integer", "value": 1 },
+//!                     "negated": false,
+//!                     "filters": []
+//!                 },
+//!                 "operator": "addition"
+//!             }
+//!         },
+//!         "negated": false,
+//!         "filters": []
+//!     })
+//! );
+//! # }
+//! ```
+//!
 //! [The Elegant Parser]: https://github.com/pest-parser/pest
 //! [grammar]: https://github.com/balena-io-modules/balena-temen/blob/master/src/parser/grammar.pest
+use std::collections::BTreeMap;
+use std::fmt;
 use std::str::FromStr;
 
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
 use crate::{error::*, parser::parse};
 
+mod printer;
+pub mod visit;
+
+pub use self::visit::{Visitor, VisitorMut};
+
 /// Math operator
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub enum MathOperator {
     /// `+`
     Addition,
@@ -43,6 +125,8 @@ pub enum MathOperator {
 
 /// Logical operator
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub enum LogicalOperator {
     /// `==`
     Equal,
@@ -64,33 +148,74 @@ pub enum LogicalOperator {
 
 /// A function call
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FunctionCall {
     /// A function name
     pub name: String,
     /// Positional arguments
+    #[cfg_attr(feature = "serde", serde(default))]
     pub args: Vec<Expression>,
+    /// Named (keyword) arguments
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kwargs: BTreeMap<String, Expression>,
 }
 
 impl FunctionCall {
     /// Creates new function call
     ///
+    /// No named arguments are passed.
+    ///
     /// # Arguments
     ///
     /// * `name` - A function name
     /// * `args` - Positional arguments
     pub fn new<S>(name: S, args: Vec<Expression>) -> FunctionCall
+    where
+        S: Into<String>,
+    {
+        FunctionCall::new_with_kwargs(name, args, BTreeMap::new())
+    }
+
+    /// Creates new function call with named arguments
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A function name
+    /// * `args` - Positional arguments
+    /// * `kwargs` - Named arguments
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::ast::*;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut kwargs = BTreeMap::new();
+    /// kwargs.insert("timestamp".to_string(), Expression::new(ExpressionValue::Boolean(true)));
+    ///
+    /// let parsed: Expression = "NOW(timestamp=true)".parse().unwrap();
+    /// let manual = Expression::new(
+    ///     ExpressionValue::FunctionCall(
+    ///         FunctionCall::new_with_kwargs("NOW", vec![], kwargs)
+    ///     )
+    /// );
+    /// assert_eq!(parsed, manual);
+    /// ```
+    pub fn new_with_kwargs<S>(name: S, args: Vec<Expression>, kwargs: BTreeMap<String, Expression>) -> FunctionCall
     where
         S: Into<String>,
     {
         FunctionCall {
             name: name.into(),
             args,
+            kwargs,
         }
     }
 }
 
 /// Math expression
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MathExpression {
     /// A left-hand side
     pub lhs: Box<Expression>,
@@ -119,6 +244,7 @@ impl MathExpression {
 
 /// Math expression
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TernaryExpression {
     pub condition: Box<Expression>,
     pub truthy: Box<Expression>,
@@ -137,6 +263,7 @@ impl TernaryExpression {
 
 /// Logical expression
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LogicalExpression {
     /// A left-hand side
     pub lhs: Box<Expression>,
@@ -165,6 +292,7 @@ impl LogicalExpression {
 
 /// String concatenation
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StringConcat {
     /// List of values to concatenate
     pub values: Vec<ExpressionValue>,
@@ -217,6 +345,7 @@ impl StringConcat {
 ///   └ IdentifierValue::This
 /// ```
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Identifier {
     /// List of identifier values (components)
     pub values: Vec<IdentifierValue>,
@@ -280,7 +409,8 @@ impl Identifier {
         true
     }
 
-    fn is_relative(&self) -> bool {
+    /// Checks if an identifier starts with `this` or `super`
+    pub(crate) fn is_relative(&self) -> bool {
         if let Some(first) = self.values.first() {
             match first {
                 IdentifierValue::This | IdentifierValue::Super => true,
@@ -299,18 +429,18 @@ impl Identifier {
             // Identifier is relative, it must start somewhere
             if position.is_relative() {
                 // Position must not be relative
-                return Err(Error::with_message("unable to canonicalize identifier")
+                return Err(Error::new(ErrorKind::LookupFailed, "unable to canonicalize identifier")
                     .context("reason", "identifier and position are relative identifiers")
-                    .context("identifier", format!("{:?}", self))
-                    .context("position", format!("{:?}", position)));
+                    .context("identifier", self.to_string())
+                    .context("position", position.to_string()));
             }
 
             if position.values.is_empty() {
                 // Position must not be empty
-                return Err(Error::with_message("unable to canonicalize identifier")
+                return Err(Error::new(ErrorKind::LookupFailed, "unable to canonicalize identifier")
                     .context("reason", "identifier is relative and position is empty")
-                    .context("identifier", format!("{:?}", self))
-                    .context("position", format!("{:?}", position)));
+                    .context("identifier", self.to_string())
+                    .context("position", position.to_string()));
             }
             Ok(Some(&position.values))
         } else {
@@ -363,7 +493,7 @@ impl Identifier {
                     // Super should resolve to parent, pop the latest identifier
                     // from result
                     result.pop().ok_or_else(|| {
-                        Error::with_message("unable to canonicalize identifier")
+                        Error::new(ErrorKind::LookupFailed, "unable to canonicalize identifier")
                             .context("reason", "`super` can not be resolved")
                     })?;
                 }
@@ -460,7 +590,7 @@ impl Identifier {
         let mut values = self.values;
         match values.pop() {
             Some(_) => Ok(Identifier { values }),
-            None => Err(Error::with_message("unable to pop identifier")),
+            None => Err(Error::new(ErrorKind::LookupFailed, "unable to pop identifier")),
         }
     }
 }
@@ -474,8 +604,67 @@ impl Default for Identifier {
     }
 }
 
+// Checks if the name can be used in the dotted notation (`wifi.ssid`)
+//
+// Grammar rejects identifiers starting with reserved keywords (`order`, `notes`, ...) and
+// `this` / `super` prefixes are consumed as relative identifier values.
+fn is_plain_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    let first_char_ok = match chars.next() {
+        Some(c) => c.is_ascii_alphabetic() || c == '_',
+        None => false,
+    };
+
+    first_char_ok
+        && name.len() <= 64
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !["not", "or", "and", "true", "false", "this", "super"]
+            .iter()
+            .any(|x| name.starts_with(x))
+}
+
+// Quotes a string with the first quote character not present in the string
+pub(crate) fn quote_string(value: &str) -> String {
+    let quote = ['`', '"', '\''].iter().find(|x| !value.contains(**x)).unwrap_or(&'`');
+    format!("{}{}{}", quote, value, quote)
+}
+
+impl fmt::Display for Identifier {
+    /// Formats an identifier in the expression language notation
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::ast::*;
+    ///
+    /// let identifier: Identifier = "super.wifi[`first network`].networks[0][this.index]".parse().unwrap();
+    /// assert_eq!(identifier.to_string(), "super.wifi[`first network`].networks[0][this.index]");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, value) in self.values.iter().enumerate() {
+            let separator = if idx == 0 { "" } else { "." };
+
+            match value {
+                // Leading `_` is enclosed in square brackets, otherwise it's parsed as a placeholder
+                // in filter arguments
+                IdentifierValue::Name(ref name) if idx == 0 && name == "_" => write!(f, "[{}]", quote_string(name))?,
+                IdentifierValue::Name(ref name) if is_plain_name(name) => write!(f, "{}{}", separator, name)?,
+                IdentifierValue::Name(ref name) => write!(f, "[{}]", quote_string(name))?,
+                IdentifierValue::Index(index) => write!(f, "[{}]", index)?,
+                IdentifierValue::Identifier(ref identifier) => write!(f, "[{}]", identifier)?,
+                IdentifierValue::This => write!(f, "{}this", separator)?,
+                IdentifierValue::Super => write!(f, "{}super", separator)?,
+            };
+        }
+        Ok(())
+    }
+}
+
 /// An identifier value (component)
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value", rename_all = "camelCase"))]
 pub enum IdentifierValue {
     /// A string index (dictionaries)
     Name(String),
@@ -491,6 +680,8 @@ pub enum IdentifierValue {
 
 /// An expression value
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value", rename_all = "camelCase"))]
 pub enum ExpressionValue {
     /// An integer
     Integer(i64),
@@ -512,16 +703,24 @@ pub enum ExpressionValue {
     StringConcat(StringConcat),
     /// Ternary expression
     Ternary(TernaryExpression),
+    /// Filter input placeholder (`_`)
+    ///
+    /// Valid as a positional argument of a function used in the filter position only
+    /// (`super.base | POW(2, _)`).
+    Placeholder,
 }
 
 /// An expression
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Expression {
     /// An expression value
     pub value: ExpressionValue,
     /// Is expression negated?
+    #[cfg_attr(feature = "serde", serde(default))]
     pub negated: bool,
     /// List of filters to apply
+    #[cfg_attr(feature = "serde", serde(default))]
     pub filters: Vec<FunctionCall>,
 }
 
@@ -589,14 +788,91 @@ impl Expression {
         }
     }
 
+    /// Returns list of identifiers an expression reads
+    ///
+    /// Identifiers are canonicalized against the position (see [`Identifier::canonicalize`])
+    /// and every identifier is listed once, in the order of appearance. Identifiers nested in
+    /// indirect lookups (`people[boss.id]`) are listed before the identifier itself.
+    ///
+    /// All function and filter arguments are included, even if they are not evaluated
+    /// (`IF(true, a, b)` depends on `a` and `b`).
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - An expression position for relative identifiers
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::ast::*;
+    ///
+    /// let expression: Expression = "super.ssid ~ people[super.boss.id].name | SLUGIFY".parse().unwrap();
+    /// let position: Identifier = "wifi.id".parse().unwrap();
+    ///
+    /// let dependencies: Vec<String> = expression
+    ///     .dependencies(&position)
+    ///     .unwrap()
+    ///     .iter()
+    ///     .map(ToString::to_string)
+    ///     .collect();
+    ///
+    /// assert_eq!(
+    ///     dependencies,
+    ///     vec!["wifi.ssid", "wifi.boss.id", "people[wifi.boss.id].name"]
+    /// );
+    /// ```
+    ///
+    /// [`Identifier::canonicalize`]: struct.Identifier.html#method.canonicalize
+    pub fn dependencies(&self, position: &Identifier) -> Result<Vec<Identifier>> {
+        let mut collector = DependenciesCollector {
+            position,
+            result: Ok(vec![]),
+        };
+        collector.visit_expression(self);
+        collector.result
+    }
+
     /// Converts self into [`Identifier`]
     ///
     /// [`Identifier`]: struct.Identifier.html
     pub fn into_identifier(self) -> Result<Identifier> {
         match self.value {
             ExpressionValue::Identifier(identifier) => Ok(identifier),
-            _ => Err(Error::with_message("expression does not contain an identifier")
-                .context("expression", format!("{:?}", self))),
+            _ => Err(
+                Error::new(ErrorKind::TypeMismatch, "expression does not contain an identifier")
+                    .context("expression", self.to_string()),
+            ),
+        }
+    }
+}
+
+// Pushes canonical identifier and all nested (indirect) identifiers
+fn push_identifier_dependencies(identifier: &Identifier, result: &mut Vec<Identifier>) {
+    for value in identifier.values.iter() {
+        if let IdentifierValue::Identifier(ref nested) = value {
+            push_identifier_dependencies(nested, result);
+        }
+    }
+
+    if !result.contains(identifier) {
+        result.push(identifier.clone());
+    }
+}
+
+/// Collects canonicalized identifiers, stops at the first error
+struct DependenciesCollector<'a> {
+    position: &'a Identifier,
+    result: Result<Vec<Identifier>>,
+}
+
+impl<'a, 'ast> Visitor<'ast> for DependenciesCollector<'a> {
+    fn visit_identifier(&mut self, identifier: &'ast Identifier) {
+        // Nested identifiers are canonicalized and collected along with the identifier itself
+        if let Ok(ref mut result) = self.result {
+            match identifier.canonicalize(self.position) {
+                Ok(identifier) => push_identifier_dependencies(&identifier, result),
+                Err(e) => self.result = Err(e),
+            };
         }
     }
 }
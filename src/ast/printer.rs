@@ -0,0 +1,365 @@
+//! An expression source printer
+//!
+//! Implements `Display` for the expression AST, which prints it back to the expression
+//! language notation. Parentheses are added only where they're required by the math operators
+//! precedence. Ternary expressions are always enclosed in parentheses, because the grammar
+//! requires them.
+use std::fmt::{self, Write};
+
+use super::*;
+
+fn math_operator(operator: MathOperator) -> &'static str {
+    match operator {
+        MathOperator::Addition => "+",
+        MathOperator::Subtraction => "-",
+        MathOperator::Multiplication => "*",
+        MathOperator::Division => "/",
+        MathOperator::Modulo => "%",
+    }
+}
+
+fn logical_operator(operator: LogicalOperator) -> &'static str {
+    match operator {
+        LogicalOperator::Equal => "==",
+        LogicalOperator::NotEqual => "!=",
+        LogicalOperator::GreaterThan => ">",
+        LogicalOperator::GreaterThanOrEqual => ">=",
+        LogicalOperator::LowerThan => "<",
+        LogicalOperator::LowerThanOrEqual => "<=",
+        LogicalOperator::And => "and",
+        LogicalOperator::Or => "or",
+    }
+}
+
+fn math_precedence(operator: MathOperator) -> u8 {
+    match operator {
+        MathOperator::Addition | MathOperator::Subtraction => 1,
+        MathOperator::Multiplication | MathOperator::Division | MathOperator::Modulo => 2,
+    }
+}
+
+// Checks if a math expression operand must be enclosed in parentheses
+//
+// Operators are left associative, right-hand side with the same precedence must be enclosed
+// as well (`a - (b - c)`). Operands with filters can't be enclosed, filters are applied to
+// the whole operand (`a | F * b + c | G`).
+fn needs_parentheses(operand: &Expression, operator: MathOperator, rhs: bool) -> bool {
+    match operand.value {
+        ExpressionValue::Math(ref math) if operand.filters.is_empty() && !operand.negated => {
+            let operand_precedence = math_precedence(math.operator);
+            let precedence = math_precedence(operator);
+            operand_precedence < precedence || (rhs && operand_precedence == precedence)
+        }
+        _ => false,
+    }
+}
+
+// Writes the expression language notation, keeps the first error and skips all
+// following writes
+struct Printer<'w, W: Write> {
+    w: &'w mut W,
+    result: fmt::Result,
+}
+
+impl<'w, W: Write> Printer<'w, W> {
+    fn new(w: &'w mut W) -> Printer<'w, W> {
+        Printer { w, result: Ok(()) }
+    }
+
+    fn write_str(&mut self, s: &str) {
+        if self.result.is_ok() {
+            self.result = self.w.write_str(s);
+        }
+    }
+
+    fn write_fmt(&mut self, args: fmt::Arguments) {
+        if self.result.is_ok() {
+            self.result = self.w.write_fmt(args);
+        }
+    }
+
+    fn write_math_operand(&mut self, operand: &Expression, operator: MathOperator, rhs: bool) {
+        if needs_parentheses(operand, operator, rhs) {
+            self.write_str("(");
+            self.visit_expression(operand);
+            self.write_str(")");
+        } else {
+            self.visit_expression(operand);
+        }
+    }
+
+    fn write_function_call(&mut self, call: &FunctionCall, filter: bool) {
+        self.write_str(&call.name);
+
+        if filter && call.args.is_empty() && call.kwargs.is_empty() {
+            // Filter without arguments, parentheses are optional
+            return;
+        }
+
+        self.write_str("(");
+        for (idx, arg) in call.args.iter().enumerate() {
+            if idx > 0 {
+                self.write_str(", ");
+            }
+            self.visit_expression(arg);
+        }
+        for (idx, (name, arg)) in call.kwargs.iter().enumerate() {
+            if idx > 0 || !call.args.is_empty() {
+                self.write_str(", ");
+            }
+            write!(self, "{}=", name);
+            self.visit_expression(arg);
+        }
+        self.write_str(")");
+    }
+}
+
+impl<'w, 'ast, W: Write> Visitor<'ast> for Printer<'w, W> {
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        if expression.negated {
+            self.write_str("not ");
+        }
+
+        self.visit_expression_value(&expression.value);
+
+        for filter in expression.filters.iter() {
+            self.write_str(" | ");
+            self.visit_filter(filter);
+        }
+    }
+
+    fn visit_integer(&mut self, value: i64) {
+        write!(self, "{}", value);
+    }
+
+    fn visit_float(&mut self, value: f64) {
+        // Display never uses the exponent notation, but it omits the fractional part
+        // if it's zero and the value would be parsed as an integer
+        let value = value.to_string();
+        if value.contains('.') {
+            self.write_str(&value);
+        } else {
+            write!(self, "{}.0", value);
+        }
+    }
+
+    fn visit_boolean(&mut self, value: bool) {
+        write!(self, "{}", value);
+    }
+
+    fn visit_string(&mut self, value: &'ast str) {
+        self.write_str(&quote_string(value));
+    }
+
+    fn visit_placeholder(&mut self) {
+        self.write_str("_");
+    }
+
+    fn visit_identifier(&mut self, identifier: &'ast Identifier) {
+        write!(self, "{}", identifier);
+    }
+
+    fn visit_math_expression(&mut self, math: &'ast MathExpression) {
+        self.write_math_operand(&math.lhs, math.operator, false);
+        self.visit_math_operator(math.operator);
+        self.write_math_operand(&math.rhs, math.operator, true);
+    }
+
+    fn visit_math_operator(&mut self, operator: MathOperator) {
+        write!(self, " {} ", math_operator(operator));
+    }
+
+    fn visit_logical_operator(&mut self, operator: LogicalOperator) {
+        write!(self, " {} ", logical_operator(operator));
+    }
+
+    fn visit_function_call(&mut self, call: &'ast FunctionCall) {
+        self.write_function_call(call, false);
+    }
+
+    fn visit_filter(&mut self, filter: &'ast FunctionCall) {
+        self.write_function_call(filter, true);
+    }
+
+    fn visit_string_concat(&mut self, concat: &'ast StringConcat) {
+        for (idx, value) in concat.values.iter().enumerate() {
+            if idx > 0 {
+                self.write_str(" ~ ");
+            }
+            self.visit_expression_value(value);
+        }
+    }
+
+    fn visit_ternary_expression(&mut self, ternary: &'ast TernaryExpression) {
+        self.write_str("(");
+        self.visit_expression(&ternary.condition);
+        self.write_str(" ? ");
+        self.visit_expression(&ternary.truthy);
+        self.write_str(" : ");
+        self.visit_expression(&ternary.falsy);
+        self.write_str(")");
+    }
+}
+
+impl fmt::Display for MathOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(math_operator(*self))
+    }
+}
+
+impl fmt::Display for LogicalOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(logical_operator(*self))
+    }
+}
+
+impl fmt::Display for FunctionCall {
+    /// Formats a function call in the expression language notation
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::ast::*;
+    ///
+    /// let call = FunctionCall::new("POW", vec![Expression::new(ExpressionValue::Integer(2))]);
+    /// assert_eq!(call.to_string(), "POW(2)");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut printer = Printer::new(f);
+        printer.visit_function_call(self);
+        printer.result
+    }
+}
+
+impl fmt::Display for MathExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut printer = Printer::new(f);
+        printer.visit_math_expression(self);
+        printer.result
+    }
+}
+
+impl fmt::Display for LogicalExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut printer = Printer::new(f);
+        printer.visit_logical_expression(self);
+        printer.result
+    }
+}
+
+impl fmt::Display for StringConcat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut printer = Printer::new(f);
+        printer.visit_string_concat(self);
+        printer.result
+    }
+}
+
+impl fmt::Display for TernaryExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut printer = Printer::new(f);
+        printer.visit_ternary_expression(self);
+        printer.result
+    }
+}
+
+impl fmt::Display for ExpressionValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut printer = Printer::new(f);
+        printer.visit_expression_value(self);
+        printer.result
+    }
+}
+
+impl fmt::Display for Expression {
+    /// Formats an expression in the expression language notation
+    ///
+    /// The output can be parsed again and the parsed expression is equal to this one. Except
+    /// strings containing all quote characters and function or keyword argument names which
+    /// are not valid identifiers, the expression language has no notation for them.
+    /// Parentheses are added only where they're required, quotes are normalized
+    /// (backquotes are preferred) and whitespace is normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::ast::*;
+    ///
+    /// let expression: Expression = "(1+(2*3))-( 4 - 5 ) | F(x='a')".parse().unwrap();
+    /// assert_eq!(expression.to_string(), "1 + 2 * 3 - (4 - 5) | F(x=`a`)");
+    /// assert_eq!(expression.to_string().parse::<Expression>().unwrap(), expression);
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut printer = Printer::new(f);
+        printer.visit_expression(self);
+        printer.result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::Expression;
+
+    fn assert_reprint(source: &str, printed: &str) {
+        let expression: Expression = source.parse().unwrap();
+        assert_eq!(expression.to_string(), printed);
+        assert_eq!(printed.parse::<Expression>().unwrap(), expression);
+    }
+
+    #[test]
+    fn primitives() {
+        assert_reprint("1", "1");
+        assert_reprint("-10", "-10");
+        assert_reprint("1.0", "1.0");
+        assert_reprint("-0.25", "-0.25");
+        assert_reprint("true", "true");
+        assert_reprint("\"a`b\"", "\"a`b\"");
+        assert_reprint("'abc'", "`abc`");
+    }
+
+    #[test]
+    fn minimal_parentheses() {
+        assert_reprint("(1 + 2) * 3", "(1 + 2) * 3");
+        assert_reprint("(1 * 2) + 3", "1 * 2 + 3");
+        assert_reprint("(1 - 2) - 3", "1 - 2 - 3");
+        assert_reprint("1 - (2 - 3)", "1 - (2 - 3)");
+        assert_reprint("1 / (2 * 3) % 4", "1 / (2 * 3) % 4");
+        assert_reprint("a - -1", "a - -1");
+    }
+
+    #[test]
+    fn filters() {
+        assert_reprint("1 + 2 | F | G(1, b=2)", "1 + 2 | F | G(1, b=2)");
+        assert_reprint("a | F * (b + c)", "a | F * (b + c)");
+        assert_reprint("a | F * b + c | G", "a | F * b + c | G");
+        assert_reprint("3 | POW(2, _)", "3 | POW(2, _)");
+        assert_reprint("3 | F()", "3 | F");
+        assert_reprint("_ | F(_, [`_`] + 1, [`_`].a)", "[`_`] | F(_, [`_`] + 1, [`_`].a)");
+    }
+
+    #[test]
+    fn logical() {
+        assert_reprint("not a and b > 1 or c | F == `x`", "not a and b > 1 or c | F == `x`");
+        assert_reprint("a < b == c", "a < b == c");
+    }
+
+    #[test]
+    fn ternary_and_concat() {
+        assert_reprint("(a > 1 ? b : c + 1) | F", "(a > 1 ? b : c + 1) | F");
+        assert_reprint("super.a ~ ' ' ~ 1 ~ this[`x y`]", "super.a ~ ` ` ~ 1 ~ this[`x y`]");
+    }
+
+    #[test]
+    fn identifiers() {
+        assert_reprint("[`order`].networks[0]", "[`order`].networks[0]");
+        assert_reprint("['first network'] ~ [0] ~ [a.b]", "[`first network`] ~ [0] ~ [a.b]");
+        assert_reprint("a[`this`][`_`]._", "a[`this`]._._");
+    }
+
+    #[test]
+    fn function_calls() {
+        assert_reprint("NOW()", "NOW()");
+        assert_reprint("IF(a == 1, b, c)", "IF(a == 1, b, c)");
+        assert_reprint("DATETIME(format=f, timestamp=t)", "DATETIME(format=f, timestamp=t)");
+    }
+}
@@ -0,0 +1,357 @@
+//! An expression AST traversal
+//!
+//! [`Visitor`] walks an expression by reference and [`VisitorMut`] walks an expression by
+//! mutable reference. Every node type has its own method. Default implementations visit all
+//! child nodes (by calling the `walk_*` function with the same name), override the method
+//! to handle the node and call the `walk_*` function from it if you'd like to visit child
+//! nodes as well.
+//!
+//! Filters are visited with the `visit_filter` method, not with the `visit_function_call`
+//! method. Both walk the function call arguments by default.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use balena_temen::ast::{visit::*, *};
+//!
+//! // Collects all function and filter names
+//! #[derive(Default)]
+//! struct Names<'ast> {
+//!     names: Vec<&'ast str>,
+//! }
+//!
+//! impl<'ast> Visitor<'ast> for Names<'ast> {
+//!     fn visit_function_call(&mut self, call: &'ast FunctionCall) {
+//!         self.names.push(&call.name);
+//!         walk_function_call(self, call);
+//!     }
+//!
+//!     fn visit_filter(&mut self, filter: &'ast FunctionCall) {
+//!         self.names.push(&filter.name);
+//!         walk_filter(self, filter);
+//!     }
+//! }
+//!
+//! let expression: Expression = "POW(a, 2) + b | ROUND(PRECISION())".parse().unwrap();
+//!
+//! let mut names = Names::default();
+//! names.visit_expression(&expression);
+//! assert_eq!(names.names, vec!["POW", "ROUND", "PRECISION"]);
+//! ```
+//!
+//! [`Visitor`]: trait.Visitor.html
+//! [`VisitorMut`]: trait.VisitorMut.html
+use super::*;
+
+/// An expression AST visitor (immutable)
+///
+/// Check the [module](index.html) documentation for more details.
+pub trait Visitor<'ast> {
+    /// Visits an expression, including its value and filters
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        walk_expression(self, expression);
+    }
+
+    /// Visits an expression value
+    fn visit_expression_value(&mut self, value: &'ast ExpressionValue) {
+        walk_expression_value(self, value);
+    }
+
+    /// Visits an integer
+    fn visit_integer(&mut self, _value: i64) {}
+
+    /// Visits a floating point
+    fn visit_float(&mut self, _value: f64) {}
+
+    /// Visits a boolean
+    fn visit_boolean(&mut self, _value: bool) {}
+
+    /// Visits a string
+    fn visit_string(&mut self, _value: &'ast str) {}
+
+    /// Visits a filter input placeholder (`_`)
+    fn visit_placeholder(&mut self) {}
+
+    /// Visits an identifier
+    fn visit_identifier(&mut self, identifier: &'ast Identifier) {
+        walk_identifier(self, identifier);
+    }
+
+    /// Visits an identifier value (component)
+    fn visit_identifier_value(&mut self, value: &'ast IdentifierValue) {
+        walk_identifier_value(self, value);
+    }
+
+    /// Visits a math expression
+    fn visit_math_expression(&mut self, math: &'ast MathExpression) {
+        walk_math_expression(self, math);
+    }
+
+    /// Visits a math operator
+    fn visit_math_operator(&mut self, _operator: MathOperator) {}
+
+    /// Visits a logical expression
+    fn visit_logical_expression(&mut self, logical: &'ast LogicalExpression) {
+        walk_logical_expression(self, logical);
+    }
+
+    /// Visits a logical operator
+    fn visit_logical_operator(&mut self, _operator: LogicalOperator) {}
+
+    /// Visits a function call (not a filter)
+    fn visit_function_call(&mut self, call: &'ast FunctionCall) {
+        walk_function_call(self, call);
+    }
+
+    /// Visits a filter
+    fn visit_filter(&mut self, filter: &'ast FunctionCall) {
+        walk_filter(self, filter);
+    }
+
+    /// Visits a string concatenation
+    fn visit_string_concat(&mut self, concat: &'ast StringConcat) {
+        walk_string_concat(self, concat);
+    }
+
+    /// Visits a ternary expression
+    fn visit_ternary_expression(&mut self, ternary: &'ast TernaryExpression) {
+        walk_ternary_expression(self, ternary);
+    }
+}
+
+/// Visits an expression value and all filters
+pub fn walk_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expression: &'ast Expression) {
+    visitor.visit_expression_value(&expression.value);
+    for filter in expression.filters.iter() {
+        visitor.visit_filter(filter);
+    }
+}
+
+/// Visits an expression value with the method matching the value type
+pub fn walk_expression_value<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, value: &'ast ExpressionValue) {
+    match value {
+        ExpressionValue::Integer(value) => visitor.visit_integer(*value),
+        ExpressionValue::Float(value) => visitor.visit_float(*value),
+        ExpressionValue::Boolean(value) => visitor.visit_boolean(*value),
+        ExpressionValue::String(ref value) => visitor.visit_string(value),
+        ExpressionValue::Identifier(ref identifier) => visitor.visit_identifier(identifier),
+        ExpressionValue::Math(ref math) => visitor.visit_math_expression(math),
+        ExpressionValue::Logical(ref logical) => visitor.visit_logical_expression(logical),
+        ExpressionValue::FunctionCall(ref call) => visitor.visit_function_call(call),
+        ExpressionValue::StringConcat(ref concat) => visitor.visit_string_concat(concat),
+        ExpressionValue::Ternary(ref ternary) => visitor.visit_ternary_expression(ternary),
+        ExpressionValue::Placeholder => visitor.visit_placeholder(),
+    };
+}
+
+/// Visits all identifier values
+pub fn walk_identifier<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, identifier: &'ast Identifier) {
+    for value in identifier.values.iter() {
+        visitor.visit_identifier_value(value);
+    }
+}
+
+/// Visits an identifier of an indirect lookup
+pub fn walk_identifier_value<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, value: &'ast IdentifierValue) {
+    if let IdentifierValue::Identifier(ref identifier) = value {
+        visitor.visit_identifier(identifier);
+    }
+}
+
+/// Visits a left-hand side, an operator and a right-hand side
+pub fn walk_math_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, math: &'ast MathExpression) {
+    visitor.visit_expression(&math.lhs);
+    visitor.visit_math_operator(math.operator);
+    visitor.visit_expression(&math.rhs);
+}
+
+/// Visits a left-hand side, an operator and a right-hand side
+pub fn walk_logical_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, logical: &'ast LogicalExpression) {
+    visitor.visit_expression(&logical.lhs);
+    visitor.visit_logical_operator(logical.operator);
+    visitor.visit_expression(&logical.rhs);
+}
+
+/// Visits positional and then named arguments
+pub fn walk_function_call<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, call: &'ast FunctionCall) {
+    for arg in call.args.iter().chain(call.kwargs.values()) {
+        visitor.visit_expression(arg);
+    }
+}
+
+/// Visits positional and then named filter arguments
+pub fn walk_filter<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, filter: &'ast FunctionCall) {
+    walk_function_call(visitor, filter);
+}
+
+/// Visits all concatenated values
+pub fn walk_string_concat<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, concat: &'ast StringConcat) {
+    for value in concat.values.iter() {
+        visitor.visit_expression_value(value);
+    }
+}
+
+/// Visits a condition, a truthy and a falsy expression
+pub fn walk_ternary_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, ternary: &'ast TernaryExpression) {
+    visitor.visit_expression(&ternary.condition);
+    visitor.visit_expression(&ternary.truthy);
+    visitor.visit_expression(&ternary.falsy);
+}
+
+/// An expression AST visitor (mutable)
+///
+/// Same as the [`Visitor`], but nodes are visited by mutable reference and can be modified
+/// in place. Check the [module](index.html) documentation for more details.
+///
+/// [`Visitor`]: trait.Visitor.html
+pub trait VisitorMut {
+    /// Visits an expression, including its value and filters
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        walk_expression_mut(self, expression);
+    }
+
+    /// Visits an expression value
+    fn visit_expression_value_mut(&mut self, value: &mut ExpressionValue) {
+        walk_expression_value_mut(self, value);
+    }
+
+    /// Visits an integer
+    fn visit_integer_mut(&mut self, _value: &mut i64) {}
+
+    /// Visits a floating point
+    fn visit_float_mut(&mut self, _value: &mut f64) {}
+
+    /// Visits a boolean
+    fn visit_boolean_mut(&mut self, _value: &mut bool) {}
+
+    /// Visits a string
+    fn visit_string_mut(&mut self, _value: &mut String) {}
+
+    /// Visits a filter input placeholder (`_`)
+    fn visit_placeholder_mut(&mut self) {}
+
+    /// Visits an identifier
+    fn visit_identifier_mut(&mut self, identifier: &mut Identifier) {
+        walk_identifier_mut(self, identifier);
+    }
+
+    /// Visits an identifier value (component)
+    fn visit_identifier_value_mut(&mut self, value: &mut IdentifierValue) {
+        walk_identifier_value_mut(self, value);
+    }
+
+    /// Visits a math expression
+    fn visit_math_expression_mut(&mut self, math: &mut MathExpression) {
+        walk_math_expression_mut(self, math);
+    }
+
+    /// Visits a math operator
+    fn visit_math_operator_mut(&mut self, _operator: &mut MathOperator) {}
+
+    /// Visits a logical expression
+    fn visit_logical_expression_mut(&mut self, logical: &mut LogicalExpression) {
+        walk_logical_expression_mut(self, logical);
+    }
+
+    /// Visits a logical operator
+    fn visit_logical_operator_mut(&mut self, _operator: &mut LogicalOperator) {}
+
+    /// Visits a function call (not a filter)
+    fn visit_function_call_mut(&mut self, call: &mut FunctionCall) {
+        walk_function_call_mut(self, call);
+    }
+
+    /// Visits a filter
+    fn visit_filter_mut(&mut self, filter: &mut FunctionCall) {
+        walk_filter_mut(self, filter);
+    }
+
+    /// Visits a string concatenation
+    fn visit_string_concat_mut(&mut self, concat: &mut StringConcat) {
+        walk_string_concat_mut(self, concat);
+    }
+
+    /// Visits a ternary expression
+    fn visit_ternary_expression_mut(&mut self, ternary: &mut TernaryExpression) {
+        walk_ternary_expression_mut(self, ternary);
+    }
+}
+
+/// Visits an expression value and all filters
+pub fn walk_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut Expression) {
+    visitor.visit_expression_value_mut(&mut expression.value);
+    for filter in expression.filters.iter_mut() {
+        visitor.visit_filter_mut(filter);
+    }
+}
+
+/// Visits an expression value with the method matching the value type
+pub fn walk_expression_value_mut<V: VisitorMut + ?Sized>(visitor: &mut V, value: &mut ExpressionValue) {
+    match value {
+        ExpressionValue::Integer(ref mut value) => visitor.visit_integer_mut(value),
+        ExpressionValue::Float(ref mut value) => visitor.visit_float_mut(value),
+        ExpressionValue::Boolean(ref mut value) => visitor.visit_boolean_mut(value),
+        ExpressionValue::String(ref mut value) => visitor.visit_string_mut(value),
+        ExpressionValue::Identifier(ref mut identifier) => visitor.visit_identifier_mut(identifier),
+        ExpressionValue::Math(ref mut math) => visitor.visit_math_expression_mut(math),
+        ExpressionValue::Logical(ref mut logical) => visitor.visit_logical_expression_mut(logical),
+        ExpressionValue::FunctionCall(ref mut call) => visitor.visit_function_call_mut(call),
+        ExpressionValue::StringConcat(ref mut concat) => visitor.visit_string_concat_mut(concat),
+        ExpressionValue::Ternary(ref mut ternary) => visitor.visit_ternary_expression_mut(ternary),
+        ExpressionValue::Placeholder => visitor.visit_placeholder_mut(),
+    };
+}
+
+/// Visits all identifier values
+pub fn walk_identifier_mut<V: VisitorMut + ?Sized>(visitor: &mut V, identifier: &mut Identifier) {
+    for value in identifier.values.iter_mut() {
+        visitor.visit_identifier_value_mut(value);
+    }
+}
+
+/// Visits an identifier of an indirect lookup
+pub fn walk_identifier_value_mut<V: VisitorMut + ?Sized>(visitor: &mut V, value: &mut IdentifierValue) {
+    if let IdentifierValue::Identifier(ref mut identifier) = value {
+        visitor.visit_identifier_mut(identifier);
+    }
+}
+
+/// Visits a left-hand side, an operator and a right-hand side
+pub fn walk_math_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, math: &mut MathExpression) {
+    visitor.visit_expression_mut(&mut math.lhs);
+    visitor.visit_math_operator_mut(&mut math.operator);
+    visitor.visit_expression_mut(&mut math.rhs);
+}
+
+/// Visits a left-hand side, an operator and a right-hand side
+pub fn walk_logical_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, logical: &mut LogicalExpression) {
+    visitor.visit_expression_mut(&mut logical.lhs);
+    visitor.visit_logical_operator_mut(&mut logical.operator);
+    visitor.visit_expression_mut(&mut logical.rhs);
+}
+
+/// Visits positional and then named arguments
+pub fn walk_function_call_mut<V: VisitorMut + ?Sized>(visitor: &mut V, call: &mut FunctionCall) {
+    for arg in call.args.iter_mut().chain(call.kwargs.values_mut()) {
+        visitor.visit_expression_mut(arg);
+    }
+}
+
+/// Visits positional and then named filter arguments
+pub fn walk_filter_mut<V: VisitorMut + ?Sized>(visitor: &mut V, filter: &mut FunctionCall) {
+    walk_function_call_mut(visitor, filter);
+}
+
+/// Visits all concatenated values
+pub fn walk_string_concat_mut<V: VisitorMut + ?Sized>(visitor: &mut V, concat: &mut StringConcat) {
+    for value in concat.values.iter_mut() {
+        visitor.visit_expression_value_mut(value);
+    }
+}
+
+/// Visits a condition, a truthy and a falsy expression
+pub fn walk_ternary_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, ternary: &mut TernaryExpression) {
+    visitor.visit_expression_mut(&mut ternary.condition);
+    visitor.visit_expression_mut(&mut ternary.truthy);
+    visitor.visit_expression_mut(&mut ternary.falsy);
+}
@@ -0,0 +1,412 @@
+use serde_json::{Map, Value};
+
+use crate::builtin::{Signature, ValueType};
+use crate::error::*;
+use crate::utils::validate_f64;
+
+/// Conversion of a function / filter argument from a JSON value
+///
+/// Implemented for `Value`, `bool`, `String`, `i64`, `u64`, `f64`, `Vec<T>`,
+/// `Map<String, Value>` and `Option<T>`. An optional argument accepts `null` and can be
+/// omitted.
+///
+/// # Examples
+///
+/// ```rust
+/// use balena_temen::{FromValue, ValueType};
+/// use serde_json::json;
+///
+/// assert_eq!(String::from_value(&json!("a")), Some("a".to_string()));
+/// assert_eq!(String::from_value(&json!(1)), None);
+/// assert_eq!(<Option<i64>>::from_value(&json!(null)), Some(None));
+/// assert_eq!(<Vec<bool>>::value_type(), ValueType::Array);
+/// ```
+pub trait FromValue: Sized {
+    /// Converts a value, returns `None` if the value is of a different type
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A value to convert
+    fn from_value(value: &Value) -> Option<Self>;
+
+    /// Converts a value which can be missing, returns `None` if the value is of a different
+    /// type or if it's missing and required
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A value to convert
+    fn from_optional_value(value: Option<&Value>) -> Option<Self> {
+        value.and_then(Self::from_value)
+    }
+
+    /// Expected value type
+    fn value_type() -> ValueType;
+
+    /// Is the value required?
+    fn is_required() -> bool {
+        true
+    }
+}
+
+/// Conversion of a function / filter result to a JSON value
+///
+/// Implemented for `Value`, `bool`, `String`, `i64`, `u64`, `f64`, `Vec<T>`,
+/// `Map<String, Value>`, `Option<T>` (`None` is converted to `null`) and `()` (`null`).
+///
+/// # Examples
+///
+/// ```rust
+/// use balena_temen::IntoValue;
+/// use serde_json::json;
+///
+/// assert_eq!(Some(1.5).into_value().unwrap(), json!(1.5));
+/// assert_eq!(vec!["a".to_string()].into_value().unwrap(), json!(["a"]));
+/// assert!(std::f64::NAN.into_value().is_err());
+/// ```
+pub trait IntoValue {
+    /// Converts to a value
+    ///
+    /// Fails if there's no JSON representation of the value (`NaN`, infinite floats).
+    fn into_value(self) -> Result<Value>;
+
+    /// Converted value type
+    fn value_type() -> ValueType;
+}
+
+macro_rules! impl_from_value {
+    ($t:ty, $value_type:expr, $convert:expr) => {
+        impl FromValue for $t {
+            fn from_value(value: &Value) -> Option<Self> {
+                $convert(value)
+            }
+
+            fn value_type() -> ValueType {
+                $value_type
+            }
+        }
+    };
+}
+
+impl_from_value!(Value, ValueType::Any, |x: &Value| Some(x.clone()));
+impl_from_value!(bool, ValueType::Boolean, Value::as_bool);
+impl_from_value!(String, ValueType::String, |x: &Value| x.as_str().map(str::to_string));
+impl_from_value!(i64, ValueType::Number, Value::as_i64);
+impl_from_value!(u64, ValueType::Number, Value::as_u64);
+impl_from_value!(f64, ValueType::Number, Value::as_f64);
+impl_from_value!(Map<String, Value>, ValueType::Object, |x: &Value| x.as_object().cloned());
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_array()?.iter().map(T::from_value).collect()
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Array
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Option<Self> {
+        if value.is_null() {
+            Some(None)
+        } else {
+            T::from_value(value).map(Some)
+        }
+    }
+
+    fn from_optional_value(value: Option<&Value>) -> Option<Self> {
+        match value {
+            Some(value) => Self::from_value(value),
+            None => Some(None),
+        }
+    }
+
+    fn value_type() -> ValueType {
+        T::value_type()
+    }
+
+    fn is_required() -> bool {
+        false
+    }
+}
+
+macro_rules! impl_into_value {
+    ($t:ty, $value_type:expr) => {
+        impl IntoValue for $t {
+            fn into_value(self) -> Result<Value> {
+                Ok(Value::from(self))
+            }
+
+            fn value_type() -> ValueType {
+                $value_type
+            }
+        }
+    };
+}
+
+impl_into_value!(Value, ValueType::Any);
+impl_into_value!(bool, ValueType::Boolean);
+impl_into_value!(String, ValueType::String);
+impl_into_value!(i64, ValueType::Number);
+impl_into_value!(u64, ValueType::Number);
+impl_into_value!(Map<String, Value>, ValueType::Object);
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Result<Value> {
+        Ok(Value::from(validate_f64(self)?))
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Number
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Result<Value> {
+        Ok(Value::Null)
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Null
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Result<Value> {
+        Ok(Value::Array(
+            self.into_iter().map(IntoValue::into_value).collect::<Result<_>>()?,
+        ))
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Array
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Result<Value> {
+        match self {
+            Some(x) => x.into_value(),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Any
+    }
+}
+
+// Names of typed function / filter arguments, they have no names in the Rust signature
+fn typed_arg_name(index: usize) -> String {
+    format!("arg{}", index + 1)
+}
+
+// Converts an argument, fails with the missing / invalid argument type error
+fn typed_arg<T: FromValue>(kind: &'static str, name: &str, args: &[Value], index: usize) -> Result<T> {
+    T::from_optional_value(args.get(index)).ok_or_else(|| match args.get(index) {
+        None => Error::new(ErrorKind::InvalidCall, "missing argument")
+            .context(kind, name.to_string())
+            .context("argument", typed_arg_name(index)),
+        Some(value) => Error::new(ErrorKind::TypeMismatch, "invalid argument type")
+            .context(kind, name.to_string())
+            .context("argument", typed_arg_name(index))
+            .context("argument value", value.to_string())
+            .context("expected", T::value_type().to_string()),
+    })
+}
+
+// Fails if there are more arguments than a typed function / filter accepts
+fn validate_typed_args_len(kind: &'static str, name: &str, args: &[Value], max: usize) -> Result<()> {
+    if args.len() > max {
+        return Err(Error::new(ErrorKind::InvalidCall, "invalid number of arguments")
+            .context(kind, name.to_string())
+            .context("argument count", format!("{}", args.len()))
+            .context("expected", format!("{}", max)));
+    }
+    Ok(())
+}
+
+/// A function with typed arguments and result
+///
+/// Implemented for functions and closures with up to four arguments, where all arguments
+/// implement [`FromValue`] and the result is `Result<R>`, where `R` implements [`IntoValue`].
+/// Optional (`Option<T>`) arguments must follow the required ones. Register it with the
+/// [`typed_function`] method.
+///
+/// [`FromValue`]: trait.FromValue.html
+/// [`IntoValue`]: trait.IntoValue.html
+/// [`typed_function`]: struct.EngineBuilder.html#method.typed_function
+pub trait TypedFunction<Args>: Send + Sync + 'static {
+    /// Converts arguments, calls the function and converts the result
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Function name (error context)
+    /// * `args` - List of arguments
+    fn call_function(&self, name: &str, args: &[Value]) -> Result<Value>;
+
+    /// Signature derived from the argument and result types
+    fn signature() -> Signature;
+}
+
+/// A filter with typed input, arguments and result
+///
+/// Implemented for functions and closures with the filter input and up to four arguments.
+/// All arguments must implement [`FromValue`] and the result is `Result<R>`, where `R`
+/// implements [`IntoValue`]. Optional (`Option<T>`) arguments must follow the required
+/// ones. Register it with the [`typed_filter`] method.
+///
+/// [`FromValue`]: trait.FromValue.html
+/// [`IntoValue`]: trait.IntoValue.html
+/// [`typed_filter`]: struct.EngineBuilder.html#method.typed_filter
+pub trait TypedFilter<Args>: Send + Sync + 'static {
+    /// Converts the input and arguments, calls the filter and converts the result
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Filter name (error context)
+    /// * `input` - An input value
+    /// * `args` - List of arguments
+    fn call_filter(&self, name: &str, input: &Value, args: &[Value]) -> Result<Value>;
+
+    /// Signature derived from the input, argument and result types
+    fn signature() -> Signature;
+}
+
+// Appends typed arguments to the signature
+fn typed_signature(signature: Signature, args: &[(ValueType, bool)]) -> Signature {
+    args.iter()
+        .enumerate()
+        .fold(signature, |signature, (index, (value_type, required))| {
+            if *required {
+                signature.arg(typed_arg_name(index), *value_type)
+            } else {
+                signature.optional_arg(typed_arg_name(index), *value_type)
+            }
+        })
+}
+
+macro_rules! impl_typed {
+    ($len:expr; $($arg:ident: $index:tt),*) => {
+        impl<F, R, $($arg,)*> TypedFunction<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> Result<R> + Send + Sync + 'static,
+            R: IntoValue,
+            $($arg: FromValue,)*
+        {
+            #[allow(unused_variables)]
+            fn call_function(&self, name: &str, args: &[Value]) -> Result<Value> {
+                validate_typed_args_len("function", name, args, $len)?;
+                let result = self($(typed_arg::<$arg>("function", name, args, $index)?),*)?;
+                result.into_value().context("function", name.to_string())
+            }
+
+            fn signature() -> Signature {
+                typed_signature(
+                    Signature::new(R::value_type()),
+                    &[$(($arg::value_type(), $arg::is_required())),*],
+                )
+            }
+        }
+
+        impl<F, I, R, $($arg,)*> TypedFilter<(I, $($arg,)*)> for F
+        where
+            F: Fn(I, $($arg),*) -> Result<R> + Send + Sync + 'static,
+            I: FromValue,
+            R: IntoValue,
+            $($arg: FromValue,)*
+        {
+            #[allow(unused_variables)]
+            fn call_filter(&self, name: &str, input: &Value, args: &[Value]) -> Result<Value> {
+                validate_typed_args_len("filter", name, args, $len)?;
+                let input = I::from_value(input).ok_or_else(|| {
+                    Error::new(ErrorKind::TypeMismatch, "invalid input type")
+                        .context("filter", name.to_string())
+                        .context("expected", I::value_type().to_string())
+                        .context("input", input.to_string())
+                })?;
+                let result = self(input, $(typed_arg::<$arg>("filter", name, args, $index)?),*)?;
+                result.into_value().context("filter", name.to_string())
+            }
+
+            fn signature() -> Signature {
+                typed_signature(
+                    Signature::new(R::value_type()).input(I::value_type()),
+                    &[$(($arg::value_type(), $arg::is_required())),*],
+                )
+            }
+        }
+    };
+}
+
+impl_typed!(0;);
+impl_typed!(1; A1: 0);
+impl_typed!(2; A1: 0, A2: 1);
+impl_typed!(3; A1: 0, A2: 1, A3: 2);
+impl_typed!(4; A1: 0, A2: 1, A3: 2, A4: 3);
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn greet(name: String, upper: Option<bool>) -> Result<String> {
+        let greeting = format!("Hello {}", name);
+        if upper.unwrap_or(false) {
+            Ok(greeting.to_uppercase())
+        } else {
+            Ok(greeting)
+        }
+    }
+
+    #[test]
+    fn call_typed_function() {
+        assert_eq!(greet.call_function("GREET", &[json!("a")]).unwrap(), json!("Hello a"));
+        assert_eq!(
+            greet.call_function("GREET", &[json!("a"), json!(true)]).unwrap(),
+            json!("HELLO A")
+        );
+        assert_eq!(
+            greet.call_function("GREET", &[json!("a"), json!(null)]).unwrap(),
+            json!("Hello a")
+        );
+    }
+
+    #[test]
+    fn fail_on_invalid_arguments() {
+        assert_eq!(
+            greet.call_function("GREET", &[]).unwrap_err().to_string(),
+            Error::with_message("missing argument")
+                .context("function", "GREET")
+                .context("argument", "arg1")
+                .to_string()
+        );
+        assert_eq!(
+            greet
+                .call_function("GREET", &[json!("a"), json!(1)])
+                .unwrap_err()
+                .to_string(),
+            Error::with_message("invalid argument type")
+                .context("function", "GREET")
+                .context("argument", "arg2")
+                .context("argument value", "1")
+                .context("expected", "boolean")
+                .to_string()
+        );
+        assert!(greet
+            .call_function("GREET", &[json!("a"), json!(true), json!(1)])
+            .is_err());
+    }
+
+    #[test]
+    fn derived_signature() {
+        let signature = <fn(String, Option<bool>) -> Result<String> as TypedFunction<_>>::signature();
+        assert_eq!(
+            signature,
+            Signature::new(ValueType::String)
+                .arg("arg1", ValueType::String)
+                .optional_arg("arg2", ValueType::Boolean)
+        );
+    }
+}
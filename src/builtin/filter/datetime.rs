@@ -1,20 +1,33 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, NaiveDateTime, Utc};
+#[cfg(feature = "datetime-tz")]
+use chrono_tz::Tz;
 use serde_json::Value;
 
+use crate::builtin::{get_arg, validate_args, Determinism, Metadata, Signature, ValueType};
 use crate::context::Context;
 use crate::error::*;
 
-fn format_timestamp(filter: &'static str, input: &Value, args: &[Value], default: &str) -> Result<Value> {
+fn format_timestamp(
+    filter: &'static str,
+    input: &Value,
+    args: &[Value],
+    kwargs: &HashMap<String, Value>,
+    default: &str,
+) -> Result<Value> {
+    validate_args(args, kwargs, &["format", "tz"]).context("filter", filter)?;
+
     let ts = input.as_i64().ok_or_else(|| {
-        Error::with_message("invalid input type")
+        Error::new(ErrorKind::TypeMismatch, "invalid input type")
             .context("filter", filter)
             .context("expected", "i64")
             .context("input", input.to_string())
     })?;
 
-    let format = match args.first() {
+    let format = match get_arg(args, kwargs, 0, "format").context("filter", filter)? {
         Some(x) => x.as_str().ok_or_else(|| {
-            Error::with_message("invalid argument type")
+            Error::new(ErrorKind::TypeMismatch, "invalid argument type")
                 .context("filter", filter)
                 .context("argument name", "format")
                 .context("argument value", x.to_string())
@@ -25,26 +38,96 @@ fn format_timestamp(filter: &'static str, input: &Value, args: &[Value], default
 
     let dt = DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(ts, 0), Utc);
 
-    Ok(Value::String(dt.format(format).to_string()))
+    match get_arg(args, kwargs, 1, "tz").context("filter", filter)? {
+        #[cfg(feature = "datetime-tz")]
+        Some(x) => {
+            let tz: Tz = x
+                .as_str()
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::TypeMismatch, "invalid argument type")
+                        .context("filter", filter)
+                        .context("argument name", "tz")
+                        .context("argument value", x.to_string())
+                        .context("expected", "string")
+                })?
+                .parse()
+                .map_err(|_| {
+                    Error::new(ErrorKind::InvalidValue, "unknown time zone")
+                        .context("filter", filter)
+                        .context("argument name", "tz")
+                        .context("argument value", x.to_string())
+                })?;
+            Ok(Value::String(dt.with_timezone(&tz).format(format).to_string()))
+        }
+        #[cfg(not(feature = "datetime-tz"))]
+        Some(x) => Err(Error::new(ErrorKind::InvalidCall, "time zones are not supported")
+            .context("filter", filter)
+            .context("argument name", "tz")
+            .context("argument value", x.to_string())
+            .context("feature", "datetime-tz")),
+        None => Ok(Value::String(dt.format(format).to_string())),
+    }
+}
+
+pub(crate) fn time(
+    input: &Value,
+    args: &[Value],
+    kwargs: &HashMap<String, Value>,
+    _context: &mut Context,
+) -> Result<Value> {
+    format_timestamp("TIME", input, args, kwargs, "%H:%M:%S")
+}
+
+pub(crate) fn date(
+    input: &Value,
+    args: &[Value],
+    kwargs: &HashMap<String, Value>,
+    _context: &mut Context,
+) -> Result<Value> {
+    format_timestamp("DATE", input, args, kwargs, "%Y-%m-%d")
+}
+
+pub(crate) fn datetime(
+    input: &Value,
+    args: &[Value],
+    kwargs: &HashMap<String, Value>,
+    _context: &mut Context,
+) -> Result<Value> {
+    format_timestamp("DATETIME", input, args, kwargs, "%Y-%m-%dT%H:%M:%S%:z")
+}
+
+fn format_timestamp_metadata(description: &str) -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(
+            Signature::new(ValueType::String)
+                .input(ValueType::Number)
+                .optional_arg("format", ValueType::String)
+                .optional_arg("tz", ValueType::String),
+        )
+        .description(description)
 }
 
-pub(crate) fn time(input: &Value, args: &[Value], _context: &mut Context) -> Result<Value> {
-    format_timestamp("TIME", input, args, "%H:%M:%S")
+pub(crate) fn time_metadata() -> Metadata {
+    format_timestamp_metadata("Formats a timestamp as a time")
 }
 
-pub(crate) fn date(input: &Value, args: &[Value], _context: &mut Context) -> Result<Value> {
-    format_timestamp("DATE", input, args, "%Y-%m-%d")
+pub(crate) fn date_metadata() -> Metadata {
+    format_timestamp_metadata("Formats a timestamp as a date")
 }
 
-pub(crate) fn datetime(input: &Value, args: &[Value], _context: &mut Context) -> Result<Value> {
-    format_timestamp("DATETIME", input, args, "%Y-%m-%dT%H:%M:%S%:z")
+pub(crate) fn datetime_metadata() -> Metadata {
+    format_timestamp_metadata("Formats a timestamp as a date and time")
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use serde_json::json;
 
     use crate::context::Context;
+    #[cfg(not(feature = "datetime-tz"))]
+    use crate::error::ErrorKind;
 
     use super::{date, datetime, format_timestamp, time};
 
@@ -52,14 +135,20 @@ mod tests {
     fn time_default_format() {
         let mut ctx = Context::default();
 
-        assert_eq!(time(&json!(1_541_485_381), &[], &mut ctx).unwrap(), json!("06:23:01"));
+        assert_eq!(
+            time(&json!(1_541_485_381), &[], &HashMap::new(), &mut ctx).unwrap(),
+            json!("06:23:01")
+        );
     }
 
     #[test]
     fn date_default_format() {
         let mut ctx = Context::default();
 
-        assert_eq!(date(&json!(1_541_485_381), &[], &mut ctx).unwrap(), json!("2018-11-06"));
+        assert_eq!(
+            date(&json!(1_541_485_381), &[], &HashMap::new(), &mut ctx).unwrap(),
+            json!("2018-11-06")
+        );
     }
 
     #[test]
@@ -67,7 +156,7 @@ mod tests {
         let mut ctx = Context::default();
 
         assert_eq!(
-            datetime(&json!(1_541_485_381), &[], &mut ctx).unwrap(),
+            datetime(&json!(1_541_485_381), &[], &HashMap::new(), &mut ctx).unwrap(),
             json!("2018-11-06T06:23:01+00:00")
         );
     }
@@ -77,37 +166,37 @@ mod tests {
         let args = vec![json!("%Y")];
 
         assert_eq!(
-            format_timestamp("", &json!(1_541_485_381), &args, "").unwrap(),
+            format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").unwrap(),
             json!("2018")
         );
 
         let args = vec![json!("%m")];
         assert_eq!(
-            format_timestamp("", &json!(1_541_485_381), &args, "").unwrap(),
+            format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").unwrap(),
             json!("11")
         );
 
         let args = vec![json!("%d")];
         assert_eq!(
-            format_timestamp("", &json!(1_541_485_381), &args, "").unwrap(),
+            format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").unwrap(),
             json!("06")
         );
 
         let args = vec![json!("%H")];
         assert_eq!(
-            format_timestamp("", &json!(1_541_485_381), &args, "").unwrap(),
+            format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").unwrap(),
             json!("06")
         );
 
         let args = vec![json!("%M")];
         assert_eq!(
-            format_timestamp("", &json!(1_541_485_381), &args, "").unwrap(),
+            format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").unwrap(),
             json!("23")
         );
 
         let args = vec![json!("%S")];
         assert_eq!(
-            format_timestamp("", &json!(1_541_485_381), &args, "").unwrap(),
+            format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").unwrap(),
             json!("01")
         );
     }
@@ -115,18 +204,77 @@ mod tests {
     #[test]
     fn fail_on_invalid_format_argument_type() {
         let args = vec![json!(1)];
-        assert!(format_timestamp("", &json!(1_541_485_381), &args, "").is_err());
+        assert!(format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").is_err());
 
         let args = vec![json!(1.2)];
-        assert!(format_timestamp("", &json!(1_541_485_381), &args, "").is_err());
+        assert!(format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").is_err());
 
         let args = vec![json!(true)];
-        assert!(format_timestamp("", &json!(1_541_485_381), &args, "").is_err());
+        assert!(format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").is_err());
 
         let args = vec![json!(["a", "b"])];
-        assert!(format_timestamp("", &json!(1_541_485_381), &args, "").is_err());
+        assert!(format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").is_err());
 
         let args = vec![json!({"a": "b"})];
-        assert!(format_timestamp("", &json!(1_541_485_381), &args, "").is_err());
+        assert!(format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").is_err());
+    }
+
+    #[test]
+    fn format_named_argument() {
+        let mut kwargs = HashMap::new();
+        kwargs.insert("format".to_string(), json!("%Y"));
+
+        assert_eq!(
+            format_timestamp("", &json!(1_541_485_381), &[], &kwargs, "").unwrap(),
+            json!("2018")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "datetime-tz")]
+    fn tz_argument() {
+        let args = vec![json!("%H:%M %Z"), json!("Europe/Prague")];
+        assert_eq!(
+            format_timestamp("", &json!(1_541_485_381), &args, &HashMap::new(), "").unwrap(),
+            json!("07:23 CET")
+        );
+
+        let mut kwargs = HashMap::new();
+        kwargs.insert("tz".to_string(), json!("America/New_York"));
+        assert_eq!(
+            format_timestamp("", &json!(1_541_485_381), &[], &kwargs, "%Y-%m-%d %H:%M").unwrap(),
+            json!("2018-11-06 01:23")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "datetime-tz")]
+    fn fail_on_invalid_tz_argument() {
+        let mut kwargs = HashMap::new();
+        kwargs.insert("tz".to_string(), json!("Europe/Atlantis"));
+        assert!(format_timestamp("", &json!(1_541_485_381), &[], &kwargs, "").is_err());
+
+        let mut kwargs = HashMap::new();
+        kwargs.insert("tz".to_string(), json!(1));
+        assert!(format_timestamp("", &json!(1_541_485_381), &[], &kwargs, "").is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "datetime-tz"))]
+    fn fail_on_tz_argument_without_time_zones() {
+        let mut kwargs = HashMap::new();
+        kwargs.insert("tz".to_string(), json!("Europe/Prague"));
+        let error = format_timestamp("DATE", &json!(1_541_485_381), &[], &kwargs, "").unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidCall);
+        assert_eq!(error.message(), "time zones are not supported");
+        assert_eq!(error.context_value("feature"), Some("datetime-tz"));
+    }
+
+    #[test]
+    fn fail_on_unknown_named_argument() {
+        let mut kwargs = HashMap::new();
+        kwargs.insert("timezone".to_string(), json!("Europe/Prague"));
+        assert!(format_timestamp("", &json!(1_541_485_381), &[], &kwargs, "").is_err());
     }
 }
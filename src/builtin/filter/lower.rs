@@ -1,11 +1,12 @@
 use serde_json::Value;
 
+use crate::builtin::{Determinism, Metadata, Signature, ValueType};
 use crate::context::Context;
 use crate::error::*;
 
 pub(crate) fn lower(input: &Value, _args: &[Value], _context: &mut Context) -> Result<Value> {
     let s = input.as_str().ok_or_else(|| {
-        Error::with_message("invalid input type")
+        Error::new(ErrorKind::TypeMismatch, "invalid input type")
             .context("filter", "LOWER")
             .context("expected", "string")
             .context("input", input.to_string())
@@ -13,6 +14,12 @@ pub(crate) fn lower(input: &Value, _args: &[Value], _context: &mut Context) -> R
     Ok(Value::String(s.to_lowercase()))
 }
 
+pub(crate) fn lower_metadata() -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(Signature::new(ValueType::String).input(ValueType::String))
+        .description("Converts a string to lowercase")
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
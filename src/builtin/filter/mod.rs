@@ -1,23 +1,31 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use serde_json::Value;
 
 use crate::context::Context;
-use crate::error::Result;
+use crate::error::{Error, ErrorKind, Result};
 
-pub(crate) use self::datetime::{date, datetime, time};
-pub(crate) use self::lower::lower;
-pub(crate) use self::slugify::slugify;
-pub(crate) use self::trim::trim;
-pub(crate) use self::upper::upper;
+#[cfg(feature = "datetime")]
+pub(crate) use self::datetime::{date, date_metadata, datetime, datetime_metadata, time, time_metadata};
+pub(crate) use self::lower::{lower, lower_metadata};
+#[cfg(feature = "slugify")]
+pub(crate) use self::slugify::{slugify, slugify_metadata};
+pub(crate) use self::trim::{trim, trim_metadata};
+pub(crate) use self::upper::{upper, upper_metadata};
 
+#[cfg(feature = "datetime")]
 mod datetime;
 mod lower;
+#[cfg(feature = "slugify")]
 mod slugify;
 mod trim;
 mod upper;
 
 /// Evaluation engine filter signature
 ///
-/// You can register custom filter with the [`filter`] method.
+/// You can register custom filter with the [`filter`] method. Closures with the same
+/// signature, which can capture their own state, are accepted as well.
 ///
 /// # Arguments
 ///
@@ -45,3 +53,67 @@ mod upper;
 /// [`Engine`]: struct.Engine.html
 /// [`filter`]: struct.EngineBuilder.html#method.filter
 pub type FilterFn = fn(input: &Value, args: &[Value], context: &mut Context) -> Result<Value>;
+
+/// Evaluation engine filter signature with named arguments support
+///
+/// You can register custom filter with the [`filter_with_kwargs`] method. Closures with
+/// the same signature are accepted as well.
+///
+/// # Arguments
+///
+/// * `input` - An input value
+/// * `args` - List of positional arguments
+/// * `kwargs` - Named arguments
+/// * `context` - An evaluation context
+///
+/// # Examples
+///
+/// `1541485381 | DATE(format="%Y", tz="Europe/Prague")`
+///
+/// * `1541485381` - filter `input`
+/// * `DATE` - filter name
+/// * `args` - empty slice
+/// * `kwargs` - contains the `format` key with the `Value::String("%Y")` value and the `tz`
+///   key with the `Value::String("Europe/Prague")` value
+///
+/// [`filter_with_kwargs`]: struct.EngineBuilder.html#method.filter_with_kwargs
+pub type FilterKwargsFn =
+    fn(input: &Value, args: &[Value], kwargs: &HashMap<String, Value>, context: &mut Context) -> Result<Value>;
+
+// Registered filters are stored as shared closures, they can capture their own state
+pub(crate) type BoxedFilterFn = Arc<dyn Fn(&Value, &[Value], &mut Context) -> Result<Value> + Send + Sync>;
+pub(crate) type BoxedFilterKwargsFn =
+    Arc<dyn Fn(&Value, &[Value], &HashMap<String, Value>, &mut Context) -> Result<Value> + Send + Sync>;
+
+/// Registered filter
+#[derive(Clone)]
+pub(crate) enum Filter {
+    /// Filter accepting positional arguments only
+    Positional(BoxedFilterFn),
+    /// Filter accepting positional and named arguments
+    Kwargs(BoxedFilterKwargsFn),
+}
+
+impl Filter {
+    pub(crate) fn call(
+        &self,
+        name: &str,
+        input: &Value,
+        args: &[Value],
+        kwargs: &HashMap<String, Value>,
+        context: &mut Context,
+    ) -> Result<Value> {
+        match self {
+            Filter::Positional(f) => {
+                if !kwargs.is_empty() {
+                    return Err(
+                        Error::new(ErrorKind::InvalidCall, "filter does not accept named arguments")
+                            .context("filter", name.to_string()),
+                    );
+                }
+                f(input, args, context)
+            }
+            Filter::Kwargs(f) => f(input, args, kwargs, context),
+        }
+    }
+}
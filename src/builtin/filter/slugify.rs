@@ -2,11 +2,12 @@ use serde_json::Value;
 use slug;
 
 use crate::context::Context;
+use crate::builtin::{Determinism, Metadata, Signature, ValueType};
 use crate::error::*;
 
 pub(crate) fn slugify(input: &Value, _args: &[Value], _context: &mut Context) -> Result<Value> {
     let s = input.as_str().ok_or_else(|| {
-        Error::with_message("invalid input type")
+        Error::new(ErrorKind::TypeMismatch, "invalid input type")
             .context("filter", "SLUGIFY")
             .context("expected", "string")
             .context("input", input.to_string())
@@ -18,7 +19,7 @@ pub(crate) fn slugify(input: &Value, _args: &[Value], _context: &mut Context) ->
 
     let result = slug::slugify(s);
     if result.is_empty() {
-        return Err(Error::with_message("empty result, unable to slugify input")
+        return Err(Error::new(ErrorKind::InvalidValue, "empty result, unable to slugify input")
             .context("filter", "SLUGIFY")
             .context("input", input.to_string()));
     }
@@ -26,6 +27,12 @@ pub(crate) fn slugify(input: &Value, _args: &[Value], _context: &mut Context) ->
     Ok(Value::String(result))
 }
 
+pub(crate) fn slugify_metadata() -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(Signature::new(ValueType::String).input(ValueType::String))
+        .description("Converts a string to a slug")
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -1,11 +1,12 @@
 use serde_json::Value;
 
+use crate::builtin::{Determinism, Metadata, Signature, ValueType};
 use crate::context::Context;
 use crate::error::*;
 
 pub(crate) fn trim(input: &Value, _args: &[Value], _context: &mut Context) -> Result<Value> {
     let s = input.as_str().ok_or_else(|| {
-        Error::with_message("invalid input type")
+        Error::new(ErrorKind::TypeMismatch, "invalid input type")
             .context("filter", "TRIM")
             .context("expected", "string")
             .context("input", input.to_string())
@@ -14,6 +15,12 @@ pub(crate) fn trim(input: &Value, _args: &[Value], _context: &mut Context) -> Re
     Ok(Value::String(s.trim().to_string()))
 }
 
+pub(crate) fn trim_metadata() -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(Signature::new(ValueType::String).input(ValueType::String))
+        .description("Removes leading and trailing whitespace")
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -1,11 +1,12 @@
 use serde_json::Value;
 
+use crate::builtin::{Determinism, Metadata, Signature, ValueType};
 use crate::context::Context;
 use crate::error::*;
 
 pub(crate) fn upper(input: &Value, _args: &[Value], _context: &mut Context) -> Result<Value> {
     let s = input.as_str().ok_or_else(|| {
-        Error::with_message("invalid input type")
+        Error::new(ErrorKind::TypeMismatch, "invalid input type")
             .context("filter", "UPPER")
             .context("expected", "string")
             .context("input", input.to_string())
@@ -13,6 +14,12 @@ pub(crate) fn upper(input: &Value, _args: &[Value], _context: &mut Context) -> R
     Ok(Value::String(s.to_uppercase()))
 }
 
+pub(crate) fn upper_metadata() -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(Signature::new(ValueType::String).input(ValueType::String))
+        .description("Converts a string to uppercase")
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
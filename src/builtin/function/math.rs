@@ -1,12 +1,15 @@
+use std::convert::TryFrom;
+
 use serde_json::{Number, Value};
 
+use crate::builtin::{Determinism, Metadata, Signature, ValueType};
 use crate::context::Context;
-use crate::error::{Error, Result};
+use crate::error::{Error, ErrorKind, Result};
 
 fn validate_args_len(args: &[Value], min: Option<usize>, max: Option<usize>, name: &'static str) -> Result<()> {
     if let Some(min) = min {
         if args.len() < min {
-            return Err(Error::with_message("invalid number of arguments")
+            return Err(Error::new(ErrorKind::InvalidCall, "invalid number of arguments")
                 .context("function", name)
                 .context("argument count", format!("{}", args.len()))
                 .context("expected", format!("{}", min)));
@@ -14,7 +17,7 @@ fn validate_args_len(args: &[Value], min: Option<usize>, max: Option<usize>, nam
     }
     if let Some(max) = max {
         if args.len() > max {
-            return Err(Error::with_message("invalid number of arguments")
+            return Err(Error::new(ErrorKind::InvalidCall, "invalid number of arguments")
                 .context("function", name)
                 .context("argument count", format!("{}", args.len()))
                 .context("expected", format!("{}", max)));
@@ -29,7 +32,7 @@ where
 {
     args.get(index)
         .and_then(f)
-        .ok_or_else(|| Error::with_message("invalid argument type"))
+        .ok_or_else(|| Error::new(ErrorKind::TypeMismatch, "invalid argument type"))
 }
 
 fn get_two_args<T1, T2, F1, F2>(args: &[Value], f1: F1, f2: F2) -> Result<(T1, T2)>
@@ -40,24 +43,27 @@ where
     let r1 = args
         .get(0)
         .and_then(f1)
-        .ok_or_else(|| Error::with_message("invalid argument type"))?;
+        .ok_or_else(|| Error::new(ErrorKind::TypeMismatch, "invalid argument type"))?;
     let r2 = args
         .get(1)
         .and_then(f2)
-        .ok_or_else(|| Error::with_message("invalid argument type"))?;
+        .ok_or_else(|| Error::new(ErrorKind::TypeMismatch, "invalid argument type"))?;
     Ok((r1, r2))
 }
 
 pub(crate) fn pow(args: &[Value], _context: &mut Context) -> Result<Value> {
     validate_args_len(args, Some(2), Some(2), "POW")?;
 
+    // Integer overflow falls back to the floating point arithmetic
     if let Ok((b, e)) = get_two_args(args, Value::as_i64, Value::as_u64) {
-        return Ok(Value::from(b.pow(e as u32)));
+        if let Some(x) = u32::try_from(e).ok().and_then(|e| b.checked_pow(e)) {
+            return Ok(Value::from(x));
+        }
     }
 
     let (b, e) = get_two_args(args, Value::as_f64, Value::as_f64)?;
     Ok(Value::Number(Number::from_f64(b.powf(e)).ok_or_else(|| {
-        Error::with_message("expressions results to NaN").context("function", "POW")
+        Error::new(ErrorKind::Arithmetic, "expressions results to NaN").context("function", "POW")
     })?))
 }
 
@@ -66,7 +72,7 @@ pub(crate) fn log10(args: &[Value], _context: &mut Context) -> Result<Value> {
 
     let x = get_arg(args, 0, Value::as_f64)?;
     Ok(Value::Number(Number::from_f64(x.log10()).ok_or_else(|| {
-        Error::with_message("expressions results to NaN").context("function", "LOG10")
+        Error::new(ErrorKind::Arithmetic, "expressions results to NaN").context("function", "LOG10")
     })?))
 }
 
@@ -74,17 +80,23 @@ pub(crate) fn max(args: &[Value], _context: &mut Context) -> Result<Value> {
     validate_args_len(args, Some(1), None, "MAX")?;
 
     if let Ok(result) = args.iter().try_fold(std::i64::MIN, |acc, x| -> Result<i64> {
-        Ok(acc.max(x.as_i64().ok_or_else(|| Error::with_message("invalid argument"))?))
+        Ok(acc.max(
+            x.as_i64()
+                .ok_or_else(|| Error::new(ErrorKind::TypeMismatch, "invalid argument"))?,
+        ))
     }) {
         return Ok(Value::Number(Number::from(result)));
     }
 
     let result = args.iter().try_fold(std::f64::MIN, |acc, x| -> Result<f64> {
-        Ok(acc.max(x.as_f64().ok_or_else(|| Error::with_message("invalid argument"))?))
+        Ok(acc.max(
+            x.as_f64()
+                .ok_or_else(|| Error::new(ErrorKind::TypeMismatch, "invalid argument"))?,
+        ))
     })?;
 
     Ok(Value::Number(Number::from_f64(result).ok_or_else(|| {
-        Error::with_message("expressions results to NaN").context("function", "MAX")
+        Error::new(ErrorKind::Arithmetic, "expressions results to NaN").context("function", "MAX")
     })?))
 }
 
@@ -92,20 +104,62 @@ pub(crate) fn min(args: &[Value], _context: &mut Context) -> Result<Value> {
     validate_args_len(args, Some(1), None, "MIN")?;
 
     if let Ok(result) = args.iter().try_fold(std::i64::MAX, |acc, x| -> Result<i64> {
-        Ok(acc.min(x.as_i64().ok_or_else(|| Error::with_message("invalid argument"))?))
+        Ok(acc.min(
+            x.as_i64()
+                .ok_or_else(|| Error::new(ErrorKind::TypeMismatch, "invalid argument"))?,
+        ))
     }) {
         return Ok(Value::Number(Number::from(result)));
     }
 
     let result = args.iter().try_fold(std::f64::MAX, |acc, x| -> Result<f64> {
-        Ok(acc.min(x.as_f64().ok_or_else(|| Error::with_message("invalid argument"))?))
+        Ok(acc.min(
+            x.as_f64()
+                .ok_or_else(|| Error::new(ErrorKind::TypeMismatch, "invalid argument"))?,
+        ))
     })?;
 
     Ok(Value::Number(Number::from_f64(result).ok_or_else(|| {
-        Error::with_message("expressions results to NaN").context("function", "MIN")
+        Error::new(ErrorKind::Arithmetic, "expressions results to NaN").context("function", "MIN")
     })?))
 }
 
+pub(crate) fn pow_metadata() -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(
+            Signature::new(ValueType::Number)
+                .arg("base", ValueType::Number)
+                .arg("exponent", ValueType::Number),
+        )
+        .description("Raises `base` to the power of `exponent`")
+}
+
+pub(crate) fn log10_metadata() -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(Signature::new(ValueType::Number).arg("value", ValueType::Number))
+        .description("Returns the base 10 logarithm of `value`")
+}
+
+pub(crate) fn max_metadata() -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(
+            Signature::new(ValueType::Number)
+                .arg("value", ValueType::Number)
+                .variadic(ValueType::Number),
+        )
+        .description("Returns the largest argument")
+}
+
+pub(crate) fn min_metadata() -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(
+            Signature::new(ValueType::Number)
+                .arg("value", ValueType::Number)
+                .variadic(ValueType::Number),
+        )
+        .description("Returns the smallest argument")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -215,6 +269,15 @@ mod tests {
         assert_eq!(result, 343);
     }
 
+    #[test]
+    fn pow_10_20_overflow() {
+        let mut ctx = Context::default();
+        let args = vec![Value::Number(Number::from(10)), Value::Number(Number::from(20))];
+
+        let result = pow(&args, &mut ctx).unwrap().as_f64().unwrap();
+        assert_relative_eq!(result, 1e20);
+    }
+
     #[test]
     fn pow_4_0_5() {
         let mut ctx = Context::default();
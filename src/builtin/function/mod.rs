@@ -1,18 +1,28 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use serde_json::Value;
 
+use crate::ast::Expression;
 use crate::context::Context;
-use crate::error::Result;
+use crate::error::{Error, ErrorKind, Result};
 
-pub(crate) use self::now::now;
-pub(crate) use self::uuidv4::uuidv4;
+#[cfg(feature = "datetime")]
+pub(crate) use self::now::{now, now_metadata};
+#[cfg(feature = "uuid")]
+pub(crate) use self::uuidv4::{uuidv4, uuidv4_metadata};
 
 pub(crate) mod math;
+#[cfg(feature = "datetime")]
 mod now;
+pub(crate) mod special;
+#[cfg(feature = "uuid")]
 mod uuidv4;
 
 /// Evaluation engine function signature
 ///
-/// You can register custom function with the [`function`] method.
+/// You can register custom function with the [`function`] method. Closures with the same
+/// signature, which can capture their own state, are accepted as well.
 ///
 /// # Arguments
 ///
@@ -37,3 +47,106 @@ mod uuidv4;
 /// [`Engine`]: struct.Engine.html
 /// [`function`]: struct.EngineBuilder.html#method.function
 pub type FunctionFn = fn(args: &[Value], context: &mut Context) -> Result<Value>;
+
+/// Evaluation engine function signature with named arguments support
+///
+/// You can register custom function with the [`function_with_kwargs`] method. Closures with
+/// the same signature are accepted as well.
+///
+/// # Arguments
+///
+/// * `args` - List of positional arguments
+/// * `kwargs` - Named arguments
+/// * `context` - An evaluation context
+///
+/// # Examples
+///
+/// `NOW(timestamp=true)`:
+///
+/// * `NOW` - function name
+/// * `args` - empty slice
+/// * `kwargs` - contains the `timestamp` key with the `Value::Bool(true)` value
+///
+/// `NOW(true)`
+///
+/// * `NOW` - function name
+/// * `args` - first slice element contains the `Value::Bool(true)` value
+/// * `kwargs` - empty map
+///
+/// [`function_with_kwargs`]: struct.EngineBuilder.html#method.function_with_kwargs
+pub type FunctionKwargsFn = fn(args: &[Value], kwargs: &HashMap<String, Value>, context: &mut Context) -> Result<Value>;
+
+/// Evaluation engine special form signature
+///
+/// Special form is a function which receives unevaluated arguments. It's up to the special
+/// form to decide which arguments (if any) are evaluated and in which order. Named arguments
+/// are not supported.
+///
+/// You can register custom special form with the [`special_form`] method. Closures with
+/// the same signature are accepted as well.
+///
+/// # Arguments
+///
+/// * `args` - List of unevaluated positional arguments
+/// * `eval` - A callback evaluating an argument (with the same position, data and context
+///   the special form was called with)
+///
+/// # Examples
+///
+/// `TRY(wifi.ssid, "Balena")`:
+///
+/// * `TRY` - special form name
+/// * `args` - slice contains `wifi.ssid` and `"Balena"` expressions
+/// * `eval(&args[1])` - returns the `Value::String("Balena")` value
+///
+/// Visit [`special_form`] method documentation to see how to register custom special form
+/// and how it should look like.
+///
+/// [`special_form`]: struct.EngineBuilder.html#method.special_form
+pub type SpecialFormFn = fn(args: &[Expression], eval: &mut dyn FnMut(&Expression) -> Result<Value>) -> Result<Value>;
+
+// Registered functions are stored as shared closures, they can capture their own state
+pub(crate) type BoxedFunctionFn = Arc<dyn Fn(&[Value], &mut Context) -> Result<Value> + Send + Sync>;
+pub(crate) type BoxedFunctionKwargsFn =
+    Arc<dyn Fn(&[Value], &HashMap<String, Value>, &mut Context) -> Result<Value> + Send + Sync>;
+pub(crate) type BoxedSpecialFormFn =
+    Arc<dyn Fn(&[Expression], &mut dyn FnMut(&Expression) -> Result<Value>) -> Result<Value> + Send + Sync>;
+
+/// Registered function
+#[derive(Clone)]
+pub(crate) enum Function {
+    /// Function accepting positional arguments only
+    Positional(BoxedFunctionFn),
+    /// Function accepting positional and named arguments
+    Kwargs(BoxedFunctionKwargsFn),
+    /// Function accepting unevaluated positional arguments
+    SpecialForm(BoxedSpecialFormFn),
+}
+
+impl Function {
+    pub(crate) fn call(
+        &self,
+        name: &str,
+        args: &[Value],
+        kwargs: &HashMap<String, Value>,
+        context: &mut Context,
+    ) -> Result<Value> {
+        match self {
+            Function::Positional(f) => {
+                if !kwargs.is_empty() {
+                    return Err(
+                        Error::new(ErrorKind::InvalidCall, "function does not accept named arguments")
+                            .context("function", name.to_string()),
+                    );
+                }
+                f(args, context)
+            }
+            Function::Kwargs(f) => f(args, kwargs, context),
+            Function::SpecialForm(_) => Err(Error::new(
+                ErrorKind::InvalidCall,
+                "special form requires unevaluated arguments",
+            )
+            .context("function", name.to_string())),
+        }
+    }
+}
@@ -1,16 +1,23 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde_json::{Number, Value};
 
+use crate::builtin::{get_arg, validate_args, Determinism, Metadata, Signature, ValueType};
 use crate::context::Context;
 use crate::error::*;
 
-fn now_with_cached(cached: DateTime<Utc>, args: &[Value]) -> Result<Value> {
-    let timestamp = args.get(0).unwrap_or_else(|| &Value::Bool(false));
+fn now_with_cached(cached: DateTime<Utc>, args: &[Value], kwargs: &HashMap<String, Value>) -> Result<Value> {
+    validate_args(args, kwargs, &["timestamp"]).context("function", "NOW")?;
+
+    let timestamp = get_arg(args, kwargs, 0, "timestamp")
+        .context("function", "NOW")?
+        .unwrap_or(&Value::Bool(false));
 
     let timestamp = timestamp.as_bool().ok_or_else(|| {
-        Error::with_message("invalid argument type")
+        Error::new(ErrorKind::TypeMismatch, "invalid argument type")
             .context("function", "NOW")
-            .context("argument", "1st")
+            .context("argument", "timestamp")
             .context("argument value", timestamp.to_string())
             .context("expected", "boolean")
     })?;
@@ -22,12 +29,20 @@ fn now_with_cached(cached: DateTime<Utc>, args: &[Value]) -> Result<Value> {
     }
 }
 
-pub(crate) fn now(args: &[Value], context: &mut Context) -> Result<Value> {
-    now_with_cached(context.cached_now(), args)
+pub(crate) fn now(args: &[Value], kwargs: &HashMap<String, Value>, context: &mut Context) -> Result<Value> {
+    now_with_cached(context.cached_now(), args, kwargs)
+}
+
+pub(crate) fn now_metadata() -> Metadata {
+    Metadata::new(Determinism::ContextDependent)
+        .signature(Signature::new(ValueType::Any).optional_arg("timestamp", ValueType::Boolean))
+        .description("Returns the current date and time, or the timestamp if `timestamp` is true")
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use chrono::{DateTime, NaiveDateTime, Utc};
     use serde_json::json;
 
@@ -38,35 +53,74 @@ mod tests {
     #[test]
     fn subsequent_call_must_return_same_value() {
         let mut ctx = Context::default();
-        assert_eq!(now(&[], &mut ctx).unwrap(), now(&[], &mut ctx).unwrap());
+        let kwargs = HashMap::new();
+        assert_eq!(
+            now(&[], &kwargs, &mut ctx).unwrap(),
+            now(&[], &kwargs, &mut ctx).unwrap()
+        );
     }
 
     #[test]
     fn default_argument_values() {
         let mut ctx = Context::default();
         let args = vec![json!(false)];
-        assert_eq!(now(&[], &mut ctx).unwrap(), now(&args, &mut ctx).unwrap());
+        let kwargs = HashMap::new();
+        assert_eq!(
+            now(&[], &kwargs, &mut ctx).unwrap(),
+            now(&args, &kwargs, &mut ctx).unwrap()
+        );
     }
 
     #[test]
     fn utc_rfc3339_as_default() {
         let dt = DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(1_541_485_381, 0), Utc);
-        assert_eq!(now_with_cached(dt, &[]).unwrap(), json!(dt.to_rfc3339()));
-        assert_eq!(now_with_cached(dt, &[]).unwrap(), json!("2018-11-06T06:23:01+00:00"));
+        let kwargs = HashMap::new();
+        assert_eq!(now_with_cached(dt, &[], &kwargs).unwrap(), json!(dt.to_rfc3339()));
+        assert_eq!(
+            now_with_cached(dt, &[], &kwargs).unwrap(),
+            json!("2018-11-06T06:23:01+00:00")
+        );
     }
 
     #[test]
     fn timestamp_argument() {
         let args = vec![json!(true)];
+        let kwargs = HashMap::new();
 
         let dt = DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(1_541_485_381, 0), Utc);
-        assert_eq!(now_with_cached(dt, &args).unwrap(), json!(1_541_485_381));
+        assert_eq!(now_with_cached(dt, &args, &kwargs).unwrap(), json!(1_541_485_381));
+    }
+
+    #[test]
+    fn timestamp_named_argument() {
+        let mut kwargs = HashMap::new();
+        kwargs.insert("timestamp".to_string(), json!(true));
+
+        let dt = DateTime::<Utc>::from_timestamp(1_541_485_381, 0).unwrap();
+        assert_eq!(now_with_cached(dt, &[], &kwargs).unwrap(), json!(1_541_485_381));
     }
 
     #[test]
     fn fail_on_invalid_timestamp_argument_type() {
         let mut ctx = Context::default();
         let args = vec![json!("foo")];
-        assert!(now(&args, &mut ctx).is_err());
+        assert!(now(&args, &HashMap::new(), &mut ctx).is_err());
+    }
+
+    #[test]
+    fn fail_on_unknown_named_argument() {
+        let mut ctx = Context::default();
+        let mut kwargs = HashMap::new();
+        kwargs.insert("foo".to_string(), json!(true));
+        assert!(now(&[], &kwargs, &mut ctx).is_err());
+    }
+
+    #[test]
+    fn fail_on_argument_passed_by_position_and_name() {
+        let mut ctx = Context::default();
+        let args = vec![json!(true)];
+        let mut kwargs = HashMap::new();
+        kwargs.insert("timestamp".to_string(), json!(true));
+        assert!(now(&args, &kwargs, &mut ctx).is_err());
     }
 }
@@ -0,0 +1,208 @@
+use serde_json::Value;
+
+use crate::ast::Expression;
+use crate::builtin::{Determinism, Metadata, Signature, ValueType};
+use crate::error::*;
+
+fn validate_args_len(args: &[Expression], min: usize, max: Option<usize>, name: &'static str) -> Result<()> {
+    if args.len() < min || max.map(|max| args.len() > max).unwrap_or(false) {
+        let expected = match max {
+            Some(max) if max == min => format!("{}", max),
+            Some(max) => format!("{} to {}", min, max),
+            None => format!("at least {}", min),
+        };
+        return Err(Error::new(ErrorKind::InvalidCall, "invalid number of arguments")
+            .context("function", name)
+            .context("argument count", format!("{}", args.len()))
+            .context("expected", expected));
+    }
+    Ok(())
+}
+
+/// `IF(condition, truthy, falsy)`
+///
+/// Only one of the `truthy` and `falsy` arguments is evaluated.
+pub(crate) fn if_(args: &[Expression], eval: &mut dyn FnMut(&Expression) -> Result<Value>) -> Result<Value> {
+    validate_args_len(args, 3, Some(3), "IF")?;
+
+    let condition = eval(&args[0])?;
+    match condition {
+        Value::Bool(true) => eval(&args[1]),
+        Value::Bool(false) => eval(&args[2]),
+        _ => Err(Error::new(ErrorKind::TypeMismatch, "invalid argument type")
+            .context("function", "IF")
+            .context("argument", "condition")
+            .context("argument value", condition.to_string())
+            .context("expected", "boolean")),
+    }
+}
+
+/// `COALESCE(value, ...)`
+///
+/// Returns the first argument which does not evaluate to `null`. Remaining arguments
+/// are not evaluated.
+pub(crate) fn coalesce(args: &[Expression], eval: &mut dyn FnMut(&Expression) -> Result<Value>) -> Result<Value> {
+    validate_args_len(args, 1, None, "COALESCE")?;
+
+    for arg in args {
+        let value = eval(arg)?;
+        if !value.is_null() {
+            return Ok(value);
+        }
+    }
+
+    Ok(Value::Null)
+}
+
+/// `TRY(expression, fallback)`
+///
+/// `fallback` is evaluated only if the `expression` evaluation fails. Unknown functions,
+/// unknown filters and non-deterministic calls are not caught, they're mistakes in the
+/// expression itself, not evaluation failures.
+pub(crate) fn try_(args: &[Expression], eval: &mut dyn FnMut(&Expression) -> Result<Value>) -> Result<Value> {
+    validate_args_len(args, 2, Some(2), "TRY")?;
+
+    match eval(&args[0]) {
+        Ok(value) => Ok(value),
+        Err(e) => match e.kind() {
+            ErrorKind::UnknownFunction | ErrorKind::UnknownFilter | ErrorKind::NonDeterministic => Err(e),
+            _ => eval(&args[1]),
+        },
+    }
+}
+
+pub(crate) fn if_metadata() -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(
+            Signature::new(ValueType::Any)
+                .arg("condition", ValueType::Boolean)
+                .arg("truthy", ValueType::Any)
+                .arg("falsy", ValueType::Any),
+        )
+        .description("Returns `truthy` if `condition` is true, `falsy` otherwise")
+}
+
+pub(crate) fn coalesce_metadata() -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(
+            Signature::new(ValueType::Any)
+                .arg("value", ValueType::Any)
+                .variadic(ValueType::Any),
+        )
+        .description("Returns the first argument which is not null")
+}
+
+pub(crate) fn try_metadata() -> Metadata {
+    Metadata::new(Determinism::Pure)
+        .signature(
+            Signature::new(ValueType::Any)
+                .arg("expression", ValueType::Any)
+                .arg("fallback", ValueType::Any),
+        )
+        .description("Returns `fallback` if `expression` evaluation fails")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::ast::{Expression, ExpressionValue};
+    use crate::error::*;
+
+    use super::{coalesce, if_, try_};
+
+    fn args(expressions: &[&str]) -> Vec<Expression> {
+        expressions.iter().map(|x| x.parse().unwrap()).collect()
+    }
+
+    // Evaluates literals only, identifiers evaluate to `null`, everything else fails
+    fn eval(evaluated: &mut Vec<Expression>) -> impl FnMut(&Expression) -> Result<Value> + '_ {
+        move |expression| {
+            evaluated.push(expression.clone());
+            match expression.value {
+                ExpressionValue::Integer(x) => Ok(json!(x)),
+                ExpressionValue::Boolean(x) => Ok(json!(x)),
+                ExpressionValue::String(ref x) => Ok(json!(x)),
+                ExpressionValue::Identifier(_) => Ok(Value::Null),
+                _ => Err(Error::with_message("unable to evaluate")),
+            }
+        }
+    }
+
+    #[test]
+    fn if_evaluates_one_branch_only() {
+        let mut evaluated = vec![];
+        let args = args(&["true", "1", "1 + 1"]);
+        assert_eq!(if_(&args, &mut eval(&mut evaluated)).unwrap(), json!(1));
+        assert_eq!(evaluated.len(), 2);
+
+        let mut evaluated = vec![];
+        let args = self::args(&["false", "1 + 1", "2"]);
+        assert_eq!(if_(&args, &mut eval(&mut evaluated)).unwrap(), json!(2));
+        assert_eq!(evaluated.len(), 2);
+    }
+
+    #[test]
+    fn if_fails_on_invalid_condition() {
+        let mut evaluated = vec![];
+        assert!(if_(&args(&["1", "2", "3"]), &mut eval(&mut evaluated)).is_err());
+        assert!(if_(&args(&["true", "2"]), &mut eval(&mut evaluated)).is_err());
+    }
+
+    #[test]
+    fn coalesce_returns_first_not_null_value() {
+        let mut evaluated = vec![];
+        let args = args(&["foo", "bar", "`baz`", "1 + 1"]);
+        assert_eq!(coalesce(&args, &mut eval(&mut evaluated)).unwrap(), json!("baz"));
+        assert_eq!(evaluated.len(), 3);
+
+        let mut evaluated = vec![];
+        let args = self::args(&["foo", "bar"]);
+        assert_eq!(coalesce(&args, &mut eval(&mut evaluated)).unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn coalesce_fails_without_arguments() {
+        let mut evaluated = vec![];
+        assert_eq!(
+            coalesce(&[], &mut eval(&mut evaluated)).unwrap_err().to_string(),
+            Error::with_message("invalid number of arguments")
+                .context("function", "COALESCE")
+                .context("argument count", "0")
+                .context("expected", "at least 1")
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn try_evaluates_fallback_on_failure_only() {
+        let mut evaluated = vec![];
+        let args = args(&["1", "1 + 1"]);
+        assert_eq!(try_(&args, &mut eval(&mut evaluated)).unwrap(), json!(1));
+        assert_eq!(evaluated.len(), 1);
+
+        let mut evaluated = vec![];
+        let args = self::args(&["1 + 1", "2"]);
+        assert_eq!(try_(&args, &mut eval(&mut evaluated)).unwrap(), json!(2));
+        assert_eq!(evaluated.len(), 2);
+    }
+
+    #[test]
+    fn try_does_not_catch_invalid_expressions() {
+        let args = args(&["1", "2"]);
+
+        for kind in &[
+            ErrorKind::UnknownFunction,
+            ErrorKind::UnknownFilter,
+            ErrorKind::NonDeterministic,
+        ] {
+            let mut evaluated = 0;
+            let mut eval = |_: &Expression| {
+                evaluated += 1;
+                Err(Error::new(*kind, "failure"))
+            };
+            assert_eq!(try_(&args, &mut eval).unwrap_err().kind(), *kind);
+            assert_eq!(evaluated, 1);
+        }
+    }
+}
@@ -2,8 +2,9 @@ use rand::{self, RngCore};
 use serde_json::Value;
 use uuid::{builder::Builder, Variant, Version};
 
+use crate::builtin::{Determinism, Metadata, Signature, ValueType};
 use crate::context::Context;
-use crate::error::{Error, Result};
+use crate::error::{Error, ErrorKind, Result};
 
 pub(crate) fn uuidv4(_args: &[Value], _context: &mut Context) -> Result<Value> {
     // We're using uuid crate with disabled v4 feature, because of the wasm issue
@@ -16,7 +17,7 @@ pub(crate) fn uuidv4(_args: &[Value], _context: &mut Context) -> Result<Value> {
     rng.fill_bytes(&mut bytes);
 
     let uuid = Builder::from_slice(&bytes)
-        .map_err(|_| Error::with_message("random generator failure"))?
+        .map_err(|_| Error::new(ErrorKind::Internal, "random generator failure"))?
         .set_version(Version::Random)
         .set_variant(Variant::RFC4122)
         .build();
@@ -24,6 +25,12 @@ pub(crate) fn uuidv4(_args: &[Value], _context: &mut Context) -> Result<Value> {
     Ok(Value::String(uuid.to_hyphenated().to_string()))
 }
 
+pub(crate) fn uuidv4_metadata() -> Metadata {
+    Metadata::new(Determinism::Random)
+        .signature(Signature::new(ValueType::String))
+        .description("Generates a random UUID (version 4)")
+}
+
 #[cfg(test)]
 mod tests {
     use uuid::{Uuid, Version};
@@ -0,0 +1,335 @@
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Function or filter determinism
+///
+/// Check the [`Metadata`] documentation to learn how it's used.
+///
+/// [`Metadata`]: struct.Metadata.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Determinism {
+    /// Result depends on arguments (and filter input) only
+    Pure,
+    /// Result depends on the evaluation context as well (`NOW`)
+    ContextDependent,
+    /// Result is random (`UUIDV4`)
+    Random,
+}
+
+impl fmt::Display for Determinism {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Determinism::Pure => f.write_str("pure"),
+            Determinism::ContextDependent => f.write_str("context-dependent"),
+            Determinism::Random => f.write_str("random"),
+        }
+    }
+}
+
+/// Registered function or filter metadata
+///
+/// Metadata can be registered with the [`function_metadata`] and [`filter_metadata`]
+/// methods. Functions and filters without metadata are considered context dependent,
+/// pure typed ones can be registered with the [`typed_pure_function`] and
+/// [`typed_pure_filter`] methods.
+///
+/// Signature is used to validate function and filter calls before the evaluation (see
+/// [`Engine::check`]). Determinism is used to:
+///
+/// * memoize calls of pure functions and filters within one evaluation [`Context`]
+/// * pre-compute calls of pure functions and filters with constant arguments
+///   (see [`Engine::fold_constants`])
+/// * refuse calls of context dependent and random functions and filters in the
+///   reproducible engine mode (see [`EngineBuilder::reproducible`])
+///
+/// [`Engine::check`]: struct.Engine.html#method.check
+/// [`function_metadata`]: struct.EngineBuilder.html#method.function_metadata
+/// [`filter_metadata`]: struct.EngineBuilder.html#method.filter_metadata
+/// [`typed_pure_function`]: struct.EngineBuilder.html#method.typed_pure_function
+/// [`typed_pure_filter`]: struct.EngineBuilder.html#method.typed_pure_filter
+/// [`Context`]: struct.Context.html
+/// [`Engine::fold_constants`]: struct.Engine.html#method.fold_constants
+/// [`EngineBuilder::reproducible`]: struct.EngineBuilder.html#method.reproducible
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Metadata {
+    /// Function or filter determinism
+    pub determinism: Determinism,
+    /// Function or filter signature
+    pub signature: Option<Signature>,
+    /// Function or filter description
+    pub description: Option<String>,
+}
+
+impl Metadata {
+    /// Creates new metadata
+    ///
+    /// # Arguments
+    ///
+    /// * `determinism` - Function or filter determinism
+    pub fn new(determinism: Determinism) -> Metadata {
+        Metadata {
+            determinism,
+            signature: None,
+            description: None,
+        }
+    }
+
+    /// Sets the signature
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - Function or filter signature
+    pub fn signature(self, signature: Signature) -> Metadata {
+        Metadata {
+            signature: Some(signature),
+            ..self
+        }
+    }
+
+    /// Sets the description
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - Function or filter description
+    pub fn description<S>(self, description: S) -> Metadata
+    where
+        S: Into<String>,
+    {
+        Metadata {
+            description: Some(description.into()),
+            ..self
+        }
+    }
+}
+
+impl Default for Metadata {
+    /// Creates new metadata of a context dependent function or filter
+    fn default() -> Metadata {
+        Metadata::new(Determinism::ContextDependent)
+    }
+}
+
+/// Function or filter argument (return value) type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum ValueType {
+    /// Any value
+    Any,
+    /// `null`
+    Null,
+    /// A boolean
+    Boolean,
+    /// A number (integer or floating point)
+    Number,
+    /// A string
+    String,
+    /// An array
+    Array,
+    /// An object
+    Object,
+}
+
+impl ValueType {
+    /// Checks if a value is of this type
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A value to check
+    pub fn matches(self, value: &Value) -> bool {
+        match self {
+            ValueType::Any => true,
+            ValueType::Null => value.is_null(),
+            ValueType::Boolean => value.is_boolean(),
+            ValueType::Number => value.is_number(),
+            ValueType::String => value.is_string(),
+            ValueType::Array => value.is_array(),
+            ValueType::Object => value.is_object(),
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValueType::Any => f.write_str("any"),
+            ValueType::Null => f.write_str("null"),
+            ValueType::Boolean => f.write_str("boolean"),
+            ValueType::Number => f.write_str("number"),
+            ValueType::String => f.write_str("string"),
+            ValueType::Array => f.write_str("array"),
+            ValueType::Object => f.write_str("object"),
+        }
+    }
+}
+
+/// Function or filter argument
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Argument {
+    /// Argument name (named arguments)
+    pub name: String,
+    /// Argument type
+    pub value_type: ValueType,
+    /// Is argument required?
+    pub required: bool,
+}
+
+/// Function or filter signature
+///
+/// Positional arguments are declared in the positional order, all required arguments
+/// must be declared before optional arguments. Functions and filters accepting named
+/// arguments accept the declared argument names.
+///
+/// # Examples
+///
+/// `DATE(format, tz)` filter signature:
+///
+/// ```rust
+/// use balena_temen::{Signature, ValueType};
+///
+/// let signature = Signature::new(ValueType::String)
+///     .input(ValueType::Number)
+///     .optional_arg("format", ValueType::String)
+///     .optional_arg("tz", ValueType::String);
+///
+/// assert_eq!(signature.min_args(), 0);
+/// assert_eq!(signature.max_args(), Some(2));
+/// ```
+///
+/// `MAX(value, ...)` function signature:
+///
+/// ```rust
+/// use balena_temen::{Signature, ValueType};
+///
+/// let signature = Signature::new(ValueType::Number)
+///     .arg("value", ValueType::Number)
+///     .variadic(ValueType::Number);
+///
+/// assert_eq!(signature.min_args(), 1);
+/// assert_eq!(signature.max_args(), None);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Signature {
+    /// Filter input type (filters only)
+    pub input: ValueType,
+    /// Declared arguments
+    pub args: Vec<Argument>,
+    /// Type of additional positional arguments, `None` if not accepted
+    pub variadic: Option<ValueType>,
+    /// Return value type
+    pub return_type: ValueType,
+}
+
+impl Signature {
+    /// Creates new signature without arguments
+    ///
+    /// # Arguments
+    ///
+    /// * `return_type` - Return value type
+    pub fn new(return_type: ValueType) -> Signature {
+        Signature {
+            input: ValueType::Any,
+            args: vec![],
+            variadic: None,
+            return_type,
+        }
+    }
+
+    /// Sets the filter input type
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Filter input type
+    pub fn input(self, input: ValueType) -> Signature {
+        Signature { input, ..self }
+    }
+
+    /// Appends a required argument
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Argument name
+    /// * `value_type` - Argument type
+    ///
+    /// # Panics
+    ///
+    /// Panics if an optional argument was already appended.
+    pub fn arg<S>(self, name: S, value_type: ValueType) -> Signature
+    where
+        S: Into<String>,
+    {
+        let name = name.into();
+        if self.args.iter().any(|x| !x.required) {
+            panic!("required argument `{}` follows an optional argument", name);
+        }
+        self.push_arg(name, value_type, true)
+    }
+
+    /// Appends an optional argument
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Argument name
+    /// * `value_type` - Argument type
+    pub fn optional_arg<S>(self, name: S, value_type: ValueType) -> Signature
+    where
+        S: Into<String>,
+    {
+        self.push_arg(name.into(), value_type, false)
+    }
+
+    fn push_arg(self, name: String, value_type: ValueType, required: bool) -> Signature {
+        let mut args = self.args;
+        args.push(Argument {
+            name,
+            value_type,
+            required,
+        });
+        Signature { args, ..self }
+    }
+
+    /// Accepts any number of additional positional arguments
+    ///
+    /// # Arguments
+    ///
+    /// * `value_type` - Additional arguments type
+    pub fn variadic(self, value_type: ValueType) -> Signature {
+        Signature {
+            variadic: Some(value_type),
+            ..self
+        }
+    }
+
+    /// Minimum number of arguments
+    pub fn min_args(&self) -> usize {
+        self.args.iter().filter(|x| x.required).count()
+    }
+
+    /// Maximum number of positional arguments, `None` if unlimited
+    pub fn max_args(&self) -> Option<usize> {
+        match self.variadic {
+            Some(_) => None,
+            None => Some(self.args.len()),
+        }
+    }
+
+    /// Returns positional argument type
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Argument position
+    pub fn arg_type(&self, index: usize) -> Option<ValueType> {
+        self.args.get(index).map(|x| x.value_type).or(self.variadic)
+    }
+}
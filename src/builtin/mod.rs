@@ -1,2 +1,114 @@
+#[cfg(feature = "datetime")]
+use std::collections::HashMap;
+
+#[cfg(feature = "datetime")]
+use serde_json::Value;
+
+#[cfg(feature = "datetime")]
+use crate::error::{Error, ErrorKind, Result};
+
+pub use self::convert::{FromValue, IntoValue, TypedFilter, TypedFunction};
+pub use self::metadata::{Argument, Determinism, Metadata, Signature, ValueType};
+
+mod convert;
 pub(crate) mod filter;
 pub(crate) mod function;
+mod metadata;
+
+/// Group of builtin functions and filters
+///
+/// Groups can be registered selectively with the [`builtins`] method, the default
+/// [`EngineBuilder`] registers all of them.
+///
+/// Builtins with external dependencies are compiled only if the corresponding cargo
+/// feature is enabled:
+///
+/// * `datetime` - the `Datetime` group
+/// * `datetime-tz` - the `tz` argument of the datetime filters (not enabled by default)
+/// * `random` - the `Random` group
+/// * `uuid` - the `UUIDV4` function (`Random` group), enables `random`
+/// * `slugify` - the `SLUGIFY` filter (`Strings` group)
+///
+/// All groups are available regardless of enabled features. Builtins which are not
+/// compiled in are not registered.
+///
+/// [`builtins`]: struct.EngineBuilder.html#method.builtins
+/// [`EngineBuilder`]: struct.EngineBuilder.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuiltinGroup {
+    /// `UPPER`, `LOWER`, `TRIM` and `SLUGIFY` filters
+    Strings,
+    /// `POW`, `LOG10`, `MIN` and `MAX` functions
+    Math,
+    /// `TIME`, `DATE` and `DATETIME` filters and the `NOW` function
+    Datetime,
+    /// `UUIDV4` function
+    Random,
+    /// `IF`, `COALESCE` and `TRY` special forms
+    ControlFlow,
+}
+
+impl BuiltinGroup {
+    /// Returns all builtin groups
+    pub fn all() -> &'static [BuiltinGroup] {
+        &[
+            BuiltinGroup::Strings,
+            BuiltinGroup::Math,
+            BuiltinGroup::Datetime,
+            BuiltinGroup::Random,
+            BuiltinGroup::ControlFlow,
+        ]
+    }
+}
+
+#[cfg(feature = "datetime")]
+/// Returns an argument passed either by position or by name
+///
+/// # Arguments
+///
+/// * `args` - List of positional arguments
+/// * `kwargs` - Named arguments
+/// * `index` - Argument position
+/// * `name` - Argument name
+fn get_arg<'a>(
+    args: &'a [Value],
+    kwargs: &'a HashMap<String, Value>,
+    index: usize,
+    name: &'static str,
+) -> Result<Option<&'a Value>> {
+    match (args.get(index), kwargs.get(name)) {
+        (Some(_), Some(_)) => Err(
+            Error::new(ErrorKind::InvalidCall, "argument passed by position and by name")
+                .context("argument", name)
+                .context("position", format!("{}", index)),
+        ),
+        (Some(value), None) | (None, Some(value)) => Ok(Some(value)),
+        (None, None) => Ok(None),
+    }
+}
+
+#[cfg(feature = "datetime")]
+/// Validates positional arguments count and named arguments names
+///
+/// # Arguments
+///
+/// * `args` - List of positional arguments
+/// * `kwargs` - Named arguments
+/// * `names` - List of all argument names in the positional order
+fn validate_args(args: &[Value], kwargs: &HashMap<String, Value>, names: &[&'static str]) -> Result<()> {
+    if args.len() > names.len() {
+        return Err(Error::new(ErrorKind::InvalidCall, "invalid number of arguments")
+            .context("argument count", format!("{}", args.len()))
+            .context("expected", format!("{}", names.len())));
+    }
+
+    for name in kwargs.keys() {
+        if !names.contains(&name.as_str()) {
+            return Err(Error::new(ErrorKind::InvalidCall, "unknown named argument")
+                .context("argument", name.to_string())
+                .context("expected", names.join(", ")));
+        }
+    }
+
+    Ok(())
+}
@@ -1,14 +1,18 @@
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(feature = "datetime", target_arch = "wasm32"))]
 use chrono::NaiveDateTime;
+#[cfg(feature = "datetime")]
 use chrono::{DateTime, Utc};
+use serde_json::Value;
 
-#[cfg(not(target_arch = "wasm32"))]
+use crate::engine::memo::{Call, CallCache};
+
+#[cfg(all(feature = "datetime", not(target_arch = "wasm32")))]
 fn utc_now() -> DateTime<Utc> {
     Utc::now()
 }
 
 // chrono crate doesn't support wasm32 arch yet, workaround
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(feature = "datetime", target_arch = "wasm32"))]
 fn utc_now() -> DateTime<Utc> {
     let now = js_sys::Date::new_0();
     let millisecs_since_unix_epoch: u64 = now.get_time() as u64;
@@ -22,9 +26,16 @@ fn utc_now() -> DateTime<Utc> {
 ///
 /// Context is required for a template evaluation. It holds cached values
 /// like date time for the `now()` function, which must return same value
-/// in case the same evaluation context is used.
+/// in case the same evaluation context is used, and memoized results of pure
+/// function and filter calls.
+///
+/// Memoized results are bound to the engine which made the call, one context can be
+/// shared by multiple engines. Number of memoized results is limited, all of them are
+/// dropped when the limit is reached.
 pub struct Context {
+    #[cfg(feature = "datetime")]
     cached_now: Option<DateTime<Utc>>,
+    memoized_calls: CallCache,
 }
 
 impl Context {
@@ -34,6 +45,7 @@ impl Context {
     ///
     /// The result is cached and subsequent calls return same value! This is used
     /// by the `now()` function for example.
+    #[cfg(feature = "datetime")]
     pub(crate) fn cached_now(&mut self) -> DateTime<Utc> {
         if let Some(x) = self.cached_now {
             return x;
@@ -43,11 +55,25 @@ impl Context {
         self.cached_now = Some(x);
         x
     }
+
+    /// Memoized result of a pure function or filter call
+    pub(crate) fn memoized_call(&self, call: &Call) -> Option<&Value> {
+        self.memoized_calls.get(call)
+    }
+
+    /// Memoizes result of a pure function or filter call
+    pub(crate) fn memoize_call(&mut self, call: &Call, value: Value) {
+        self.memoized_calls.insert(call, value);
+    }
 }
 
 impl Default for Context {
     /// Creates new, empty, context
     fn default() -> Context {
-        Context { cached_now: None }
+        Context {
+            #[cfg(feature = "datetime")]
+            cached_now: None,
+            memoized_calls: CallCache::default(),
+        }
     }
 }
@@ -1,11 +1,21 @@
 use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use serde_json::Value;
 
 use crate::{
+    ast::Expression,
     builtin::{
-        filter::{self, FilterFn},
-        function::{self, FunctionFn},
+        filter::{self, Filter},
+        function::{self, Function},
+        BuiltinGroup, Determinism, Metadata, TypedFilter, TypedFunction,
     },
+    context::Context,
     engine::Engine,
+    error::*,
 };
 
 /// A custom engine builder
@@ -14,50 +24,222 @@ use crate::{
 ///
 /// [`Engine`]: struct.Engine.html
 pub struct EngineBuilder {
-    functions: HashMap<String, FunctionFn>,
-    filters: HashMap<String, FilterFn>,
+    functions: HashMap<String, Function>,
+    filters: HashMap<String, Filter>,
+    function_metadata: HashMap<String, Metadata>,
+    filter_metadata: HashMap<String, Metadata>,
     eval_keyword: Option<String>,
+    reproducible: bool,
 }
 
 impl Default for EngineBuilder {
     /// Creates new [`EngineBuilder`] with default filters, functions and the evaluation keyword
     ///
+    /// All [`BuiltinGroup`]s are registered.
+    ///
     /// [`EngineBuilder`]: struct.EngineBuilder.html
+    /// [`BuiltinGroup`]: enum.BuiltinGroup.html
     fn default() -> EngineBuilder {
-        EngineBuilder::new()
-            .filter("UPPER", filter::upper)
-            .filter("LOWER", filter::lower)
-            .filter("TIME", filter::time)
-            .filter("DATE", filter::date)
-            .filter("DATETIME", filter::datetime)
-            .filter("TRIM", filter::trim)
-            .filter("SLUGIFY", filter::slugify)
-            .function("UUIDV4", function::uuidv4)
-            .function("NOW", function::now)
-            .function("POW", function::math::pow)
-            .function("LOG10", function::math::log10)
-            .function("MIN", function::math::min)
-            .function("MAX", function::math::max)
+        BuiltinGroup::all()
+            .iter()
+            .fold(EngineBuilder::new(), |builder, group| builder.builtins(*group))
     }
 }
 
 impl EngineBuilder {
     /// Creates new, empty, [`EngineBuilder`]
     ///
-    /// No filters and functions are registered.
+    /// No filters and functions are registered. Use the [`builtins`] method to register
+    /// builtin groups selectively.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{BuiltinGroup, Engine, EngineBuilder};
+    ///
+    /// let engine: Engine = EngineBuilder::new()
+    ///     .builtins(BuiltinGroup::Strings)
+    ///     .into();
+    ///
+    /// assert!(engine.check("name | UPPER").is_ok());
+    /// assert!(engine.check("POW(2, 3)").is_err());
+    /// ```
     ///
     /// [`EngineBuilder`]: struct.EngineBuilder.html
-    fn new() -> EngineBuilder {
+    /// [`builtins`]: struct.EngineBuilder.html#method.builtins
+    pub fn new() -> EngineBuilder {
         EngineBuilder {
             functions: HashMap::new(),
             filters: HashMap::new(),
+            function_metadata: HashMap::new(),
+            filter_metadata: HashMap::new(),
             eval_keyword: None,
+            reproducible: false,
+        }
+    }
+
+    /// Registers a group of builtin functions and filters
+    ///
+    /// Builtins of the group (and their metadata) are registered again if they were
+    /// overwritten or removed. Builtins disabled by cargo features are not registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - A group to register
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{BuiltinGroup, Engine, EngineBuilder};
+    ///
+    /// let engine: Engine = EngineBuilder::new()
+    ///     .builtins(BuiltinGroup::Math)
+    ///     .builtins(BuiltinGroup::ControlFlow)
+    ///     .into();
+    ///
+    /// assert!(engine.check("IF(a, POW(2, 3), 1)").is_ok());
+    /// assert!(engine.check("`a` | UPPER").is_err());
+    /// ```
+    pub fn builtins(self, group: BuiltinGroup) -> EngineBuilder {
+        match group {
+            BuiltinGroup::Strings => {
+                let builder = self
+                    .filter("UPPER", filter::upper)
+                    .register_filter_metadata("UPPER", filter::upper_metadata())
+                    .filter("LOWER", filter::lower)
+                    .register_filter_metadata("LOWER", filter::lower_metadata())
+                    .filter("TRIM", filter::trim)
+                    .register_filter_metadata("TRIM", filter::trim_metadata());
+                #[cfg(feature = "slugify")]
+                let builder = builder
+                    .filter("SLUGIFY", filter::slugify)
+                    .register_filter_metadata("SLUGIFY", filter::slugify_metadata());
+                builder
+            }
+            BuiltinGroup::Math => self
+                .function("POW", function::math::pow)
+                .register_function_metadata("POW", function::math::pow_metadata())
+                .function("LOG10", function::math::log10)
+                .register_function_metadata("LOG10", function::math::log10_metadata())
+                .function("MIN", function::math::min)
+                .register_function_metadata("MIN", function::math::min_metadata())
+                .function("MAX", function::math::max)
+                .register_function_metadata("MAX", function::math::max_metadata()),
+            #[cfg(feature = "datetime")]
+            BuiltinGroup::Datetime => self
+                .filter_with_kwargs("TIME", filter::time)
+                .register_filter_metadata("TIME", filter::time_metadata())
+                .filter_with_kwargs("DATE", filter::date)
+                .register_filter_metadata("DATE", filter::date_metadata())
+                .filter_with_kwargs("DATETIME", filter::datetime)
+                .register_filter_metadata("DATETIME", filter::datetime_metadata())
+                .function_with_kwargs("NOW", function::now)
+                .register_function_metadata("NOW", function::now_metadata()),
+            #[cfg(not(feature = "datetime"))]
+            BuiltinGroup::Datetime => self,
+            #[cfg(feature = "random")]
+            BuiltinGroup::Random => {
+                #[cfg(feature = "uuid")]
+                let builder = self
+                    .function("UUIDV4", function::uuidv4)
+                    .register_function_metadata("UUIDV4", function::uuidv4_metadata());
+                #[cfg(not(feature = "uuid"))]
+                let builder = self;
+                builder
+            }
+            #[cfg(not(feature = "random"))]
+            BuiltinGroup::Random => self,
+            BuiltinGroup::ControlFlow => self
+                .special_form("IF", function::special::if_)
+                .register_function_metadata("IF", function::special::if_metadata())
+                .special_form("COALESCE", function::special::coalesce)
+                .register_function_metadata("COALESCE", function::special::coalesce_metadata())
+                .special_form("TRY", function::special::try_)
+                .register_function_metadata("TRY", function::special::try_metadata()),
+        }
+    }
+
+    /// Removes a registered filter
+    ///
+    /// Filter metadata are removed as well. Nothing happens if there's no such filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Filter name
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{Engine, EngineBuilder};
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .without_filter("slugify")
+    ///     .into();
+    ///
+    /// assert!(engine.check("name | SLUGIFY").is_err());
+    /// assert!(engine.check("name | UPPER").is_ok());
+    /// ```
+    pub fn without_filter<S>(self, name: S) -> EngineBuilder
+    where
+        S: Into<String>,
+    {
+        let name = name.into().to_uppercase();
+        let mut filters = self.filters;
+        let mut filter_metadata = self.filter_metadata;
+        filters.remove(&name);
+        filter_metadata.remove(&name);
+        EngineBuilder {
+            filters,
+            filter_metadata,
+            ..self
+        }
+    }
+
+    /// Removes a registered function
+    ///
+    /// Function (special form) metadata are removed as well. Nothing happens if there's
+    /// no such function.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Function name
+    ///
+    /// # Examples
+    ///
+    /// Locked-down engine without non-deterministic functions:
+    ///
+    /// ```rust
+    /// use balena_temen::{Engine, EngineBuilder};
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .without_function("UUIDV4")
+    ///     .without_function("NOW")
+    ///     .into();
+    ///
+    /// assert!(engine.check("UUIDV4()").is_err());
+    /// assert!(engine.check("NOW()").is_err());
+    /// assert!(engine.check("POW(2, 3)").is_ok());
+    /// ```
+    pub fn without_function<S>(self, name: S) -> EngineBuilder
+    where
+        S: Into<String>,
+    {
+        let name = name.into().to_uppercase();
+        let mut functions = self.functions;
+        let mut function_metadata = self.function_metadata;
+        functions.remove(&name);
+        function_metadata.remove(&name);
+        EngineBuilder {
+            functions,
+            function_metadata,
+            ..self
         }
     }
 
     /// Registers custom filter
     ///
-    /// If a filter with the name already exists, it will be overwritten.
+    /// If a filter with the name already exists, it will be overwritten. The filter can be
+    /// a closure (or a boxed closure) capturing its own state.
     ///
     /// Visit [`FilterFn`] to learn more about filters.
     ///
@@ -139,22 +321,300 @@ impl EngineBuilder {
     /// ```
     ///
     /// [`FilterFn`]: type.FilterFn.html
-    pub fn filter<S>(self, name: S, filter: FilterFn) -> EngineBuilder
+    pub fn filter<S, F>(self, name: S, filter: F) -> EngineBuilder
     where
         S: Into<String>,
+        F: Fn(&Value, &[Value], &mut Context) -> Result<Value> + Send + Sync + 'static,
     {
+        self.register_filter(name, Filter::Positional(Arc::new(filter)))
+    }
+
+    /// Registers custom filter accepting named arguments
+    ///
+    /// If a filter with the name already exists, it will be overwritten.
+    ///
+    /// Visit [`FilterKwargsFn`] to learn more about filters with named arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Custom filter name
+    /// * `filter` - Custom filter function
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{
+    ///     ast::Identifier,
+    ///     Engine, EngineBuilder, Context, Value,
+    ///     error::*
+    /// };
+    /// use serde_json::json;
+    /// use std::collections::HashMap;
+    ///
+    /// fn repeat_filter(input: &Value, args: &[Value], kwargs: &HashMap<String, Value>, _: &mut Context) -> Result<Value> {
+    ///     let input = input.as_str()
+    ///         .ok_or_else(|| {
+    ///             Error::with_message("invalid input type")
+    ///                 .context("expected", "string")
+    ///                 .context("value", input.to_string())
+    ///         })?;
+    ///
+    ///     let times = args.first()
+    ///         .or_else(|| kwargs.get("times"))
+    ///         .unwrap_or(&Value::Null);
+    ///     let times = times
+    ///         .as_u64()
+    ///         .ok_or_else(|| {
+    ///             Error::with_message("invalid argument type")
+    ///                 .context("argument", "times")
+    ///                 .context("expected", "positive integer")
+    ///                 .context("value", times.to_string())
+    ///         })?;
+    ///
+    ///     Ok(Value::String(input.repeat(times as usize)))
+    /// };
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .filter_with_kwargs("REPEAT", repeat_filter)
+    ///     .into();
+    /// let mut ctx = Context::default();
+    /// let position = Identifier::default();
+    /// let data = Value::Null;
+    ///
+    /// assert_eq!(
+    ///     engine.eval("`ab` | REPEAT(2)", &position, &data, &mut ctx).unwrap(),
+    ///     json!("abab")
+    /// );
+    /// assert_eq!(
+    ///     engine.eval("`ab` | REPEAT(times=3)", &position, &data, &mut ctx).unwrap(),
+    ///     json!("ababab")
+    /// );
+    /// ```
+    ///
+    /// [`FilterKwargsFn`]: type.FilterKwargsFn.html
+    pub fn filter_with_kwargs<S, F>(self, name: S, filter: F) -> EngineBuilder
+    where
+        S: Into<String>,
+        F: Fn(&Value, &[Value], &HashMap<String, Value>, &mut Context) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.register_filter(name, Filter::Kwargs(Arc::new(filter)))
+    }
+
+    /// Registers custom filter with typed input, arguments and result
+    ///
+    /// Input and arguments are converted with the [`FromValue`] trait and the result with
+    /// the [`IntoValue`] trait. Invalid input type, invalid argument type and invalid number
+    /// of arguments errors are generated automatically. Filter signature is derived from
+    /// the types (arguments are named `arg1`, `arg2`, ...) and used by the [`check`]
+    /// method. If a filter with the name already exists, it will be overwritten.
+    ///
+    /// Typed filters can't access the evaluation context, but they can capture a state and
+    /// they're considered context dependent like the other custom filters. Use the
+    /// [`typed_pure_filter`] method to register a pure filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Custom filter name
+    /// * `filter` - Custom filter function
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{
+    ///     ast::Identifier,
+    ///     Engine, EngineBuilder, Context, Value,
+    ///     error::*
+    /// };
+    /// use serde_json::json;
+    ///
+    /// fn text_filter(input: String, trim: Option<bool>, upper: Option<bool>) -> Result<String> {
+    ///     let input = if trim.unwrap_or(false) { input.trim().to_string() } else { input };
+    ///     Ok(if upper.unwrap_or(false) { input.to_uppercase() } else { input })
+    /// };
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .typed_filter("TEXT", text_filter)
+    ///     .into();
+    /// let mut ctx = Context::default();
+    /// let position = Identifier::default();
+    /// let data = Value::Null;
+    ///
+    /// assert_eq!(
+    ///     engine.eval("` abc ` | TEXT(true, true)", &position, &data, &mut ctx).unwrap(),
+    ///     json!("ABC")
+    /// );
+    /// assert!(engine.eval("1 | TEXT", &position, &data, &mut ctx).is_err());
+    /// assert!(engine.eval("`abc` | TEXT(1)", &position, &data, &mut ctx).is_err());
+    /// assert!(engine.check("`abc` | TEXT(true, false, true)").is_err());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if an optional (`Option<T>`) argument precedes a required one.
+    ///
+    /// [`FromValue`]: trait.FromValue.html
+    /// [`IntoValue`]: trait.IntoValue.html
+    /// [`check`]: struct.Engine.html#method.check
+    /// [`typed_pure_filter`]: #method.typed_pure_filter
+    pub fn typed_filter<S, F, Args>(self, name: S, filter: F) -> EngineBuilder
+    where
+        S: Into<String>,
+        F: TypedFilter<Args>,
+    {
+        self.register_typed_filter(name, filter, Metadata::default())
+    }
+
+    /// Registers custom pure filter with typed input, arguments and result
+    ///
+    /// Same as the [`typed_filter`] method, but the filter is registered as pure. Calls of
+    /// pure filters are memoized and pre-computed by the [`fold_constants`] method. Do not
+    /// use it for filters returning random values, current time, captured state, ...
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Custom filter name
+    /// * `filter` - Custom filter function
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{ast::Expression, Engine, EngineBuilder, error::*};
+    ///
+    /// fn double(input: i64) -> Result<i64> {
+    ///     Ok(input * 2)
+    /// }
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .typed_pure_filter("DOUBLE", double)
+    ///     .into();
+    ///
+    /// let mut expression: Expression = "2 | DOUBLE".parse().unwrap();
+    /// engine.fold_constants(&mut expression);
+    /// assert_eq!(expression.to_string(), "4");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if an optional (`Option<T>`) argument precedes a required one.
+    ///
+    /// [`typed_filter`]: #method.typed_filter
+    /// [`fold_constants`]: struct.Engine.html#method.fold_constants
+    pub fn typed_pure_filter<S, F, Args>(self, name: S, filter: F) -> EngineBuilder
+    where
+        S: Into<String>,
+        F: TypedFilter<Args>,
+    {
+        self.register_typed_filter(name, filter, Metadata::new(Determinism::Pure))
+    }
+
+    // Registers typed filter with the signature derived from the types
+    //
+    // Determinism is taken from the `metadata`, `typed_filter` passes the default
+    // (context dependent) one and `typed_pure_filter` the pure one.
+    fn register_typed_filter<S, F, Args>(self, name: S, filter: F, metadata: Metadata) -> EngineBuilder
+    where
+        S: Into<String>,
+        F: TypedFilter<Args>,
+    {
+        let name = name.into().to_uppercase();
+        let filter_name = name.clone();
+        let metadata = metadata.signature(F::signature());
+        self.register_filter(
+            name.clone(),
+            Filter::Positional(Arc::new(move |input, args, _| {
+                filter.call_filter(&filter_name, input, args)
+            })),
+        )
+        .register_filter_metadata(name, metadata)
+    }
+
+    fn register_filter<S>(self, name: S, filter: Filter) -> EngineBuilder
+    where
+        S: Into<String>,
+    {
+        let name = name.into().to_uppercase();
         let mut filters = self.filters;
-        filters.insert(name.into().to_uppercase(), filter);
+        let mut filter_metadata = self.filter_metadata;
+        // Overwritten filter metadata do not apply to the new one
+        filter_metadata.insert(name.clone(), Metadata::default());
+        filters.insert(name, filter);
         EngineBuilder {
-            functions: self.functions,
             filters,
-            eval_keyword: self.eval_keyword,
+            filter_metadata,
+            ..self
+        }
+    }
+
+    /// Registers custom filter metadata
+    ///
+    /// Metadata of a filter are reset when the filter is registered again, register
+    /// metadata after the filter itself.
+    ///
+    /// Visit [`Metadata`] to learn more about metadata.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Filter name
+    /// * `metadata` - Filter metadata
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{
+    ///     ast::Expression,
+    ///     Context, Determinism, Engine, EngineBuilder, Metadata, Value,
+    ///     error::*
+    /// };
+    ///
+    /// fn double(input: &Value, _: &[Value], _: &mut Context) -> Result<Value> {
+    ///     Ok(Value::String(input.as_str().unwrap_or_default().repeat(2)))
+    /// }
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .filter("DOUBLE", double)
+    ///     .filter_metadata("DOUBLE", Metadata::new(Determinism::Pure))
+    ///     .unwrap()
+    ///     .into();
+    ///
+    /// let mut expression: Expression = "`a` | DOUBLE".parse().unwrap();
+    /// engine.fold_constants(&mut expression);
+    /// assert_eq!(expression.to_string(), "`aa`");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with the [`ErrorKind::UnknownFilter`] error if there's no filter with the name.
+    ///
+    /// [`Metadata`]: struct.Metadata.html
+    /// [`ErrorKind::UnknownFilter`]: error/enum.ErrorKind.html#variant.UnknownFilter
+    pub fn filter_metadata<S>(self, name: S, metadata: Metadata) -> Result<EngineBuilder>
+    where
+        S: Into<String>,
+    {
+        let name = name.into().to_uppercase();
+        if !self.filters.contains_key(&name) {
+            return Err(Error::new(ErrorKind::UnknownFilter, "filter not found").context("filter", name));
+        }
+        Ok(self.register_filter_metadata(name, metadata))
+    }
+
+    fn register_filter_metadata<S>(self, name: S, metadata: Metadata) -> EngineBuilder
+    where
+        S: Into<String>,
+    {
+        let mut filter_metadata = self.filter_metadata;
+        filter_metadata.insert(name.into().to_uppercase(), metadata);
+        EngineBuilder {
+            filter_metadata,
+            ..self
         }
     }
 
     /// Registers custom function
     ///
-    /// If a function with the name already exists, it will be overwritten.
+    /// If a function with the name already exists, it will be overwritten. The function
+    /// can be a closure (or a boxed closure) capturing its own state, like a lookup table
+    /// or a configuration loaded at startup.
     ///
     /// Visit [`FunctionFn`] to learn more about functions.
     ///
@@ -209,20 +669,430 @@ impl EngineBuilder {
     /// );
     /// ```
     ///
+    /// Closure capturing a lookup table:
+    ///
+    /// ```rust
+    /// use balena_temen::{
+    ///     ast::Identifier,
+    ///     Engine, EngineBuilder, Context, Value,
+    ///     error::*
+    /// };
+    /// use serde_json::json;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut regions = HashMap::new();
+    /// regions.insert("cz".to_string(), json!("Europe/Prague"));
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .function("REGION", move |args: &[Value], _: &mut Context| {
+    ///         let code = args.first().and_then(Value::as_str).unwrap_or_default();
+    ///         regions.get(code).cloned().ok_or_else(|| {
+    ///             Error::with_message("unknown region").context("code", code.to_string())
+    ///         })
+    ///     })
+    ///     .into();
+    /// let mut ctx = Context::default();
+    /// let position = Identifier::default();
+    /// let data = Value::Null;
+    ///
+    /// assert_eq!(
+    ///     engine.eval("REGION(`cz`)", &position, &data, &mut ctx).unwrap(),
+    ///     json!("Europe/Prague")
+    /// );
+    /// assert!(engine.eval("REGION(`de`)", &position, &data, &mut ctx).is_err());
+    /// ```
+    ///
     /// [`FunctionFn`]: type.FunctionFn.html
-    pub fn function<S>(self, name: S, function: FunctionFn) -> EngineBuilder
+    pub fn function<S, F>(self, name: S, function: F) -> EngineBuilder
+    where
+        S: Into<String>,
+        F: Fn(&[Value], &mut Context) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.register_function(name, Function::Positional(Arc::new(function)))
+    }
+
+    /// Registers custom function accepting named arguments
+    ///
+    /// If a function with the name already exists, it will be overwritten.
+    ///
+    /// Visit [`FunctionKwargsFn`] to learn more about functions with named arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Custom function name
+    /// * `function` - Custom function function
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{
+    ///     ast::Identifier,
+    ///     Engine, EngineBuilder, Context, Value,
+    ///     error::*
+    /// };
+    /// use serde_json::json;
+    /// use std::collections::HashMap;
+    ///
+    /// fn greet_function(args: &[Value], kwargs: &HashMap<String, Value>, _: &mut Context) -> Result<Value> {
+    ///     let name = args.first()
+    ///         .or_else(|| kwargs.get("name"))
+    ///         .and_then(Value::as_str)
+    ///         .unwrap_or("world");
+    ///     let greeting = kwargs.get("greeting")
+    ///         .and_then(Value::as_str)
+    ///         .unwrap_or("Hello");
+    ///
+    ///     Ok(Value::String(format!("{}, {}!", greeting, name)))
+    /// };
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .function_with_kwargs("GREET", greet_function)
+    ///     .into();
+    /// let mut ctx = Context::default();
+    /// let position = Identifier::default();
+    /// let data = Value::Null;
+    ///
+    /// assert_eq!(
+    ///     engine.eval("GREET()", &position, &data, &mut ctx).unwrap(),
+    ///     json!("Hello, world!")
+    /// );
+    /// assert_eq!(
+    ///     engine.eval("GREET(`Balena`, greeting=`Ahoj`)", &position, &data, &mut ctx).unwrap(),
+    ///     json!("Ahoj, Balena!")
+    /// );
+    /// ```
+    ///
+    /// [`FunctionKwargsFn`]: type.FunctionKwargsFn.html
+    pub fn function_with_kwargs<S, F>(self, name: S, function: F) -> EngineBuilder
+    where
+        S: Into<String>,
+        F: Fn(&[Value], &HashMap<String, Value>, &mut Context) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.register_function(name, Function::Kwargs(Arc::new(function)))
+    }
+
+    /// Registers custom function with typed arguments and result
+    ///
+    /// Arguments are converted with the [`FromValue`] trait and the result with the
+    /// [`IntoValue`] trait. Invalid argument type and invalid number of arguments errors
+    /// are generated automatically. Function signature is derived from the types (arguments
+    /// are named `arg1`, `arg2`, ...) and used by the [`check`] method. If a function with
+    /// the name already exists, it will be overwritten.
+    ///
+    /// Typed functions can't access the evaluation context, but they can capture a state and
+    /// they're considered context dependent like the other custom functions. Use the
+    /// [`typed_pure_function`] method to register a pure function.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Custom function name
+    /// * `function` - Custom function
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{
+    ///     ast::Identifier,
+    ///     Engine, EngineBuilder, Context, Value,
+    ///     error::*
+    /// };
+    /// use serde_json::json;
+    ///
+    /// fn greet(name: String, upper: Option<bool>) -> Result<String> {
+    ///     let greeting = format!("Hello {}", name);
+    ///     Ok(if upper.unwrap_or(false) { greeting.to_uppercase() } else { greeting })
+    /// }
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .typed_function("GREET", greet)
+    ///     .into();
+    /// let mut ctx = Context::default();
+    /// let position = Identifier::default();
+    /// let data = Value::Null;
+    ///
+    /// assert_eq!(
+    ///     engine.eval("GREET(`Balena`)", &position, &data, &mut ctx).unwrap(),
+    ///     json!("Hello Balena")
+    /// );
+    /// assert_eq!(
+    ///     engine.eval("GREET(`Balena`, true)", &position, &data, &mut ctx).unwrap(),
+    ///     json!("HELLO BALENA")
+    /// );
+    ///
+    /// let error = engine.eval("GREET(1)", &position, &data, &mut ctx).unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     Error::with_message("invalid argument type")
+    ///         .context("function", "GREET")
+    ///         .context("argument", "arg1")
+    ///         .context("argument value", "1")
+    ///         .context("expected", "string")
+    ///         .to_string()
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if an optional (`Option<T>`) argument precedes a required one.
+    ///
+    /// [`FromValue`]: trait.FromValue.html
+    /// [`IntoValue`]: trait.IntoValue.html
+    /// [`check`]: struct.Engine.html#method.check
+    /// [`typed_pure_function`]: #method.typed_pure_function
+    pub fn typed_function<S, F, Args>(self, name: S, function: F) -> EngineBuilder
     where
         S: Into<String>,
+        F: TypedFunction<Args>,
     {
+        self.register_typed_function(name, function, Metadata::default())
+    }
+
+    /// Registers custom pure function with typed arguments and result
+    ///
+    /// Same as the [`typed_function`] method, but the function is registered as pure. Calls
+    /// of pure functions are memoized and pre-computed by the [`fold_constants`] method. Do
+    /// not use it for functions returning random values, current time, captured state, ...
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Custom function name
+    /// * `function` - Custom function
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{ast::Expression, Engine, EngineBuilder, error::*};
+    ///
+    /// fn add(a: i64, b: i64) -> Result<i64> {
+    ///     Ok(a + b)
+    /// }
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .typed_pure_function("ADD", add)
+    ///     .into();
+    ///
+    /// let mut expression: Expression = "ADD(1, 2) + a".parse().unwrap();
+    /// engine.fold_constants(&mut expression);
+    /// assert_eq!(expression.to_string(), "3 + a");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if an optional (`Option<T>`) argument precedes a required one.
+    ///
+    /// [`typed_function`]: #method.typed_function
+    /// [`fold_constants`]: struct.Engine.html#method.fold_constants
+    pub fn typed_pure_function<S, F, Args>(self, name: S, function: F) -> EngineBuilder
+    where
+        S: Into<String>,
+        F: TypedFunction<Args>,
+    {
+        self.register_typed_function(name, function, Metadata::new(Determinism::Pure))
+    }
+
+    // Registers typed function with the signature derived from the types
+    //
+    // Determinism is taken from the `metadata`, `typed_function` passes the default
+    // (context dependent) one and `typed_pure_function` the pure one.
+    fn register_typed_function<S, F, Args>(self, name: S, function: F, metadata: Metadata) -> EngineBuilder
+    where
+        S: Into<String>,
+        F: TypedFunction<Args>,
+    {
+        let name = name.into().to_uppercase();
+        let function_name = name.clone();
+        let metadata = metadata.signature(F::signature());
+        self.register_function(
+            name.clone(),
+            Function::Positional(Arc::new(move |args, _| function.call_function(&function_name, args))),
+        )
+        .register_function_metadata(name, metadata)
+    }
+
+    /// Registers custom special form
+    ///
+    /// If a function with the name already exists, it will be overwritten. Special forms
+    /// share the namespace with functions.
+    ///
+    /// Visit [`SpecialFormFn`] to learn more about special forms.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Custom special form name
+    /// * `special_form` - Custom special form function
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{
+    ///     ast::{Expression, Identifier},
+    ///     Engine, EngineBuilder, Context, Value,
+    ///     error::*
+    /// };
+    /// use serde_json::json;
+    ///
+    /// // Evaluates the first argument only if the second one can't be evaluated
+    /// fn unless_ok(args: &[Expression], eval: &mut dyn FnMut(&Expression) -> Result<Value>) -> Result<Value> {
+    ///     if args.len() != 2 {
+    ///         return Err(Error::with_message("invalid number of arguments"));
+    ///     }
+    ///     match eval(&args[1]) {
+    ///         Ok(_) => Ok(Value::Null),
+    ///         Err(_) => eval(&args[0]),
+    ///     }
+    /// };
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .special_form("UNLESS_OK", unless_ok)
+    ///     .into();
+    /// let mut ctx = Context::default();
+    /// let position = Identifier::default();
+    /// let data = json!({"ssid": "Balena"});
+    ///
+    /// assert_eq!(
+    ///     engine.eval("UNLESS_OK(`fallback`, missing)", &position, &data, &mut ctx).unwrap(),
+    ///     json!("fallback")
+    /// );
+    /// assert_eq!(
+    ///     engine.eval("UNLESS_OK(missing, ssid)", &position, &data, &mut ctx).unwrap(),
+    ///     Value::Null
+    /// );
+    /// ```
+    ///
+    /// [`SpecialFormFn`]: type.SpecialFormFn.html
+    pub fn special_form<S, F>(self, name: S, special_form: F) -> EngineBuilder
+    where
+        S: Into<String>,
+        F: Fn(&[Expression], &mut dyn FnMut(&Expression) -> Result<Value>) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.register_function(name, Function::SpecialForm(Arc::new(special_form)))
+    }
+
+    fn register_function<S>(self, name: S, function: Function) -> EngineBuilder
+    where
+        S: Into<String>,
+    {
+        let name = name.into().to_uppercase();
         let mut functions = self.functions;
-        functions.insert(name.into().to_uppercase(), function);
+        let mut function_metadata = self.function_metadata;
+        // Overwritten function metadata do not apply to the new one
+        function_metadata.insert(name.clone(), Metadata::default());
+        functions.insert(name, function);
         EngineBuilder {
             functions,
-            filters: self.filters,
-            eval_keyword: self.eval_keyword,
+            function_metadata,
+            ..self
         }
     }
 
+    /// Registers custom function metadata
+    ///
+    /// Metadata of a function are reset when the function is registered again, register
+    /// metadata after the function itself.
+    ///
+    /// Visit [`Metadata`] to learn more about metadata.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Function name
+    /// * `metadata` - Function metadata
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{
+    ///     ast::Identifier,
+    ///     Context, Determinism, Engine, EngineBuilder, Metadata, Value,
+    ///     error::*
+    /// };
+    ///
+    /// fn dice(_: &[Value], _: &mut Context) -> Result<Value> {
+    ///     Ok(Value::from(4))
+    /// }
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .function("DICE", dice)
+    ///     .function_metadata("DICE", Metadata::new(Determinism::Random))
+    ///     .unwrap()
+    ///     .reproducible(true)
+    ///     .into();
+    /// let mut ctx = Context::default();
+    /// let position = Identifier::default();
+    /// let data = Value::Null;
+    ///
+    /// assert!(engine.eval("DICE()", &position, &data, &mut ctx).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with the [`ErrorKind::UnknownFunction`] error if there's no function with the name.
+    ///
+    /// [`Metadata`]: struct.Metadata.html
+    /// [`ErrorKind::UnknownFunction`]: error/enum.ErrorKind.html#variant.UnknownFunction
+    pub fn function_metadata<S>(self, name: S, metadata: Metadata) -> Result<EngineBuilder>
+    where
+        S: Into<String>,
+    {
+        let name = name.into().to_uppercase();
+        if !self.functions.contains_key(&name) {
+            return Err(Error::new(ErrorKind::UnknownFunction, "function not found").context("function", name));
+        }
+        Ok(self.register_function_metadata(name, metadata))
+    }
+
+    fn register_function_metadata<S>(self, name: S, metadata: Metadata) -> EngineBuilder
+    where
+        S: Into<String>,
+    {
+        let mut function_metadata = self.function_metadata;
+        function_metadata.insert(name.into().to_uppercase(), metadata);
+        EngineBuilder {
+            function_metadata,
+            ..self
+        }
+    }
+
+    /// Enables or disables the reproducible mode
+    ///
+    /// Engine in the reproducible mode refuses to call functions and filters which are not
+    /// pure (like `NOW` or `UUIDV4`), the evaluation fails instead. The same expression with
+    /// the same data always evaluates to the same value. Disabled by default.
+    ///
+    /// Visit [`Metadata`] to learn more about functions and filters determinism.
+    ///
+    /// # Arguments
+    ///
+    /// * `reproducible` - `true` to enable the reproducible mode
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{
+    ///     ast::Identifier,
+    ///     Context, Engine, EngineBuilder, Value
+    /// };
+    /// use serde_json::json;
+    ///
+    /// let engine: Engine = EngineBuilder::default()
+    ///     .reproducible(true)
+    ///     .into();
+    /// let mut ctx = Context::default();
+    /// let position = Identifier::default();
+    /// let data = Value::Null;
+    ///
+    /// assert_eq!(
+    ///     engine.eval("`Balena` | UPPER", &position, &data, &mut ctx).unwrap(),
+    ///     json!("BALENA")
+    /// );
+    /// assert!(engine.eval("UUIDV4()", &position, &data, &mut ctx).is_err());
+    /// assert!(engine.eval("NOW()", &position, &data, &mut ctx).is_err());
+    /// ```
+    ///
+    /// [`Metadata`]: struct.Metadata.html
+    pub fn reproducible(self, reproducible: bool) -> EngineBuilder {
+        EngineBuilder { reproducible, ..self }
+    }
+
     /// Registers custom evaluation keyword
     ///
     /// Defaults to `$$formula` if no keyword is registered.
@@ -239,19 +1109,25 @@ impl EngineBuilder {
         S: Into<String>,
     {
         EngineBuilder {
-            functions: self.functions,
-            filters: self.filters,
             eval_keyword: Some(keyword.into()),
+            ..self
         }
     }
 }
 
+// Identifier of the next engine
+static NEXT_ENGINE_ID: AtomicUsize = AtomicUsize::new(0);
+
 impl From<EngineBuilder> for Engine {
     fn from(builder: EngineBuilder) -> Engine {
         Engine {
+            id: NEXT_ENGINE_ID.fetch_add(1, Ordering::Relaxed),
             functions: builder.functions,
             filters: builder.filters,
+            function_metadata: builder.function_metadata,
+            filter_metadata: builder.filter_metadata,
             eval_keyword: builder.eval_keyword.unwrap_or_else(|| "$$formula".into()),
+            reproducible: builder.reproducible,
         }
     }
 }
@@ -0,0 +1,253 @@
+use serde_json::Value;
+
+use crate::{
+    ast::{visit::*, *},
+    builtin::{filter::Filter, function::Function, Signature},
+    engine::{is_placeholder, Engine},
+    error::*,
+};
+
+/// Converts a literal expression to a value
+///
+/// Returns `None` if the expression is not a literal (identifier, function call, ...).
+fn literal_value(expression: &Expression) -> Option<Value> {
+    if expression.negated || !expression.filters.is_empty() {
+        return None;
+    }
+
+    match expression.value {
+        ExpressionValue::Integer(x) => Some(Value::from(x)),
+        ExpressionValue::Float(x) => Some(Value::from(x)),
+        ExpressionValue::Boolean(x) => Some(Value::Bool(x)),
+        ExpressionValue::String(ref x) => Some(Value::String(x.to_string())),
+        _ => None,
+    }
+}
+
+/// Validates function and filter calls without evaluating them
+///
+/// Keeps the first error and skips the rest of the expression.
+struct Checker<'a> {
+    engine: &'a Engine,
+    result: Result<()>,
+}
+
+impl<'a> Checker<'a> {
+    fn check_function(&self, call: &FunctionCall) -> Result<()> {
+        let f = self.engine.functions.get(&call.name).ok_or_else(|| {
+            Error::new(ErrorKind::UnknownFunction, "function not found").context("function", call.name.to_string())
+        })?;
+
+        self.engine
+            .validate_determinism("function", &call.name, self.engine.function_determinism(&call.name))?;
+
+        if !call.kwargs.is_empty() {
+            match f {
+                Function::Positional(_) => {
+                    return Err(
+                        Error::new(ErrorKind::InvalidCall, "function does not accept named arguments")
+                            .context("function", call.name.to_string()),
+                    );
+                }
+                Function::SpecialForm(_) => {
+                    return Err(
+                        Error::new(ErrorKind::InvalidCall, "special form does not accept named arguments")
+                            .context("function", call.name.to_string()),
+                    );
+                }
+                Function::Kwargs(_) => {}
+            };
+        }
+
+        match self
+            .engine
+            .function_metadata
+            .get(&call.name)
+            .and_then(|x| x.signature.as_ref())
+        {
+            Some(signature) => check_signature("function", call, signature),
+            None => Ok(()),
+        }
+    }
+
+    fn check_filter(&self, call: &FunctionCall) -> Result<()> {
+        let f = self.engine.filters.get(&call.name).ok_or_else(|| {
+            Error::new(ErrorKind::UnknownFilter, "filter not found").context("filter", call.name.to_string())
+        })?;
+
+        self.engine
+            .validate_determinism("filter", &call.name, self.engine.filter_determinism(&call.name))?;
+
+        if let Filter::Positional(_) = f {
+            if !call.kwargs.is_empty() {
+                return Err(
+                    Error::new(ErrorKind::InvalidCall, "filter does not accept named arguments")
+                        .context("filter", call.name.to_string()),
+                );
+            }
+        }
+
+        match self
+            .engine
+            .filter_metadata
+            .get(&call.name)
+            .and_then(|x| x.signature.as_ref())
+        {
+            Some(signature) => check_signature("filter", call, signature),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Validates call arguments against the signature
+///
+/// Placeholders are counted as arguments, but their type is unknown. Type of other arguments
+/// is validated only if they're literals.
+fn check_signature(kind: &'static str, call: &FunctionCall, signature: &Signature) -> Result<()> {
+    if let Some(max) = signature.max_args() {
+        if call.args.len() > max {
+            return Err(Error::new(ErrorKind::InvalidCall, "invalid number of arguments")
+                .context(kind, call.name.to_string())
+                .context("argument count", format!("{}", call.args.len()))
+                .context("expected", format!("{}", max)));
+        }
+    }
+
+    for name in call.kwargs.keys() {
+        match signature.args.iter().position(|x| &x.name == name) {
+            None => {
+                let names: Vec<&str> = signature.args.iter().map(|x| x.name.as_str()).collect();
+                return Err(Error::new(ErrorKind::InvalidCall, "unknown named argument")
+                    .context(kind, call.name.to_string())
+                    .context("argument", name.to_string())
+                    .context("expected", names.join(", ")));
+            }
+            Some(index) if index < call.args.len() => {
+                return Err(
+                    Error::new(ErrorKind::InvalidCall, "argument passed by position and by name")
+                        .context(kind, call.name.to_string())
+                        .context("argument", name.to_string())
+                        .context("position", format!("{}", index)),
+                );
+            }
+            Some(_) => {}
+        };
+    }
+
+    for (index, arg) in signature.args.iter().enumerate() {
+        if arg.required && index >= call.args.len() && !call.kwargs.contains_key(&arg.name) {
+            return Err(Error::new(ErrorKind::InvalidCall, "missing argument")
+                .context(kind, call.name.to_string())
+                .context("argument", arg.name.to_string()));
+        }
+    }
+
+    let positional = call
+        .args
+        .iter()
+        .enumerate()
+        .filter_map(|(index, arg)| signature.arg_type(index).map(|x| (x, arg)));
+    let named = call.kwargs.iter().filter_map(|(name, arg)| {
+        signature
+            .args
+            .iter()
+            .find(|x| &x.name == name)
+            .map(|x| (x.value_type, arg))
+    });
+
+    for (value_type, arg) in positional.chain(named) {
+        if let Some(value) = literal_value(arg) {
+            if !value_type.matches(&value) {
+                return Err(Error::new(ErrorKind::TypeMismatch, "invalid argument type")
+                    .context(kind, call.name.to_string())
+                    .context("argument value", value.to_string())
+                    .context("expected", value_type.to_string()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl<'a, 'ast> Visitor<'ast> for Checker<'a> {
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        if self.result.is_ok() {
+            walk_expression(self, expression);
+        }
+    }
+
+    fn visit_function_call(&mut self, call: &'ast FunctionCall) {
+        if self.result.is_ok() {
+            self.result = self.check_function(call);
+        }
+        walk_function_call(self, call);
+    }
+
+    fn visit_filter(&mut self, filter: &'ast FunctionCall) {
+        if self.result.is_ok() {
+            // Filter with a placeholder calls a function
+            self.result = if filter.args.iter().any(is_placeholder) {
+                self.check_function(filter)
+            } else {
+                self.check_filter(filter)
+            };
+        }
+
+        for arg in filter.args.iter().filter(|x| !is_placeholder(x)) {
+            self.visit_expression(arg);
+        }
+        for arg in filter.kwargs.values() {
+            self.visit_expression(arg);
+        }
+    }
+}
+
+impl Engine {
+    /// Checks an expression without evaluating it
+    ///
+    /// Expression is parsed and all function and filter calls are validated against
+    /// the registered functions, filters and their signatures (see [`Metadata`]). No data
+    /// and no context are involved. Following problems are reported:
+    ///
+    /// * unknown functions and filters,
+    /// * invalid number of arguments,
+    /// * unknown or missing named arguments,
+    /// * literal arguments of an invalid type,
+    /// * non-deterministic calls in the reproducible mode.
+    ///
+    /// Functions and filters without a signature are checked for existence only. The first
+    /// problem found is returned. An expression which passes the check can still fail
+    /// during the evaluation (missing data, invalid input type, ...).
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - An expression to check
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::Engine;
+    ///
+    /// let engine = Engine::default();
+    ///
+    /// # #[cfg(feature = "datetime")]
+    /// assert!(engine.check("POW(2, exponent) | DATE(tz=`Europe/Prague`)").is_ok());
+    ///
+    /// assert!(engine.check("FOO(1)").is_err());
+    /// assert!(engine.check("POW(2)").is_err());
+    /// assert!(engine.check("POW(2, `10`)").is_err());
+    /// # #[cfg(feature = "datetime")]
+    /// assert!(engine.check("ts | DATE(timezone=`Europe/Prague`)").is_err());
+    /// ```
+    ///
+    /// [`Metadata`]: struct.Metadata.html
+    pub fn check(&self, expression: &str) -> Result<()> {
+        let expression: Expression = expression.parse()?;
+        let mut checker = Checker {
+            engine: self,
+            result: Ok(()),
+        };
+        checker.visit_expression(&expression);
+        checker.result
+    }
+}
@@ -0,0 +1,81 @@
+use std::str::FromStr;
+
+use crate::{ast::Expression, error::*};
+
+/// A pre-compiled (parsed) expression
+///
+/// [`Engine::eval`] parses an expression on every call. Compile an expression once and
+/// evaluate it many times with the [`Engine::eval_compiled`] method, against different
+/// data, positions, ...
+///
+/// # Examples
+///
+/// ```rust
+/// use balena_temen::{
+///     ast::Identifier,
+///     CompiledExpression, Engine, Context, Value
+/// };
+/// use serde_json::json;
+///
+/// let engine = Engine::default();
+/// let mut ctx = Context::default();
+/// let position = Identifier::default();
+///
+/// let compiled: CompiledExpression = "numbers.one + numbers.two".parse().unwrap();
+///
+/// assert_eq!(compiled.as_str(), "numbers.one + numbers.two");
+///
+/// for i in 0..10 {
+///     let data = json!({"numbers": {"one": i, "two": 2}});
+///     assert_eq!(
+///         engine.eval_compiled(&compiled, &position, &data, &mut ctx).unwrap(),
+///         json!(i + 2)
+///     );
+/// }
+/// ```
+///
+/// [`Engine::eval`]: struct.Engine.html#method.eval
+/// [`Engine::eval_compiled`]: struct.Engine.html#method.eval_compiled
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompiledExpression {
+    source: String,
+    expression: Expression,
+}
+
+impl CompiledExpression {
+    /// Compiles an expression
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - An expression to compile
+    pub fn new<S>(source: S) -> Result<CompiledExpression>
+    where
+        S: Into<String>,
+    {
+        let source = source.into();
+        let expression = source.parse()?;
+        Ok(CompiledExpression { source, expression })
+    }
+
+    /// Returns the expression source
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the parsed expression
+    pub fn expression(&self) -> &Expression {
+        &self.expression
+    }
+
+    pub(crate) fn expression_mut(&mut self) -> &mut Expression {
+        &mut self.expression
+    }
+}
+
+impl FromStr for CompiledExpression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<CompiledExpression> {
+        CompiledExpression::new(s)
+    }
+}
@@ -0,0 +1,196 @@
+use serde_json::Value;
+
+use crate::{
+    ast::*,
+    context::Context,
+    engine::{
+        helper::{get_value, replace_value, set_value, Formulas},
+        Engine,
+    },
+    error::*,
+};
+
+/// Checks that the position can be used to update a template value
+fn validate_position(position: &Identifier) -> Result<Identifier> {
+    let canonical = position.canonicalize(&Identifier::default())?;
+
+    let valid = canonical.values.iter().all(|x| match x {
+        IdentifierValue::Name(_) => true,
+        IdentifierValue::Index(index) => *index >= 0,
+        _ => false,
+    });
+
+    if valid {
+        Ok(canonical)
+    } else {
+        Err(Error::new(ErrorKind::InvalidUpdate, "unable to update value")
+            .context("reason", "indirect lookups and negative indexes are not allowed")
+            .context("position", position.to_string()))
+    }
+}
+
+/// An incremental template evaluator
+///
+/// Evaluator keeps the template, formulas with their dependencies and the last evaluation
+/// result. When template values are updated, only formulas depending on these values
+/// (directly or transitively) are evaluated again. Other formulas keep their values, even
+/// if they are not deterministic (`UUIDV4()`, `NOW()`, ...).
+///
+/// Formulas which can't be evaluated are left untouched in the result, see the
+/// [`evaluate_partial`] function.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(all(feature = "slugify", feature = "uuid"))]
+/// # {
+/// use balena_temen::{ast::Identifier, Context, Engine, Evaluator};
+/// use serde_json::json;
+///
+/// let template = json!({
+///     "ssid": "Balena 5G",
+///     "id": {
+///         "$$formula": "super.ssid | SLUGIFY"
+///     },
+///     "uuid": {
+///         "$$formula": "UUIDV4()"
+///     }
+/// });
+///
+/// let engine = Engine::default();
+/// let mut context = Context::default();
+/// let mut evaluator = Evaluator::new(template, &engine, &mut context).unwrap();
+///
+/// assert_eq!(evaluator.result()["id"], json!("balena-5g"));
+/// let uuid = evaluator.result()["uuid"].clone();
+///
+/// evaluator
+///     .update(vec![(Identifier::default().name("ssid"), json!("Zrzka"))], &mut context)
+///     .unwrap();
+///
+/// assert_eq!(evaluator.result()["ssid"], json!("Zrzka"));
+/// assert_eq!(evaluator.result()["id"], json!("zrzka"));
+/// // Does not depend on `ssid`, not evaluated again
+/// assert_eq!(evaluator.result()["uuid"], uuid);
+/// # }
+/// ```
+///
+/// [`evaluate_partial`]: fn.evaluate_partial.html
+pub struct Evaluator<'a> {
+    engine: &'a Engine,
+    template: Value,
+    formulas: Formulas,
+    result: Value,
+    errors: Vec<Option<Error>>,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Creates new evaluator and evaluates the template
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - A JSON to evaluate
+    /// * `engine` - An engine to use
+    /// * `context` - An evaluation context
+    pub fn new(template: Value, engine: &'a Engine, context: &mut Context) -> Result<Evaluator<'a>> {
+        let formulas = Formulas::new(&template, engine.eval_keyword())?;
+
+        let mut evaluator = Evaluator {
+            engine,
+            result: Value::Null,
+            errors: vec![],
+            template,
+            formulas,
+        };
+        evaluator.evaluate_all(context);
+        Ok(evaluator)
+    }
+
+    /// Returns the template (with all updates applied)
+    pub fn template(&self) -> &Value {
+        &self.template
+    }
+
+    /// Returns the last evaluation result
+    pub fn result(&self) -> &Value {
+        &self.result
+    }
+
+    /// Returns the list of formulas which failed to evaluate
+    pub fn failures(&self) -> Vec<FormulaError> {
+        self.formulas.failures(&self.errors)
+    }
+
+    /// Updates template values and evaluates affected formulas
+    ///
+    /// Formula is affected if it reads any of the updated values (or their parent objects or
+    /// nested values) or if it depends on another affected formula.
+    ///
+    /// All formulas are evaluated again if a formula, its parent object or nested value is
+    /// updated or if a new value contains formulas.
+    ///
+    /// Changes are applied all or nothing. If any of them fails, the template and the result
+    /// are left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `changes` - List of (position, new value) pairs, position must not be relative,
+    ///   must not contain indirect lookups and negative indexes
+    /// * `context` - An evaluation context
+    pub fn update<I>(&mut self, changes: I, context: &mut Context) -> Result<()>
+    where
+        I: IntoIterator<Item = (Identifier, Value)>,
+    {
+        let changes = changes
+            .into_iter()
+            .map(|(position, value)| Ok((validate_position(&position)?, value)))
+            .collect::<Result<Vec<(Identifier, Value)>>>()?;
+
+        // Changes are applied to copies, the evaluator is left untouched if any of them fails
+        let mut template = self.template.clone();
+        let mut rebuild = false;
+        for (position, value) in changes.iter() {
+            rebuild = rebuild
+                || self.formulas.graph().overlaps_formula(position)
+                || Formulas::new(value, self.engine.eval_keyword())?.len() > 0;
+            set_value(&mut template, value.clone(), position)?;
+        }
+
+        if rebuild {
+            self.formulas = Formulas::new(&template, self.engine.eval_keyword())?;
+            self.template = template;
+            self.evaluate_all(context);
+            return Ok(());
+        }
+
+        let mut result = self.result.clone();
+        for (position, value) in changes.iter() {
+            set_value(&mut result, value.clone(), position)?;
+        }
+        self.template = template;
+
+        let positions: Vec<Identifier> = changes.into_iter().map(|(position, _)| position).collect();
+
+        // Restore formulas to evaluate them again
+        let affected = self.formulas.graph().affected(&positions);
+        for (idx, _) in affected.iter().enumerate().filter(|(_, x)| **x) {
+            let position = self.formulas.position(idx);
+            if let Some(formula) = get_value(&self.template, position) {
+                result = replace_value(result, formula.clone(), position);
+            }
+        }
+
+        self.result = self
+            .formulas
+            .evaluate(result, &affected, &mut self.errors, self.engine, context);
+        Ok(())
+    }
+
+    fn evaluate_all(&mut self, context: &mut Context) {
+        let selected = vec![true; self.formulas.len()];
+        self.errors = vec![None; self.formulas.len()];
+        self.result = self
+            .formulas
+            .evaluate(self.template.clone(), &selected, &mut self.errors, self.engine, context);
+    }
+}
@@ -0,0 +1,170 @@
+use serde_json::Value;
+
+use crate::{
+    ast::{visit::*, *},
+    context::Context,
+    engine::{is_placeholder, Engine},
+};
+
+/// Checks if an expression can be evaluated without data and context
+///
+/// Expression is constant if it doesn't contain identifiers and calls pure functions and
+/// filters only. Placeholders are allowed as filter function arguments only.
+struct ConstantCheck<'a> {
+    engine: &'a Engine,
+    constant: bool,
+}
+
+impl<'a, 'ast> Visitor<'ast> for ConstantCheck<'a> {
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        if self.constant {
+            walk_expression(self, expression);
+        }
+    }
+
+    fn visit_identifier(&mut self, _identifier: &'ast Identifier) {
+        self.constant = false;
+    }
+
+    fn visit_placeholder(&mut self) {
+        self.constant = false;
+    }
+
+    fn visit_function_call(&mut self, call: &'ast FunctionCall) {
+        if !self.engine.is_pure_function(&call.name) {
+            self.constant = false;
+        }
+        walk_function_call(self, call);
+    }
+
+    fn visit_filter(&mut self, filter: &'ast FunctionCall) {
+        // Filter with a placeholder calls a function
+        let pure = if filter.args.iter().any(is_placeholder) {
+            self.engine.is_pure_function(&filter.name)
+        } else {
+            self.engine.is_pure_filter(&filter.name)
+        };
+        if !pure {
+            self.constant = false;
+        }
+
+        for arg in filter.args.iter().filter(|x| !is_placeholder(x)) {
+            self.visit_expression(arg);
+        }
+        for arg in filter.kwargs.values() {
+            self.visit_expression(arg);
+        }
+    }
+}
+
+/// Converts a value to a literal expression value
+///
+/// Returns `None` if there's no literal for the value (null, array, object, string
+/// containing all quote characters, ...).
+fn literal(value: Value) -> Option<ExpressionValue> {
+    match value {
+        Value::Bool(x) => Some(ExpressionValue::Boolean(x)),
+        Value::String(ref x) if ['`', '"', '\''].iter().all(|q| x.contains(*q)) => None,
+        Value::String(x) => Some(ExpressionValue::String(x)),
+        Value::Number(x) => match x.as_i64() {
+            Some(x) => Some(ExpressionValue::Integer(x)),
+            None if x.is_f64() => x.as_f64().map(ExpressionValue::Float),
+            None => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_literal(expression: &Expression) -> bool {
+    !expression.negated
+        && expression.filters.is_empty()
+        && matches!(
+            expression.value,
+            ExpressionValue::Integer(_)
+                | ExpressionValue::Float(_)
+                | ExpressionValue::Boolean(_)
+                | ExpressionValue::String(_)
+        )
+}
+
+/// Replaces constant sub-expressions with literals
+struct ConstantFolder<'a> {
+    engine: &'a Engine,
+}
+
+impl<'a> ConstantFolder<'a> {
+    fn is_constant(&self, expression: &Expression) -> bool {
+        let mut check = ConstantCheck {
+            engine: self.engine,
+            constant: true,
+        };
+        check.visit_expression(expression);
+        check.constant
+    }
+
+    fn fold(&self, expression: &Expression) -> Option<ExpressionValue> {
+        if is_literal(expression) || !self.is_constant(expression) {
+            return None;
+        }
+
+        // Failing expressions are kept untouched to fail during evaluation
+        let value = self
+            .engine
+            .eval_expression(
+                expression,
+                &Identifier::default(),
+                &Value::Null,
+                &mut Context::default(),
+            )
+            .ok()?;
+        literal(value.into_owned())
+    }
+}
+
+impl<'a> VisitorMut for ConstantFolder<'a> {
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        if let Some(value) = self.fold(expression) {
+            *expression = Expression::new(value);
+        } else {
+            walk_expression_mut(self, expression);
+        }
+    }
+}
+
+impl Engine {
+    /// Pre-computes constant sub-expressions
+    ///
+    /// Sub-expressions without identifiers, which call pure functions and filters only, are
+    /// evaluated and replaced with literals. All builtin functions and filters are pure except
+    /// `UUIDV4` and `NOW`. Custom functions and filters are pure only if they're registered
+    /// with the pure [`Metadata`].
+    ///
+    /// Sub-expressions which fail to evaluate and sub-expressions evaluated to a value which
+    /// can't be expressed as a literal (`null`, arrays, objects) are kept untouched. The result
+    /// of the evaluation is the same, folded or not.
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - An expression to fold
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{ast::Expression, Engine};
+    ///
+    /// let engine = Engine::default();
+    ///
+    /// let mut expression: Expression = "POW(2, 10) * 3 + count".parse().unwrap();
+    /// engine.fold_constants(&mut expression);
+    /// assert_eq!(expression.to_string(), "3072 + count");
+    ///
+    /// let mut expression: Expression = "`Balena` | LOWER == UUIDV4() | LOWER".parse().unwrap();
+    /// engine.fold_constants(&mut expression);
+    /// assert_eq!(expression.to_string(), "`balena` == UUIDV4() | LOWER");
+    /// ```
+    ///
+    /// [`Metadata`]: struct.Metadata.html
+    pub fn fold_constants(&self, expression: &mut Expression) {
+        ConstantFolder { engine: self }.visit_expression_mut(expression);
+    }
+}
@@ -0,0 +1,137 @@
+use serde_json::Value;
+
+use crate::{
+    ast::{visit::walk_filter_mut, *},
+    engine::{
+        helper::{set_value, Formulas},
+        Engine,
+    },
+    error::*,
+};
+
+// Uppercases names of all filters, including filters in nested expressions
+struct UppercaseFilters;
+
+impl VisitorMut for UppercaseFilters {
+    fn visit_filter_mut(&mut self, filter: &mut FunctionCall) {
+        filter.name = filter.name.to_ascii_uppercase();
+        walk_filter_mut(self, filter);
+    }
+}
+
+/// Formats an expression
+///
+/// Whitespace is normalized, parentheses which are not required are removed, strings are
+/// quoted with backquotes (unless they contain one) and filter names are uppercased.
+///
+/// # Arguments
+///
+/// * `expression` - An expression to format
+///
+/// # Examples
+///
+/// ```rust
+/// use balena_temen::format_expression;
+///
+/// assert_eq!(
+///     format_expression("(  a+b )*c|Slugify(sep='_')").unwrap(),
+///     "(a + b) * c | SLUGIFY(sep=`_`)"
+/// );
+/// assert_eq!(
+///     format_expression("super.ssid~'-'~super.id|Slugify").unwrap(),
+///     "super.ssid ~ `-` ~ super.id | SLUGIFY"
+/// );
+/// ```
+pub fn format_expression(expression: &str) -> Result<String> {
+    let mut expression: Expression = expression.parse()?;
+    UppercaseFilters.visit_expression_mut(&mut expression);
+    Ok(expression.to_string())
+}
+
+/// Formats all document formulas
+///
+/// Every formula is formatted with the [`format_expression`] function. Nothing else is
+/// changed.
+///
+/// Fails if any formula can't be parsed, the error contains the list of all failures
+/// (see [`Error::failures`]).
+///
+/// # Arguments
+///
+/// * `data` - A JSON
+///
+/// # Examples
+///
+/// ```rust
+/// use balena_temen::format_document;
+/// use serde_json::json;
+///
+/// let data = json!({
+///     "ssid": "Balena 5G",
+///     "id": {
+///         "$$formula": "super.ssid|Slugify"
+///     },
+///     "names": [
+///         {
+///             "$$formula": "'a'~\"b\""
+///         }
+///     ]
+/// });
+///
+/// assert_eq!(
+///     format_document(data).unwrap(),
+///     json!({
+///         "ssid": "Balena 5G",
+///         "id": {
+///             "$$formula": "super.ssid | SLUGIFY"
+///         },
+///         "names": [
+///             {
+///                 "$$formula": "`a` ~ `b`"
+///             }
+///         ]
+///     })
+/// );
+/// ```
+///
+/// [`format_expression`]: fn.format_expression.html
+/// [`Error::failures`]: error/struct.Error.html#method.failures
+pub fn format_document(data: Value) -> Result<Value> {
+    format_document_with_engine(data, &Engine::default())
+}
+
+/// Formats all document formulas with custom [`Engine`]
+///
+/// Check the [`format_document`] function for more details and examples.
+///
+/// # Arguments
+///
+/// * `data` - A JSON
+/// * `engine` - An engine to use (evaluation keyword)
+///
+/// [`format_document`]: fn.format_document.html
+/// [`Engine`]: struct.Engine.html
+pub fn format_document_with_engine(data: Value, engine: &Engine) -> Result<Value> {
+    let keyword = engine.eval_keyword();
+    let formulas = Formulas::new(&data, keyword)?;
+
+    let mut formatted = vec![];
+    let mut failures = vec![];
+    for idx in 0..formulas.len() {
+        let position = formulas.position(idx);
+        match format_expression(formulas.source(idx)) {
+            Ok(expression) => formatted.push((position.clone().name(keyword), expression)),
+            Err(e) => failures.push(FormulaError::new(position.clone(), formulas.source(idx), e)),
+        };
+    }
+
+    if !failures.is_empty() {
+        return Err(Error::with_failures(failures));
+    }
+
+    let mut data = data;
+    for (position, expression) in formatted {
+        set_value(&mut data, Value::String(expression), &position)?;
+    }
+    Ok(data)
+}
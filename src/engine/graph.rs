@@ -0,0 +1,703 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    ast::*,
+    engine::{helper::Formulas, Engine},
+    error::*,
+};
+
+/// Dependency kind
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Edge {
+    /// Dependency identifier matches the formula position exactly
+    Exact,
+    /// Dependency identifier contains an indirect lookup or a negative index, which can't
+    /// be resolved statically, and it may match the formula position
+    Wildcard,
+}
+
+/// Checks if a dependency identifier refers to the position or to any of its ancestors / descendants
+///
+/// Both identifiers must be canonical.
+fn overlap(dependency: &Identifier, position: &Identifier) -> Option<Edge> {
+    let mut edge = Edge::Exact;
+
+    for values in dependency.values.iter().zip(position.values.iter()) {
+        match values {
+            (IdentifierValue::Name(ref lhs), IdentifierValue::Name(ref rhs)) if lhs == rhs => {}
+            (IdentifierValue::Index(lhs), IdentifierValue::Index(rhs)) if lhs == rhs => {}
+            (IdentifierValue::Index(lhs), IdentifierValue::Index(_)) if *lhs < 0 => edge = Edge::Wildcard,
+            (IdentifierValue::Identifier(_), IdentifierValue::Name(_))
+            | (IdentifierValue::Identifier(_), IdentifierValue::Index(_)) => edge = Edge::Wildcard,
+            _ => return None,
+        };
+    }
+
+    Some(edge)
+}
+
+/// Static formulas dependency graph
+///
+/// Nodes are formulas and edges are dependencies between them. Formula depends on another
+/// formula if it reads the formula value, its ancestor (`wifi` if the formula position is
+/// `wifi.id`) or descendant (`wifi.id.first` if the formula position is `wifi.id`).
+pub(crate) struct Graph {
+    /// Formula positions
+    positions: Vec<Identifier>,
+    /// List of identifiers every formula reads
+    identifiers: Vec<Vec<Identifier>>,
+    /// List of dependencies (formula index, kind) for every formula
+    dependencies: Vec<Vec<(usize, Edge)>>,
+    /// List of dependents (formula indexes) for every formula
+    dependents: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    /// Creates new dependency graph
+    ///
+    /// Formulas without an expression (syntax error) or with dependencies which can't be
+    /// extracted (invalid `super` usage, ...) are treated as formulas without dependencies.
+    /// Their evaluation fails anyway.
+    ///
+    /// # Arguments
+    ///
+    /// * `formulas` - List of formula (position, expression) pairs
+    pub(crate) fn new<'a, I>(formulas: I) -> Graph
+    where
+        I: IntoIterator<Item = (&'a Identifier, Option<&'a Expression>)>,
+    {
+        let formulas: Vec<_> = formulas.into_iter().collect();
+        let positions: Vec<Identifier> = formulas.iter().map(|(position, _)| (*position).clone()).collect();
+
+        // Formula indexes grouped by the first position value name, other positions
+        // (empty, starting with an index) are stored under the `None` key
+        let mut buckets: HashMap<Option<&str>, Vec<usize>> = HashMap::new();
+        for (idx, position) in positions.iter().enumerate() {
+            let key = match position.values.first() {
+                Some(IdentifierValue::Name(ref name)) => Some(name.as_str()),
+                _ => None,
+            };
+            buckets.entry(key).or_default().push(idx);
+        }
+        let all: Vec<usize> = (0..positions.len()).collect();
+        let empty = vec![];
+
+        let mut identifiers = vec![];
+        let mut dependencies = vec![];
+        for (position, expression) in formulas.iter() {
+            let mut formula_dependencies: Vec<(usize, Edge)> = vec![];
+
+            let formula_identifiers = expression
+                .map(|x| x.dependencies(position).unwrap_or_default())
+                .unwrap_or_default();
+
+            for dependency in formula_identifiers.iter() {
+                let candidates: Vec<&usize> = match dependency.values.first() {
+                    Some(IdentifierValue::Name(ref name)) => buckets
+                        .get(&Some(name.as_str()))
+                        .unwrap_or(&empty)
+                        .iter()
+                        .chain(buckets.get(&None).unwrap_or(&empty).iter())
+                        .collect(),
+                    _ => all.iter().collect(),
+                };
+
+                for idx in candidates {
+                    if let Some(edge) = overlap(dependency, &positions[*idx]) {
+                        match formula_dependencies.iter_mut().find(|(x, _)| x == idx) {
+                            Some(existing) if edge == Edge::Exact => existing.1 = Edge::Exact,
+                            Some(_) => {}
+                            None => formula_dependencies.push((*idx, edge)),
+                        };
+                    }
+                }
+            }
+
+            identifiers.push(formula_identifiers);
+            dependencies.push(formula_dependencies);
+        }
+
+        let mut dependents: Vec<Vec<usize>> = vec![vec![]; positions.len()];
+        for (idx, formula_dependencies) in dependencies.iter().enumerate() {
+            for (dependency, _) in formula_dependencies.iter() {
+                dependents[*dependency].push(idx);
+            }
+        }
+
+        Graph {
+            positions,
+            identifiers,
+            dependencies,
+            dependents,
+        }
+    }
+
+    /// Returns formula exact dependencies (formula indexes)
+    pub(crate) fn exact_dependencies(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        self.dependencies[idx]
+            .iter()
+            .filter(|(_, edge)| *edge == Edge::Exact)
+            .map(|(x, _)| *x)
+    }
+
+    /// Checks if a position refers to any formula, its ancestor or descendant
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - A canonical position
+    pub(crate) fn overlaps_formula(&self, position: &Identifier) -> bool {
+        self.positions.iter().any(|x| overlap(position, x).is_some())
+    }
+
+    /// Returns formulas affected by changes
+    ///
+    /// Formula is affected if it reads any of the changed values (or their ancestors /
+    /// descendants) or if it depends on another affected formula.
+    ///
+    /// # Arguments
+    ///
+    /// * `changes` - List of changed positions (must be canonical)
+    pub(crate) fn affected(&self, changes: &[Identifier]) -> Vec<bool> {
+        let mut affected: Vec<bool> = self
+            .identifiers
+            .iter()
+            .map(|identifiers| {
+                identifiers
+                    .iter()
+                    .any(|identifier| changes.iter().any(|change| overlap(identifier, change).is_some()))
+            })
+            .collect();
+
+        // Propagate to dependent formulas, every formula is visited once
+        let mut queue: VecDeque<usize> = (0..affected.len()).filter(|x| affected[*x]).collect();
+        while let Some(idx) = queue.pop_front() {
+            for dependent in self.dependents[idx].iter() {
+                if !affected[*dependent] {
+                    affected[*dependent] = true;
+                    queue.push_back(*dependent);
+                }
+            }
+        }
+
+        affected
+    }
+
+    /// Returns strongly connected components in the evaluation order
+    ///
+    /// Every component is evaluated after all components it depends on. Formula indexes
+    /// in a component are sorted.
+    ///
+    /// Tarjan's strongly connected components algorithm is used.
+    pub(crate) fn components(&self) -> Vec<Vec<usize>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: 0,
+            indexes: vec![None; self.positions.len()],
+            low_links: vec![0; self.positions.len()],
+            stack: vec![],
+            on_stack: vec![false; self.positions.len()],
+            components: vec![],
+        };
+
+        for idx in 0..self.positions.len() {
+            if tarjan.indexes[idx].is_none() {
+                tarjan.connect(idx);
+            }
+        }
+
+        tarjan.components
+    }
+
+    /// Checks if a component contains a cycle
+    ///
+    /// # Arguments
+    ///
+    /// * `component` - A strongly connected component
+    pub(crate) fn is_cyclic(&self, component: &[usize]) -> bool {
+        component.len() > 1 || self.dependencies[component[0]].iter().any(|(x, _)| *x == component[0])
+    }
+
+    /// Returns a cycle consisting of exact dependencies only
+    ///
+    /// The cycle starts and ends with the same formula index.
+    ///
+    /// # Arguments
+    ///
+    /// * `component` - A strongly connected component
+    pub(crate) fn exact_cycle(&self, component: &[usize]) -> Option<Vec<usize>> {
+        for start in component {
+            // Breadth first search from the start back to the start
+            let mut parents: HashMap<usize, usize> = HashMap::new();
+            let mut queue = VecDeque::new();
+            queue.push_back(*start);
+
+            while let Some(current) = queue.pop_front() {
+                for (next, edge) in self.dependencies[current].iter() {
+                    if *edge != Edge::Exact || !component.contains(next) || parents.contains_key(next) {
+                        continue;
+                    }
+
+                    parents.insert(*next, current);
+
+                    if next == start {
+                        let mut cycle = vec![*start];
+                        let mut idx = current;
+                        while idx != *start {
+                            cycle.push(idx);
+                            idx = parents[&idx];
+                        }
+                        cycle.push(*start);
+                        cycle.reverse();
+                        return Some(cycle);
+                    }
+
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// A document formula with its dependencies
+#[derive(Clone, Debug, PartialEq)]
+pub struct DependencyNode {
+    /// Formula position
+    pub position: Identifier,
+    /// Formula expression (`$$formula` value)
+    pub expression: String,
+    /// Formulas (node indexes) this formula depends on
+    pub dependencies: Vec<usize>,
+    /// Formulas (node indexes) this formula may depend on
+    ///
+    /// Dependencies with indirect lookups (`networks[index]`) or negative indexes can't be
+    /// resolved statically. They're listed here if they may refer to another formula.
+    pub possible_dependencies: Vec<usize>,
+}
+
+/// A document formulas dependency graph
+///
+/// Nodes are document formulas and edges are dependencies between them. Formula depends on
+/// another formula if it reads the formula value, its parent object or any nested value.
+/// Dependencies are extracted statically, formulas are not evaluated.
+///
+/// The graph can be exported as a [Graphviz] DOT graph or as a JSON adjacency list.
+///
+/// # Examples
+///
+/// ```rust
+/// use balena_temen::{ast::Identifier, DependencyGraph, Engine};
+/// use serde_json::json;
+///
+/// let data = json!({
+///     "ssid": "Balena 5G",
+///     "id": {
+///         "$$formula": "super.ssid | SLUGIFY"
+///     },
+///     "hostname": {
+///         "$$formula": "`balena-` ~ id"
+///     }
+/// });
+///
+/// let graph = DependencyGraph::new(&data, &Engine::default()).unwrap();
+///
+/// assert_eq!(graph.nodes().len(), 2);
+/// assert_eq!(graph.nodes()[0].position, Identifier::default().name("hostname"));
+/// assert_eq!(graph.nodes()[0].dependencies, vec![1]);
+///
+/// assert_eq!(
+///     graph.to_json(),
+///     json!({
+///         "hostname": {
+///             "expression": "`balena-` ~ id",
+///             "dependencies": ["id"],
+///             "possibleDependencies": []
+///         },
+///         "id": {
+///             "expression": "super.ssid | SLUGIFY",
+///             "dependencies": [],
+///             "possibleDependencies": []
+///         }
+///     })
+/// );
+/// ```
+///
+/// [Graphviz]: https://www.graphviz.org/
+#[derive(Clone, Debug, PartialEq)]
+pub struct DependencyGraph {
+    nodes: Vec<DependencyNode>,
+}
+
+// Escapes a string for the DOT quoted string
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+impl DependencyGraph {
+    /// Creates new dependency graph of all document formulas
+    ///
+    /// Nodes are in the document order.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - A JSON
+    /// * `engine` - An engine to use (evaluation keyword)
+    pub fn new(data: &Value, engine: &Engine) -> Result<DependencyGraph> {
+        let formulas = Formulas::new(data, engine.eval_keyword())?;
+        let graph = formulas.graph();
+
+        let nodes = (0..formulas.len())
+            .map(|idx| {
+                let dependencies = |kind| {
+                    graph.dependencies[idx]
+                        .iter()
+                        .filter(|(_, edge)| *edge == kind)
+                        .map(|(x, _)| *x)
+                        .collect()
+                };
+
+                DependencyNode {
+                    position: formulas.position(idx).clone(),
+                    expression: formulas.source(idx).to_string(),
+                    dependencies: dependencies(Edge::Exact),
+                    possible_dependencies: dependencies(Edge::Wildcard),
+                }
+            })
+            .collect();
+
+        Ok(DependencyGraph { nodes })
+    }
+
+    /// Returns graph nodes (formulas)
+    pub fn nodes(&self) -> &[DependencyNode] {
+        &self.nodes
+    }
+
+    /// Exports the graph as a Graphviz DOT graph
+    ///
+    /// Nodes are labelled with the formula position and expression. Edges point from
+    /// a formula to its dependencies, possible dependencies are dashed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{DependencyGraph, Engine};
+    /// use serde_json::json;
+    ///
+    /// let data = json!({
+    ///     "a": {
+    ///         "$$formula": "b + values[-1]"
+    ///     },
+    ///     "b": {
+    ///         "$$formula": "\"x\""
+    ///     },
+    ///     "values": [
+    ///         {
+    ///             "$$formula": "1"
+    ///         }
+    ///     ]
+    /// });
+    ///
+    /// let graph = DependencyGraph::new(&data, &Engine::default()).unwrap();
+    ///
+    /// assert_eq!(
+    ///     graph.to_dot(),
+    ///     r#"digraph formulas {
+    ///     node [shape=box];
+    ///     n0 [label="a\nb + values[-1]"];
+    ///     n1 [label="b\n\"x\""];
+    ///     n2 [label="values[0]\n1"];
+    ///     n0 -> n1;
+    ///     n0 -> n2 [style=dashed];
+    /// }
+    /// "#
+    /// );
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut result = String::from("digraph formulas {\n    node [shape=box];\n");
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            result.push_str(&format!(
+                "    n{} [label=\"{}\\n{}\"];\n",
+                idx,
+                escape_dot(&node.position.to_string()),
+                escape_dot(&node.expression)
+            ));
+        }
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            for dependency in node.dependencies.iter() {
+                result.push_str(&format!("    n{} -> n{};\n", idx, dependency));
+            }
+            for dependency in node.possible_dependencies.iter() {
+                result.push_str(&format!("    n{} -> n{} [style=dashed];\n", idx, dependency));
+            }
+        }
+
+        result.push_str("}\n");
+        result
+    }
+
+    /// Exports the graph as a JSON adjacency list
+    ///
+    /// Every formula is an object field (key is the formula position) with the `expression`,
+    /// `dependencies` and `possibleDependencies` fields. Dependencies are lists of formula
+    /// positions.
+    ///
+    /// Check the [`DependencyGraph`] for an example.
+    ///
+    /// [`DependencyGraph`]: struct.DependencyGraph.html
+    pub fn to_json(&self) -> Value {
+        let positions = |dependencies: &[usize]| -> Vec<String> {
+            dependencies
+                .iter()
+                .map(|x| self.nodes[*x].position.to_string())
+                .collect()
+        };
+
+        let mut result = Map::new();
+        for node in self.nodes.iter() {
+            result.insert(
+                node.position.to_string(),
+                json!({
+                    "expression": node.expression,
+                    "dependencies": positions(&node.dependencies),
+                    "possibleDependencies": positions(&node.possible_dependencies),
+                }),
+            );
+        }
+        Value::Object(result)
+    }
+}
+
+struct Tarjan<'a> {
+    graph: &'a Graph,
+    index: usize,
+    indexes: Vec<Option<usize>>,
+    low_links: Vec<usize>,
+    stack: Vec<usize>,
+    on_stack: Vec<bool>,
+    components: Vec<Vec<usize>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn visit(&mut self, node: usize) {
+        self.indexes[node] = Some(self.index);
+        self.low_links[node] = self.index;
+        self.index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+    }
+
+    // Explicit call stack (node, next dependency position) is used instead of recursion,
+    // long dependency chains would overflow the thread stack otherwise
+    fn connect(&mut self, node: usize) {
+        let mut calls = vec![(node, 0)];
+        self.visit(node);
+
+        while let Some((node, position)) = calls.pop() {
+            if let Some((dependency, _)) = self.graph.dependencies[node].get(position) {
+                calls.push((node, position + 1));
+
+                match self.indexes[*dependency] {
+                    None => {
+                        self.visit(*dependency);
+                        calls.push((*dependency, 0));
+                    }
+                    Some(index) if self.on_stack[*dependency] => {
+                        self.low_links[node] = self.low_links[node].min(index);
+                    }
+                    _ => {}
+                };
+                continue;
+            }
+
+            // All dependencies visited, propagate the low link to the caller
+            if let Some((caller, _)) = calls.last() {
+                self.low_links[*caller] = self.low_links[*caller].min(self.low_links[node]);
+            }
+
+            if Some(self.low_links[node]) == self.indexes[node] {
+                let mut component = vec![];
+                while let Some(idx) = self.stack.pop() {
+                    self.on_stack[idx] = false;
+                    component.push(idx);
+                    if idx == node {
+                        break;
+                    }
+                }
+                component.sort();
+                self.components.push(component);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::*;
+
+    use super::Graph;
+
+    fn graph(formulas: &[(&str, &str)]) -> Graph {
+        let formulas: Vec<(Identifier, Expression)> = formulas
+            .iter()
+            .map(|(position, expression)| (position.parse().unwrap(), expression.parse().unwrap()))
+            .collect();
+        Graph::new(
+            formulas
+                .iter()
+                .map(|(position, expression)| (position, Some(expression))),
+        )
+    }
+
+    #[test]
+    fn dependencies_are_evaluated_first() {
+        let graph = graph(&[("a", "b + c"), ("b", "c"), ("c", "1")]);
+        assert_eq!(graph.components(), vec![vec![2], vec![1], vec![0]]);
+    }
+
+    #[test]
+    fn ancestors_and_descendants_are_dependencies() {
+        let graph = graph(&[
+            ("a", "wifi"),
+            ("b", "networks.first.id"),
+            ("wifi.ssid", "1"),
+            ("networks.first", "2"),
+        ]);
+        assert_eq!(graph.components(), vec![vec![2], vec![0], vec![3], vec![1]]);
+    }
+
+    #[test]
+    fn exact_cycle() {
+        let graph = graph(&[("a.id", "b.name"), ("b.name", "super.super.a.id"), ("c", "a.id")]);
+        let components = graph.components();
+        assert_eq!(components, vec![vec![0, 1], vec![2]]);
+        assert!(graph.is_cyclic(&components[0]));
+        assert!(!graph.is_cyclic(&components[1]));
+        assert_eq!(graph.exact_cycle(&components[0]), Some(vec![0, 1, 0]));
+    }
+
+    #[test]
+    fn self_cycle() {
+        let graph = graph(&[("a", "this + 1")]);
+        let components = graph.components();
+        assert!(graph.is_cyclic(&components[0]));
+        assert_eq!(graph.exact_cycle(&components[0]), Some(vec![0, 0]));
+    }
+
+    #[test]
+    fn affected_formulas() {
+        let graph = graph(&[
+            ("a", "wifi.ssid"),
+            ("b", "a ~ c"),
+            ("c", "names[index]"),
+            ("d", "1"),
+            ("e", "wifi"),
+        ]);
+        assert_eq!(
+            graph.affected(&["wifi.ssid".parse().unwrap()]),
+            vec![true, true, false, false, true]
+        );
+        assert_eq!(
+            graph.affected(&["names[3]".parse().unwrap()]),
+            vec![false, true, true, false, false]
+        );
+        assert_eq!(
+            graph.affected(&["index".parse().unwrap()]),
+            vec![false, true, true, false, false]
+        );
+        assert_eq!(graph.affected(&["d".parse().unwrap()]), vec![false; 5]);
+    }
+
+    #[test]
+    fn components_long_chain() {
+        // Recursive walk overflows the stack on long chains, expressions are not parsed
+        // to keep the test fast
+        let chain = |cyclic: bool| -> Vec<(Identifier, Expression)> {
+            (0..200_000)
+                .map(|idx| {
+                    let dependency = match idx {
+                        0 if cyclic => 199_999,
+                        0 => {
+                            return (
+                                Identifier::default().name("f0"),
+                                Expression::new(ExpressionValue::Integer(1)),
+                            )
+                        }
+                        _ => idx - 1,
+                    };
+                    let identifier = Identifier::default().name(format!("f{}", dependency));
+                    (
+                        Identifier::default().name(format!("f{}", idx)),
+                        Expression::new(ExpressionValue::Identifier(identifier)),
+                    )
+                })
+                .collect()
+        };
+
+        let formulas = chain(false);
+        let graph = Graph::new(
+            formulas
+                .iter()
+                .map(|(position, expression)| (position, Some(expression))),
+        );
+        let components = graph.components();
+        assert_eq!(components.len(), 200_000);
+        assert!(components.iter().enumerate().all(|(idx, x)| x == &[idx]));
+
+        let formulas = chain(true);
+        let graph = Graph::new(
+            formulas
+                .iter()
+                .map(|(position, expression)| (position, Some(expression))),
+        );
+        let components = graph.components();
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].len(), 200_000);
+        assert!(graph.is_cyclic(&components[0]));
+    }
+
+    #[test]
+    fn affected_long_chain() {
+        let positions: Vec<String> = (0..2000).map(|idx| format!("f{}", idx)).collect();
+        let expressions: Vec<String> = (0..2000)
+            .map(|idx| {
+                if idx == 0 {
+                    "input".to_string()
+                } else {
+                    format!("f{}", idx - 1)
+                }
+            })
+            .collect();
+        let formulas: Vec<(&str, &str)> = positions
+            .iter()
+            .rev()
+            .zip(expressions.iter().rev())
+            .map(|(position, expression)| (position.as_str(), expression.as_str()))
+            .collect();
+
+        let graph = graph(&formulas);
+        assert_eq!(graph.affected(&["input".parse().unwrap()]), vec![true; 2000]);
+        assert_eq!(
+            graph
+                .affected(&["f1000".parse().unwrap()])
+                .iter()
+                .filter(|x| **x)
+                .count(),
+            999
+        );
+    }
+
+    #[test]
+    fn wildcard_cycle() {
+        let graph = graph(&[("a[0]", "a[b]"), ("b", "a[-1]")]);
+        let components = graph.components();
+        assert_eq!(components, vec![vec![0, 1]]);
+        assert!(graph.is_cyclic(&components[0]));
+        assert_eq!(graph.exact_cycle(&components[0]), None);
+    }
+}
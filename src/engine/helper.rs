@@ -2,17 +2,17 @@ use serde_json::Value;
 
 use crate::ast::*;
 use crate::context::Context;
-use crate::engine::Engine;
+use crate::engine::{compiled::CompiledExpression, graph::Graph, Engine};
 use crate::error::*;
 
 /// Item to evaluate
 struct Item {
     /// Item position
     position: Identifier,
-    /// Item expression (`$$formula` value)
-    expression: String,
-    /// Set to `true` if evaluated
-    evaluated: bool,
+    /// Item expression source (`$$formula` value)
+    source: String,
+    /// Compiled item expression, `Err` if the source can't be parsed
+    expression: Result<CompiledExpression>,
 }
 
 /// Creates an item to evaluate if applicable
@@ -31,15 +31,15 @@ fn item_to_eval(value: &Value, position: &Identifier, keyword: &str) -> Result<O
             if let Some(ref value) = object.get(keyword) {
                 // Object with $$formula keyword, must be a string
                 let expression = value.as_str().ok_or_else(|| {
-                    Error::with_message("unable to evaluate")
+                    Error::new(ErrorKind::TypeMismatch, "unable to evaluate")
                         .context("reason", "eval keyword value is not a string")
                         .context("value", value.to_string())
-                        .context("position", format!("{:?}", position))
+                        .context("position", position.to_string())
                 })?;
                 Ok(Some(Item {
                     position: position.clone(),
-                    expression: expression.to_string(),
-                    evaluated: false,
+                    source: expression.to_string(),
+                    expression: CompiledExpression::new(expression),
                 }))
             } else {
                 // Object, but not $$formula keyword
@@ -109,6 +109,98 @@ fn items_to_eval(value: &Value, position: &Identifier, keyword: &str) -> Result<
     }
 }
 
+/// Returns value at the position
+///
+/// # Arguments
+///
+/// * `data` - A JSON
+/// * `position` - A canonical position without indirect lookups and negative indexes
+pub(crate) fn get_value<'a>(data: &'a Value, position: &Identifier) -> Option<&'a Value> {
+    let mut current = data;
+    for value in &position.values {
+        current = match value {
+            IdentifierValue::Name(ref name) => current.get(name)?,
+            IdentifierValue::Index(index) if *index >= 0 => current.get(*index as usize)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// Sets value in a JSON
+///
+/// Unlike [`replace_value`], it does not panic if the position does not exist. The last
+/// position value can refer to a non existing object field, which is inserted.
+///
+/// # Arguments
+///
+/// * `data` - A JSON
+/// * `new_value` - New value to use
+/// * `position` - A canonical position without indirect lookups and negative indexes
+///
+/// [`replace_value`]: fn.replace_value.html
+pub(crate) fn set_value(data: &mut Value, new_value: Value, position: &Identifier) -> Result<()> {
+    let error =
+        || Error::new(ErrorKind::InvalidUpdate, "unable to set value").context("position", position.to_string());
+
+    let (last, parent) = match position.values.split_last() {
+        Some(x) => x,
+        None => {
+            // Empty position = root = whole JSON
+            *data = new_value;
+            return Ok(());
+        }
+    };
+
+    let mut current = data;
+    for value in parent {
+        current = match value {
+            IdentifierValue::Name(ref name) => current.get_mut(name),
+            IdentifierValue::Index(index) if *index >= 0 => current.get_mut(*index as usize),
+            _ => None,
+        }
+        .ok_or_else(error)?;
+    }
+
+    match (last, current) {
+        (IdentifierValue::Name(ref name), Value::Object(ref mut object)) => {
+            object.insert(name.to_string(), new_value);
+        }
+        (IdentifierValue::Index(index), Value::Array(ref mut array)) if *index >= 0 => {
+            *array.get_mut(*index as usize).ok_or_else(error)? = new_value;
+        }
+        _ => return Err(error()),
+    };
+    Ok(())
+}
+
+/// Removes object field from a JSON
+///
+/// Returns the removed value or `None` if the position does not exist or if the last
+/// position value is not an object field.
+///
+/// # Arguments
+///
+/// * `data` - A JSON
+/// * `position` - A canonical position without indirect lookups and negative indexes
+pub(crate) fn remove_value(data: &mut Value, position: &Identifier) -> Option<Value> {
+    let (last, parent) = position.values.split_last()?;
+
+    let mut current = data;
+    for value in parent {
+        current = match value {
+            IdentifierValue::Name(ref name) => current.get_mut(name)?,
+            IdentifierValue::Index(index) if *index >= 0 => current.get_mut(*index as usize)?,
+            _ => return None,
+        };
+    }
+
+    match (last, current) {
+        (IdentifierValue::Name(ref name), Value::Object(ref mut object)) => object.remove(name),
+        _ => None,
+    }
+}
+
 /// Replaces value in a JSON
 ///
 /// # Arguments
@@ -116,7 +208,7 @@ fn items_to_eval(value: &Value, position: &Identifier, keyword: &str) -> Result<
 /// * `data` - A JSON
 /// * `new_value` - New value to use
 /// * `position` - A position of the new value
-fn replace_value(data: Value, new_value: Value, position: &Identifier) -> Value {
+pub(crate) fn replace_value(data: Value, new_value: Value, position: &Identifier) -> Value {
     if position.values.is_empty() {
         // Empty position = root = whole JSON
         return new_value;
@@ -137,52 +229,232 @@ fn replace_value(data: Value, new_value: Value, position: &Identifier) -> Value
     data
 }
 
-// This is pretty naive, multi pass evaluation. It works in this way:
+// Multi pass evaluation of formulas with dependencies which can't be resolved statically
+// (indirect lookups, negative indexes, ...). It works in this way:
 //
-//   * evaluate all items, one by one,
-//   * do not fail if it fails, just increase the counters
+//   * evaluate all formulas, one by one,
+//   * do not fail if it fails, just remember the error
 //   * nothing failed? return what we have, success
-//   * at least one item failed to evaluate?
-//     * no item succeeded? return an error
+//   * at least one formula failed to evaluate?
+//     * no formula succeeded? return all errors (aligned with formulas)
 //   * try again with another pass
-//
-// It's good enough for now.
-//
-// We will see what kind of DSLs we will have and if we will need to create
-// dependency tree, detect circular dependencies, analyze if we can evaluate
-// before the actual evaluation, etc.
-fn eval_with_items(data: Value, mut items: Vec<Item>, engine: &Engine, context: &mut Context) -> Result<Value> {
-    let mut fail_counter;
-    let mut success_counter;
+fn eval_multipass(
+    data: Value,
+    formulas: &[(&Identifier, &CompiledExpression)],
+    engine: &Engine,
+    context: &mut Context,
+) -> (Value, Vec<Option<Error>>) {
     let mut data = data;
+    let mut pending: Vec<usize> = (0..formulas.len()).collect();
 
     loop {
-        fail_counter = 0;
-        success_counter = 0;
-
-        for item in items.iter_mut().filter(|x| !x.evaluated) {
-            match engine.eval(&item.expression, &item.position, &data, context) {
-                Err(_) => fail_counter += 1,
-                Ok(new_value) => {
-                    data = replace_value(data, new_value, &item.position);
-                    success_counter += 1;
-                    item.evaluated = true;
-                }
+        let mut failed = vec![];
+
+        for idx in pending.iter() {
+            let (position, expression) = formulas[*idx];
+            match engine.eval_compiled(expression, position, &data, context) {
+                Ok(new_value) => data = replace_value(data, new_value, position),
+                Err(e) => failed.push((*idx, e)),
             };
         }
 
-        if fail_counter == 0 {
+        if failed.is_empty() {
             // Nothing failed, return what we have
-            return Ok(data);
+            return (data, formulas.iter().map(|_| None).collect());
         }
 
-        if fail_counter > 0 && success_counter == 0 {
-            // Something failed, but not even one item was evaluated, another pass won't help, fail
-            return Err(Error::with_message("unable to evaluate"));
+        if failed.len() == pending.len() {
+            // Something failed, but not even one formula was evaluated, another pass won't help
+            let mut errors: Vec<Option<Error>> = (0..formulas.len()).map(|_| None).collect();
+            for (idx, e) in failed {
+                errors[idx] = Some(e);
+            }
+            return (data, errors);
         }
 
-        // Something failed here, but also at least one item was evaluated. Try
+        // Something failed here, but also at least one formula was evaluated. Try
         // another pass to check if we can evaluate more.
+        pending = failed.into_iter().map(|(idx, _)| idx).collect();
+    }
+}
+
+/// Document formulas with the dependency graph
+pub(crate) struct Formulas {
+    /// Formulas in the document order
+    items: Vec<Item>,
+    /// Formulas dependency graph
+    graph: Graph,
+}
+
+impl Formulas {
+    /// Creates list of all document formulas
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - A JSON
+    /// * `keyword` - An evaluation keyword
+    pub(crate) fn new(data: &Value, keyword: &str) -> Result<Formulas> {
+        let items = items_to_eval(data, &Identifier::default(), keyword)?.unwrap_or_default();
+        let graph = Graph::new(items.iter().map(|item| {
+            (
+                &item.position,
+                item.expression.as_ref().ok().map(CompiledExpression::expression),
+            )
+        }));
+
+        Ok(Formulas { items, graph })
+    }
+
+    /// Returns number of formulas
+    pub(crate) fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns formula position
+    pub(crate) fn position(&self, idx: usize) -> &Identifier {
+        &self.items[idx].position
+    }
+
+    /// Returns formula expression source
+    pub(crate) fn source(&self, idx: usize) -> &str {
+        &self.items[idx].source
+    }
+
+    /// Returns formula expression, `None` if the formula can't be parsed
+    pub(crate) fn expression(&self, idx: usize) -> Option<&Expression> {
+        self.items[idx]
+            .expression
+            .as_ref()
+            .ok()
+            .map(CompiledExpression::expression)
+    }
+
+    /// Returns formulas dependency graph
+    pub(crate) fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Evaluates selected formulas in the dependency graph order
+    ///
+    /// Formulas are evaluated once, except formulas which dependencies can't be resolved
+    /// statically - multi pass evaluation is used for them. Formulas which failed to evaluate
+    /// are left untouched in the data.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - A JSON to evaluate
+    /// * `selected` - Formulas to evaluate (formula index = slice index)
+    /// * `errors` - Formula errors (formula index = slice index), errors of selected formulas
+    ///   are replaced, errors of other formulas are used to skip dependent formulas
+    /// * `engine` - An engine to use
+    /// * `context` - An evaluation context
+    pub(crate) fn evaluate(
+        &self,
+        data: Value,
+        selected: &[bool],
+        errors: &mut [Option<Error>],
+        engine: &Engine,
+        context: &mut Context,
+    ) -> Value {
+        let mut data = data;
+
+        for (idx, error) in errors.iter_mut().enumerate() {
+            if selected[idx] {
+                *error = self.items[idx].expression.as_ref().err().cloned();
+            }
+        }
+
+        for component in self.graph.components() {
+            let component: Vec<usize> = component.into_iter().filter(|x| selected[*x]).collect();
+            if component.is_empty() {
+                continue;
+            }
+
+            if let Some(cycle) = self.graph.exact_cycle(&component) {
+                let cycle: Vec<String> = cycle.into_iter().map(|idx| self.position(idx).to_string()).collect();
+                let cycle = cycle.join(" -> ");
+                for idx in component {
+                    errors[idx].get_or_insert_with(|| {
+                        Error::new(ErrorKind::CircularDependency, "circular dependency").context("cycle", cycle.clone())
+                    });
+                }
+                continue;
+            }
+
+            // Do not evaluate formulas depending on formulas which failed to evaluate
+            let mut formulas = vec![];
+            for idx in component.iter() {
+                if errors[*idx].is_some() {
+                    continue;
+                }
+
+                if let Some(dependency) = self.graph.exact_dependencies(*idx).find(|x| errors[*x].is_some()) {
+                    errors[*idx] = Some(
+                        Error::new(ErrorKind::Evaluation, "unable to evaluate dependency")
+                            .context("dependency", self.position(dependency).to_string()),
+                    );
+                    continue;
+                }
+
+                if let Ok(ref expression) = self.items[*idx].expression {
+                    formulas.push((*idx, (self.position(*idx), expression)));
+                }
+            }
+
+            let (new_data, formula_errors) = if self.graph.is_cyclic(&component) {
+                let (_, formulas): (Vec<usize>, Vec<_>) = formulas.iter().cloned().unzip();
+                eval_multipass(data, &formulas, engine, context)
+            } else {
+                match formulas.first() {
+                    Some((_, (position, expression))) => {
+                        match engine.eval_compiled(expression, position, &data, context) {
+                            Ok(new_value) => (replace_value(data, new_value, position), vec![None]),
+                            Err(e) => (data, vec![Some(e)]),
+                        }
+                    }
+                    None => (data, vec![]),
+                }
+            };
+            data = new_data;
+
+            for ((idx, _), e) in formulas.iter().zip(formula_errors) {
+                errors[*idx] = e;
+            }
+        }
+
+        data
+    }
+
+    /// Returns positions of formulas reading the path (directly or transitively)
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A canonical path
+    pub(crate) fn dependents(&self, path: &Identifier) -> Vec<Identifier> {
+        self.graph
+            .affected(std::slice::from_ref(path))
+            .into_iter()
+            .enumerate()
+            .filter(|(_, affected)| *affected)
+            .map(|(idx, _)| self.position(idx).clone())
+            .collect()
+    }
+
+    /// Converts formula errors into the list of failures
+    ///
+    /// # Arguments
+    ///
+    /// * `errors` - Formula errors (formula index = slice index)
+    pub(crate) fn failures(&self, errors: &[Option<Error>]) -> Vec<FormulaError> {
+        self.items
+            .iter()
+            .zip(errors.iter())
+            .filter_map(|(item, error)| {
+                error
+                    .as_ref()
+                    .map(|e| FormulaError::new(item.position.clone(), item.source.as_str(), e.clone()))
+            })
+            .collect()
     }
 }
 
@@ -193,6 +465,9 @@ pub fn eval(data: Value) -> Result<Value> {
 
 /// Evaluates the whole JSON
 ///
+/// If any formula fails to evaluate, the returned error contains the list of all
+/// failing formulas. Visit the [`Error::failures`] method documentation for an example.
+///
 /// # Arguments
 ///
 /// * `data` - A JSON to evaluate
@@ -215,6 +490,8 @@ pub fn eval(data: Value) -> Result<Value> {
 /// Chained dependencies evaluation.
 ///
 /// ```rust
+/// # #[cfg(feature = "slugify")]
+/// # {
 /// use balena_temen::{evaluate, Value};
 /// use serde_json::json;
 ///
@@ -235,16 +512,15 @@ pub fn eval(data: Value) -> Result<Value> {
 /// });
 ///
 /// assert_eq!(evaluate(data).unwrap(), evaluated);
+/// # }
 /// ```
+///
+/// [`Error::failures`]: error/struct.Error.html#method.failures
 pub fn evaluate(data: Value) -> Result<Value> {
     let engine = Engine::default();
     let mut context = Context::default();
 
-    if let Some(items) = items_to_eval(&data, &Identifier::default(), engine.eval_keyword())? {
-        eval_with_items(data, items, &engine, &mut context)
-    } else {
-        Ok(data)
-    }
+    evaluate_with_engine(data, &engine, &mut context)
 }
 
 /// Evaluates the whole JSON with custom [`Engine`]
@@ -271,18 +547,188 @@ pub fn evaluate(data: Value) -> Result<Value> {
 /// assert_eq!(evaluate_with_engine(data, &engine, &mut context).unwrap(), json!(3));
 /// ```
 ///
-/// Check the [`eval`] function for more examples.
+/// Check the [`evaluate`] function for more examples.
 ///
-/// [`eval`]: fn.eval.html
+/// [`evaluate`]: fn.evaluate.html
 /// [`Engine`]: struct.Engine.html
 pub fn evaluate_with_engine(data: Value, engine: &Engine, context: &mut Context) -> Result<Value> {
-    if let Some(items) = items_to_eval(&data, &Identifier::default(), engine.eval_keyword())? {
-        eval_with_items(data, items, engine, context)
-    } else {
+    let (data, failures) = evaluate_partial_with_engine(data, engine, context)?;
+
+    if failures.is_empty() {
         Ok(data)
+    } else {
+        Err(Error::with_failures(failures))
     }
 }
 
+/// Evaluates as much of the JSON as possible
+///
+/// Unlike [`evaluate`], it does not fail if a formula can't be evaluated. Formulas which
+/// can't be evaluated (and formulas depending on them) are left untouched and the list of
+/// failures is returned along with the partially evaluated JSON. The result can be evaluated
+/// again later, when more data are available.
+///
+/// # Arguments
+///
+/// * `data` - A JSON to evaluate
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "slugify")]
+/// # {
+/// use balena_temen::{ast::Identifier, evaluate, evaluate_partial, Value};
+/// use serde_json::json;
+///
+/// let data = json!({
+///     "uuid": {
+///         "$$formula": "super.device.uuid"
+///     },
+///     "hostname": {
+///         "$$formula": "`balena-` ~ super.uuid"
+///     },
+///     "ssid": {
+///         "$$formula": "`Balena 5G` | SLUGIFY"
+///     }
+/// });
+///
+/// let (mut partial, failures) = evaluate_partial(data).unwrap();
+///
+/// assert_eq!(
+///     partial,
+///     json!({
+///         "uuid": {
+///             "$$formula": "super.device.uuid"
+///         },
+///         "hostname": {
+///             "$$formula": "`balena-` ~ super.uuid"
+///         },
+///         "ssid": "balena-5g"
+///     })
+/// );
+/// assert_eq!(failures.len(), 2);
+/// assert_eq!(failures[0].position, Identifier::default().name("hostname"));
+/// assert_eq!(failures[1].position, Identifier::default().name("uuid"));
+///
+/// // Device UUID is known later
+/// partial["device"] = json!({"uuid": "abcd"});
+///
+/// assert_eq!(
+///     evaluate(partial).unwrap(),
+///     json!({
+///         "device": {
+///             "uuid": "abcd"
+///         },
+///         "uuid": "abcd",
+///         "hostname": "balena-abcd",
+///         "ssid": "balena-5g"
+///     })
+/// );
+/// # }
+/// ```
+///
+/// [`evaluate`]: fn.evaluate.html
+pub fn evaluate_partial(data: Value) -> Result<(Value, Vec<FormulaError>)> {
+    let engine = Engine::default();
+    let mut context = Context::default();
+
+    evaluate_partial_with_engine(data, &engine, &mut context)
+}
+
+/// Evaluates as much of the JSON as possible with custom [`Engine`]
+///
+/// Check the [`evaluate_partial`] function for more details and examples.
+///
+/// # Arguments
+///
+/// * `data` - A JSON to evaluate
+/// * `engine` - An engine to use
+/// * `context` - An evaluation context
+///
+/// [`evaluate_partial`]: fn.evaluate_partial.html
+/// [`Engine`]: struct.Engine.html
+pub fn evaluate_partial_with_engine(
+    data: Value,
+    engine: &Engine,
+    context: &mut Context,
+) -> Result<(Value, Vec<FormulaError>)> {
+    let formulas = Formulas::new(&data, engine.eval_keyword())?;
+    let selected = vec![true; formulas.len()];
+    let mut errors = vec![None; formulas.len()];
+
+    let data = formulas.evaluate(data, &selected, &mut errors, engine, context);
+    Ok((data, formulas.failures(&errors)))
+}
+
+/// Finds formulas reading a path
+///
+/// Returns positions of all formulas which read the path directly or transitively (a formula
+/// reads another formula reading the path). Formula reads the path if it reads the path
+/// itself, any of its parent objects (`wifi` for `wifi.ssid`) or any nested value
+/// (`wifi.ssid.first` for `wifi.ssid`). Formulas with indirect lookups (`networks[index]`)
+/// or negative indexes are included if they may read the path.
+///
+/// Positions are returned in the document order. Formulas are not evaluated, dependencies
+/// are extracted statically.
+///
+/// # Arguments
+///
+/// * `data` - A JSON
+/// * `path` - A path to find formulas for (relative to the JSON root)
+///
+/// # Examples
+///
+/// ```rust
+/// use balena_temen::{ast::Identifier, dependents};
+/// use serde_json::json;
+///
+/// let data = json!({
+///     "wifi": {
+///         "ssid": "Balena 5G",
+///         "id": {
+///             "$$formula": "super.ssid | SLUGIFY"
+///         }
+///     },
+///     "hostname": {
+///         "$$formula": "`balena-` ~ wifi.id"
+///     },
+///     "uuid": {
+///         "$$formula": "UUIDV4()"
+///     }
+/// });
+///
+/// let path: Identifier = "wifi.ssid".parse().unwrap();
+///
+/// assert_eq!(
+///     dependents(&data, &path).unwrap(),
+///     vec![
+///         Identifier::default().name("hostname"),
+///         Identifier::default().name("wifi").name("id"),
+///     ]
+/// );
+/// ```
+pub fn dependents(data: &Value, path: &Identifier) -> Result<Vec<Identifier>> {
+    dependents_with_engine(data, path, &Engine::default())
+}
+
+/// Finds formulas reading a path with custom [`Engine`]
+///
+/// Check the [`dependents`] function for more details and examples.
+///
+/// # Arguments
+///
+/// * `data` - A JSON
+/// * `path` - A path to find formulas for (relative to the JSON root)
+/// * `engine` - An engine to use (evaluation keyword)
+///
+/// [`dependents`]: fn.dependents.html
+/// [`Engine`]: struct.Engine.html
+pub fn dependents_with_engine(data: &Value, path: &Identifier, engine: &Engine) -> Result<Vec<Identifier>> {
+    let path = path.canonicalize(&Identifier::default())?;
+    let formulas = Formulas::new(data, engine.eval_keyword())?;
+    Ok(formulas.dependents(&path))
+}
+
 #[deprecated(since = "0.0.16", note = "please use `evaluate_with_engine` instead")]
 pub fn eval_with_engine(data: Value, engine: &Engine, context: &mut Context) -> Result<Value> {
     evaluate_with_engine(data, engine, context)
@@ -294,7 +740,12 @@ pub mod wasm {
     pub use console_error_panic_hook::set_once as set_panic_hook;
     use wasm_bindgen::prelude::*;
 
+    use serde_json::{json, Value};
+
     use super::evaluate;
+    #[cfg(feature = "serde")]
+    use crate::ast::Expression;
+    use crate::{builtin::Metadata, engine::Engine};
 
     /// Evaluates the whole JSON
     #[wasm_bindgen(js_name = "evaluate")]
@@ -311,6 +762,86 @@ pub mod wasm {
         Ok(result)
     }
 
+    /// Parses an expression and returns its AST
+    ///
+    /// Check the [`ast`](../../ast/index.html) module documentation for the JSON shape.
+    #[cfg(feature = "serde")]
+    #[wasm_bindgen(js_name = "parse")]
+    pub fn js_parse(expression: &str) -> Result<JsValue, JsValue> {
+        console_error_panic_hook::set_once();
+
+        let expression: Expression = expression.parse().map_err(|e| JsValue::from(format!("{:#?}", e)))?;
+
+        let result = JsValue::from_serde(&expression).map_err(|e| JsValue::from(format!("{:#?}", e)))?;
+
+        Ok(result)
+    }
+
+    // Converts a registered function / filter metadata to the JSON object
+    //
+    // Shape matches the `serde` feature serialization of the `Metadata` structure, but
+    // doesn't require the feature to be enabled.
+    fn registration_to_json(name: &str, metadata: &Metadata) -> Value {
+        let signature = metadata.signature.as_ref().map(|signature| {
+            let args: Vec<Value> = signature
+                .args
+                .iter()
+                .map(|arg| {
+                    json!({
+                        "name": arg.name,
+                        "valueType": arg.value_type.to_string(),
+                        "required": arg.required
+                    })
+                })
+                .collect();
+
+            json!({
+                "input": signature.input.to_string(),
+                "args": args,
+                "variadic": signature.variadic.map(|x| x.to_string()),
+                "returnType": signature.return_type.to_string()
+            })
+        });
+
+        json!({
+            "name": name,
+            "determinism": metadata.determinism.to_string(),
+            "signature": signature,
+            "description": metadata.description
+        })
+    }
+
+    fn js_registrations(registrations: Vec<(&str, &Metadata)>) -> Result<JsValue, JsValue> {
+        let registrations: Vec<Value> = registrations
+            .into_iter()
+            .map(|(name, metadata)| registration_to_json(name, metadata))
+            .collect();
+
+        JsValue::from_serde(&registrations).map_err(|e| JsValue::from(format!("{:#?}", e)))
+    }
+
+    /// Lists functions of the default engine with their metadata
+    ///
+    /// Every function is an object with the `name`, `determinism`, `signature` and
+    /// `description` properties.
+    #[wasm_bindgen(js_name = "functions")]
+    pub fn js_functions() -> Result<JsValue, JsValue> {
+        console_error_panic_hook::set_once();
+
+        js_registrations(Engine::default().functions())
+    }
+
+    /// Lists filters of the default engine with their metadata
+    ///
+    /// Every filter is an object with the `name`, `determinism`, `signature` and
+    /// `description` properties.
+    #[wasm_bindgen(js_name = "filters")]
+    pub fn js_filters() -> Result<JsValue, JsValue> {
+        console_error_panic_hook::set_once();
+
+        js_registrations(Engine::default().filters())
+    }
+
     #[cfg(test)]
     mod tests {
         use serde_json::{json, Value};
@@ -340,5 +871,60 @@ pub mod wasm {
 
             assert_eq!(output, valid_output);
         }
+
+        #[cfg(feature = "serde")]
+        #[wasm_bindgen_test]
+        fn parse_in_browser() {
+            let output: Value = super::js_parse("1").unwrap().into_serde().unwrap();
+
+            let valid_output = json!({
+                "value": { "type": "integer", "value": 1 },
+                "negated": false,
+                "filters": []
+            });
+
+            assert_eq!(output, valid_output);
+        }
+
+        #[wasm_bindgen_test]
+        fn functions_in_browser() {
+            let output: Value = super::js_functions().unwrap().into_serde().unwrap();
+
+            let pow = json!({
+                "name": "POW",
+                "determinism": "pure",
+                "signature": {
+                    "input": "any",
+                    "args": [
+                        { "name": "base", "valueType": "number", "required": true },
+                        { "name": "exponent", "valueType": "number", "required": true }
+                    ],
+                    "variadic": null,
+                    "returnType": "number"
+                },
+                "description": "Raises `base` to the power of `exponent`"
+            });
+
+            assert!(output.as_array().unwrap().contains(&pow));
+        }
+
+        #[wasm_bindgen_test]
+        fn filters_in_browser() {
+            let output: Value = super::js_filters().unwrap().into_serde().unwrap();
+
+            let upper = json!({
+                "name": "UPPER",
+                "determinism": "pure",
+                "signature": {
+                    "input": "string",
+                    "args": [],
+                    "variadic": null,
+                    "returnType": "string"
+                },
+                "description": "Converts a string to uppercase"
+            });
+
+            assert!(output.as_array().unwrap().contains(&upper));
+        }
     }
 }
@@ -17,7 +17,7 @@ pub struct Lookup<'a> {
 fn validate_not_for_evaluation(value: &Value, eval_keyword: &str) -> Result<()> {
     if let Value::Object(object) = value {
         if object.contains_key(eval_keyword) {
-            return Err(Error::with_message("unable to lookup identifier"));
+            return Err(Error::new(ErrorKind::LookupFailed, "unable to lookup identifier"));
         }
     }
     Ok(())
@@ -54,7 +54,8 @@ impl<'a> Lookup<'a> {
         }
 
         let result = Cow::Borrowed(lookup.stack.pop().ok_or_else(|| {
-            Error::with_message("unable to lookup identifier").context("reason", "empty stack = invalid identifier")
+            Error::new(ErrorKind::LookupFailed, "unable to lookup identifier")
+                .context("reason", "empty stack = invalid identifier")
         })?);
         Ok(result)
     }
@@ -76,7 +77,8 @@ impl<'a> Lookup<'a> {
         eval_keyword: &str,
     ) -> Result<()> {
         let last_value = self.stack.last().ok_or_else(|| {
-            Error::with_message("unable to lookup identifier").context("reason", "empty stack = invalid identifier")
+            Error::new(ErrorKind::LookupFailed, "unable to lookup identifier")
+                .context("reason", "empty stack = invalid identifier")
         })?;
 
         match identifier_value {
@@ -84,13 +86,13 @@ impl<'a> Lookup<'a> {
                 let new_value = last_value
                     .as_object()
                     .ok_or_else(|| {
-                        Error::with_message("unable to lookup identifier")
+                        Error::new(ErrorKind::LookupFailed, "unable to lookup identifier")
                             .context("reason", "parent value is not an object")
                             .context("name", name.to_string())
                     })
                     .and_then(|x| {
                         x.get(name).ok_or_else(|| {
-                            Error::with_message("unable to lookup identifier")
+                            Error::new(ErrorKind::LookupFailed, "unable to lookup identifier")
                                 .context("reason", "field does not exist")
                                 .context("name", name.to_string())
                                 .context("object", format!("{:?}", x))
@@ -105,7 +107,7 @@ impl<'a> Lookup<'a> {
             IdentifierValue::Super => {
                 // Pop the last stack value, `super` refers to parent
                 self.stack.pop().ok_or_else(|| {
-                    Error::with_message("unable to lookup identifier")
+                    Error::new(ErrorKind::LookupFailed, "unable to lookup identifier")
                         .context("reason", "super must not be used in the root")
                 })?;
             }
@@ -114,7 +116,7 @@ impl<'a> Lookup<'a> {
                 let new_value = last_value
                     .as_array()
                     .ok_or_else(|| {
-                        Error::with_message("unable to lookup identifier")
+                        Error::new(ErrorKind::LookupFailed, "unable to lookup identifier")
                             .context("reason", "parent value is not an array")
                             .context("index", format!("{}", idx))
                     })
@@ -127,13 +129,13 @@ impl<'a> Lookup<'a> {
                         }
 
                         if index < 0 {
-                            return Err(Error::with_message("unable to lookup identifier")
+                            return Err(Error::new(ErrorKind::LookupFailed, "unable to lookup identifier")
                                 .context("reason", "invalid index")
                                 .context("index", format!("{}", index)));
                         }
 
                         x.get(index as usize).ok_or_else(|| {
-                            Error::with_message("unable to lookup identifier")
+                            Error::new(ErrorKind::LookupFailed, "unable to lookup identifier")
                                 .context("reason", "index out of bounds")
                                 .context("index", format!("{}", index))
                                 .context("array", format!("{:?}", x))
@@ -157,9 +159,9 @@ impl<'a> Lookup<'a> {
                     )?,
                     Value::Number(ref x) => {
                         let idx = x.as_i64().ok_or_else(|| {
-                            Error::with_message("unable to lookup identifier")
+                            Error::new(ErrorKind::LookupFailed, "unable to lookup identifier")
                                 .context("reason", "invalid index")
-                                .context("index", format!("{:?}", x))
+                                .context("index", x.to_string())
                         })?;
 
                         self.update_with_identifier_value(
@@ -169,10 +171,10 @@ impl<'a> Lookup<'a> {
                         )?;
                     }
                     _ => {
-                        return Err(Error::with_message("unable to lookup identifier")
+                        return Err(Error::new(ErrorKind::LookupFailed, "unable to lookup identifier")
                             .context("reason", "identifier does not point to an integer / string")
-                            .context("identifier", format!("{:?}", identifier))
-                            .context("position", format!("{:?}", position)));
+                            .context("identifier", identifier.to_string())
+                            .context("position", position.to_string()));
                     }
                 };
             }
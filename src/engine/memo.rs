@@ -0,0 +1,211 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use serde_json::Value;
+
+/// Maximum number of memoized calls, the cache is cleared when reached
+const MEMOIZED_CALLS_LIMIT: usize = 1024;
+
+/// Function or filter call
+///
+/// Borrowed version is used for lookups, arguments are cloned only if a call
+/// result is memoized.
+#[derive(Clone, Copy)]
+pub(crate) struct Call<'a> {
+    /// Unique engine identifier (registered functions & filters differ)
+    pub(crate) engine: usize,
+    /// `function` or `filter`
+    pub(crate) kind: &'static str,
+    pub(crate) name: &'a str,
+    pub(crate) input: Option<&'a Value>,
+    pub(crate) args: &'a [Value],
+    pub(crate) kwargs: &'a HashMap<String, Value>,
+}
+
+// Hashes a value, object fields are hashed independently of their order
+fn hash_value<H: Hasher>(value: &Value, state: &mut H) {
+    match value {
+        Value::Null => 0u8.hash(state),
+        Value::Bool(x) => {
+            1u8.hash(state);
+            x.hash(state);
+        }
+        Value::Number(x) => {
+            2u8.hash(state);
+            if let Some(x) = x.as_i64() {
+                x.hash(state);
+            } else if let Some(x) = x.as_u64() {
+                x.hash(state);
+            } else if let Some(x) = x.as_f64() {
+                x.to_bits().hash(state);
+            }
+        }
+        Value::String(x) => {
+            3u8.hash(state);
+            x.hash(state);
+        }
+        Value::Array(x) => {
+            4u8.hash(state);
+            x.len().hash(state);
+            for item in x {
+                hash_value(item, state);
+            }
+        }
+        Value::Object(x) => {
+            5u8.hash(state);
+            hash_unordered(x.iter(), state);
+        }
+    };
+}
+
+// Hashes key, value pairs independently of their order
+fn hash_unordered<'a, I, H>(entries: I, state: &mut H)
+where
+    I: Iterator<Item = (&'a String, &'a Value)>,
+    H: Hasher,
+{
+    let mut len = 0usize;
+    let mut sum = 0u64;
+    for (k, v) in entries {
+        let mut hasher = DefaultHasher::new();
+        k.hash(&mut hasher);
+        hash_value(v, &mut hasher);
+        sum = sum.wrapping_add(hasher.finish());
+        len += 1;
+    }
+    len.hash(state);
+    sum.hash(state);
+}
+
+impl<'a> Call<'a> {
+    fn hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.engine.hash(&mut hasher);
+        self.kind.hash(&mut hasher);
+        self.name.hash(&mut hasher);
+        match self.input {
+            Some(input) => hash_value(input, &mut hasher),
+            None => 6u8.hash(&mut hasher),
+        };
+        self.args.len().hash(&mut hasher);
+        for arg in self.args {
+            hash_value(arg, &mut hasher);
+        }
+        hash_unordered(self.kwargs.iter(), &mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Memoized call with its result
+struct Entry {
+    engine: usize,
+    kind: &'static str,
+    name: String,
+    input: Option<Value>,
+    args: Vec<Value>,
+    kwargs: HashMap<String, Value>,
+    result: Value,
+}
+
+impl Entry {
+    fn matches(&self, call: &Call) -> bool {
+        self.engine == call.engine
+            && self.kind == call.kind
+            && self.name == call.name
+            && self.input.as_ref() == call.input
+            && self.args.as_slice() == call.args
+            && &self.kwargs == call.kwargs
+    }
+}
+
+/// Results of pure function and filter calls
+#[derive(Default)]
+pub(crate) struct CallCache {
+    /// Entries grouped by the call hash
+    entries: HashMap<u64, Vec<Entry>>,
+    len: usize,
+}
+
+impl CallCache {
+    /// Returns memoized result of a call
+    pub(crate) fn get(&self, call: &Call) -> Option<&Value> {
+        self.entries
+            .get(&call.hash())?
+            .iter()
+            .find(|x| x.matches(call))
+            .map(|x| &x.result)
+    }
+
+    /// Memoizes result of a call
+    ///
+    /// All memoized results are dropped if the cache is full.
+    pub(crate) fn insert(&mut self, call: &Call, result: Value) {
+        if self.len >= MEMOIZED_CALLS_LIMIT {
+            self.entries.clear();
+            self.len = 0;
+        }
+
+        self.entries.entry(call.hash()).or_default().push(Entry {
+            engine: call.engine,
+            kind: call.kind,
+            name: call.name.to_string(),
+            input: call.input.cloned(),
+            args: call.args.to_vec(),
+            kwargs: call.kwargs.clone(),
+            result,
+        });
+        self.len += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{json, Value};
+
+    use super::{Call, CallCache, MEMOIZED_CALLS_LIMIT};
+
+    fn call<'a>(engine: usize, args: &'a [Value], kwargs: &'a HashMap<String, Value>) -> Call<'a> {
+        Call {
+            engine,
+            kind: "function",
+            name: "F",
+            input: None,
+            args,
+            kwargs,
+        }
+    }
+
+    #[test]
+    fn calls_are_distinguished_by_engine_and_arguments() {
+        let kwargs = HashMap::new();
+        let mut cache = CallCache::default();
+
+        cache.insert(&call(1, &[json!(1)], &kwargs), json!("a"));
+        cache.insert(&call(2, &[json!(1)], &kwargs), json!("b"));
+
+        assert_eq!(cache.get(&call(1, &[json!(1)], &kwargs)), Some(&json!("a")));
+        assert_eq!(cache.get(&call(2, &[json!(1)], &kwargs)), Some(&json!("b")));
+        assert_eq!(cache.get(&call(1, &[json!(2)], &kwargs)), None);
+        assert_eq!(cache.get(&call(1, &[json!(1.5)], &kwargs)), None);
+    }
+
+    #[test]
+    fn cache_is_bounded() {
+        let kwargs = HashMap::new();
+        let mut cache = CallCache::default();
+
+        for idx in 0..=MEMOIZED_CALLS_LIMIT {
+            cache.insert(&call(1, &[json!(idx)], &kwargs), json!(idx));
+        }
+
+        assert!(cache.len <= MEMOIZED_CALLS_LIMIT);
+        assert_eq!(cache.get(&call(1, &[json!(0)], &kwargs)), None);
+        assert_eq!(
+            cache.get(&call(1, &[json!(MEMOIZED_CALLS_LIMIT)], &kwargs)),
+            Some(&json!(MEMOIZED_CALLS_LIMIT))
+        );
+    }
+}
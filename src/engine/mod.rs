@@ -1,29 +1,48 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use serde_json::{Number, Value};
 
 use crate::{
     ast::*,
-    builtin::{filter::FilterFn, function::FunctionFn},
+    builtin::{
+        filter::Filter,
+        function::{BoxedSpecialFormFn, Function},
+        Determinism, Metadata,
+    },
     context::Context,
     error::*,
     utils::{validate_f64, RelativeEq},
 };
 
 use self::builder::EngineBuilder;
+use self::compiled::CompiledExpression;
 use self::lookup::Lookup;
+use self::memo::Call;
 
 pub(crate) mod builder;
+mod check;
+pub(crate) mod compiled;
+pub(crate) mod evaluator;
+mod fold;
+pub(crate) mod format;
+pub(crate) mod graph;
 pub(crate) mod helper;
 mod lookup;
+pub(crate) mod memo;
+pub(crate) mod rename;
 
 /// An expression evaluation engine
 pub struct Engine {
-    functions: HashMap<String, FunctionFn>,
-    filters: HashMap<String, FilterFn>,
+    functions: HashMap<String, Function>,
+    filters: HashMap<String, Filter>,
+    function_metadata: HashMap<String, Metadata>,
+    filter_metadata: HashMap<String, Metadata>,
     #[allow(dead_code)]
     eval_keyword: String,
+    reproducible: bool,
+    /// Unique engine identifier, memoized calls of different engines must not be shared
+    id: usize,
 }
 
 impl Default for Engine {
@@ -36,11 +55,52 @@ impl Default for Engine {
 }
 
 fn unable_to_evaluate_as_a_number_error() -> Error {
-    Error::with_message("unable to evaluate as a number").context("expected", "number")
+    Error::new(ErrorKind::TypeMismatch, "unable to evaluate as a number").context("expected", "number")
 }
 
 fn unable_to_evaluate_as_a_bool_error() -> Error {
-    Error::with_message("unable to evaluate as a bool").context("expected", "bool")
+    Error::new(ErrorKind::TypeMismatch, "unable to evaluate as a bool").context("expected", "bool")
+}
+
+fn placeholder_outside_of_filter_error() -> Error {
+    Error::new(
+        ErrorKind::InvalidCall,
+        "placeholder is allowed as a filter function argument only",
+    )
+}
+
+// Calls a function or filter, results of pure calls are memoized in the context
+fn call_memoized<F>(call: Call, determinism: Determinism, context: &mut Context, f: F) -> Result<Value>
+where
+    F: FnOnce(&mut Context) -> Result<Value>,
+{
+    if determinism != Determinism::Pure {
+        return f(context);
+    }
+
+    if let Some(value) = context.memoized_call(&call) {
+        return Ok(value.clone());
+    }
+
+    let value = f(context)?;
+    context.memoize_call(&call, value.clone());
+    Ok(value)
+}
+
+// Pairs registered names with their metadata, sorted by name
+fn registrations<'a, I>(names: I, metadata: &'a HashMap<String, Metadata>) -> Vec<(&'a str, &'a Metadata)>
+where
+    I: Iterator<Item = &'a String>,
+{
+    let mut result: Vec<(&str, &Metadata)> = names
+        .filter_map(|name| metadata.get(name).map(|x| (name.as_str(), x)))
+        .collect();
+    result.sort_by_key(|(name, _)| *name);
+    result
+}
+
+fn is_placeholder(expression: &Expression) -> bool {
+    matches!(expression.value, ExpressionValue::Placeholder)
 }
 
 impl Engine {
@@ -88,6 +148,7 @@ impl Engine {
     ///
     /// // Filters
     ///
+    /// # #[cfg(feature = "slugify")]
     /// assert_eq!(
     ///     engine.eval("`Balena is great!` | SLUGIFY", &position, &data, &mut ctx).unwrap(),
     ///     json!("balena-is-great")
@@ -120,6 +181,57 @@ impl Engine {
         Ok(self.eval_expression(&expression, position, data, context)?.into_owned())
     }
 
+    /// Evaluates a pre-compiled expression
+    ///
+    /// Same as [`eval`], but the expression is not parsed again.
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - A compiled expression to evaluate
+    /// * `position` - An initial position for relative identifiers
+    /// * `data` - A JSON with variable values
+    /// * `context` - An evaluation context
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "slugify")]
+    /// # {
+    /// use balena_temen::{
+    ///     ast::Identifier,
+    ///     CompiledExpression, Engine, Context, Value
+    /// };
+    /// use serde_json::json;
+    ///
+    /// let engine = Engine::default();
+    /// let mut ctx = Context::default();
+    /// let position = Identifier::default();
+    /// let expression = CompiledExpression::new("ssid | SLUGIFY").unwrap();
+    ///
+    /// assert_eq!(
+    ///     engine.eval_compiled(&expression, &position, &json!({"ssid": "Balena 5G"}), &mut ctx).unwrap(),
+    ///     json!("balena-5g")
+    /// );
+    /// assert_eq!(
+    ///     engine.eval_compiled(&expression, &position, &json!({"ssid": "Zrzka"}), &mut ctx).unwrap(),
+    ///     json!("zrzka")
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// [`eval`]: struct.Engine.html#method.eval
+    pub fn eval_compiled(
+        &self,
+        expression: &CompiledExpression,
+        position: &Identifier,
+        data: &Value,
+        context: &mut Context,
+    ) -> Result<Value> {
+        Ok(self
+            .eval_expression(expression.expression(), position, data, context)?
+            .into_owned())
+    }
+
     /// Evaluates an expression as a boolean
     ///
     /// Result must evaluate to a boolean value otherwise it fails. Numbers, strings, ... do not
@@ -174,6 +286,205 @@ impl Engine {
         self.eval_expression_as_bool(&expression, position, data, context)
     }
 
+    /// Evaluates a pre-compiled expression as a boolean
+    ///
+    /// Same as [`eval_as_bool`], but the expression is not parsed again.
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - A compiled expression to evaluate
+    /// * `position` - An initial position for relative identifiers
+    /// * `data` - A JSON with variable values
+    /// * `context` - An evaluation context
+    ///
+    /// [`eval_as_bool`]: struct.Engine.html#method.eval_as_bool
+    pub fn eval_compiled_as_bool(
+        &self,
+        expression: &CompiledExpression,
+        position: &Identifier,
+        data: &Value,
+        context: &mut Context,
+    ) -> Result<bool> {
+        self.eval_expression_as_bool(expression.expression(), position, data, context)
+    }
+
+    /// Compiles an expression and pre-computes constant sub-expressions
+    ///
+    /// Sub-expressions without identifiers, which call pure functions and filters only,
+    /// are evaluated once and replaced with the result. Check the [`fold_constants`] method
+    /// for more details.
+    ///
+    /// The compiled expression can be evaluated with this engine only. Other engines can
+    /// have different functions and filters registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - An expression to compile
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{
+    ///     ast::Identifier,
+    ///     Engine, Context, Value
+    /// };
+    /// use serde_json::json;
+    ///
+    /// let engine = Engine::default();
+    /// let mut ctx = Context::default();
+    /// let position = Identifier::default();
+    ///
+    /// let compiled = engine.compile("POW(2, 10) * count").unwrap();
+    ///
+    /// assert_eq!(compiled.as_str(), "POW(2, 10) * count");
+    /// assert_eq!(compiled.expression().to_string(), "1024 * count");
+    ///
+    /// for i in 0..10 {
+    ///     let data = json!({"count": i});
+    ///     assert_eq!(
+    ///         engine.eval_compiled(&compiled, &position, &data, &mut ctx).unwrap(),
+    ///         json!(1024 * i)
+    ///     );
+    /// }
+    /// ```
+    ///
+    /// [`fold_constants`]: struct.Engine.html#method.fold_constants
+    pub fn compile<S>(&self, expression: S) -> Result<CompiledExpression>
+    where
+        S: Into<String>,
+    {
+        let mut compiled = CompiledExpression::new(expression)?;
+        self.fold_constants(compiled.expression_mut());
+        Ok(compiled)
+    }
+
+    /// Lists registered functions
+    ///
+    /// Returns function names (including special forms) with their metadata, sorted by name.
+    /// Visit [`Metadata`] to learn more about metadata.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{Determinism, Engine};
+    ///
+    /// let engine = Engine::default();
+    /// let functions = engine.functions();
+    ///
+    /// let (name, metadata) = functions[0];
+    /// assert_eq!(name, "COALESCE");
+    /// assert_eq!(metadata.determinism, Determinism::Pure);
+    /// # #[cfg(feature = "uuid")]
+    /// assert!(functions.iter().any(|(name, _)| *name == "UUIDV4"));
+    /// ```
+    ///
+    /// [`Metadata`]: struct.Metadata.html
+    pub fn functions(&self) -> Vec<(&str, &Metadata)> {
+        registrations(self.functions.keys(), &self.function_metadata)
+    }
+
+    /// Lists registered filters
+    ///
+    /// Returns filter names with their metadata, sorted by name. Visit [`Metadata`] to learn
+    /// more about metadata.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::Engine;
+    ///
+    /// let engine = Engine::default();
+    /// let names: Vec<&str> = engine.filters().iter().map(|(name, _)| *name).collect();
+    ///
+    /// # #[cfg(all(feature = "datetime", feature = "slugify"))]
+    /// assert_eq!(names, vec!["DATE", "DATETIME", "LOWER", "SLUGIFY", "TIME", "TRIM", "UPPER"]);
+    /// ```
+    ///
+    /// [`Metadata`]: struct.Metadata.html
+    pub fn filters(&self) -> Vec<(&str, &Metadata)> {
+        registrations(self.filters.keys(), &self.filter_metadata)
+    }
+
+    /// Returns registered function metadata
+    ///
+    /// Returns `None` if there's no such function. Function name is case insensitive.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Function name
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{Engine, ValueType};
+    ///
+    /// let engine = Engine::default();
+    /// let metadata = engine.function_metadata("pow").unwrap();
+    ///
+    /// assert_eq!(metadata.description.as_ref().unwrap(), "Raises `base` to the power of `exponent`");
+    /// assert_eq!(metadata.signature.as_ref().unwrap().return_type, ValueType::Number);
+    /// assert!(engine.function_metadata("FOO").is_none());
+    /// ```
+    pub fn function_metadata(&self, name: &str) -> Option<&Metadata> {
+        let name = name.to_uppercase();
+        if self.functions.contains_key(&name) {
+            self.function_metadata.get(&name)
+        } else {
+            None
+        }
+    }
+
+    /// Returns registered filter metadata
+    ///
+    /// Returns `None` if there's no such filter. Filter name is case insensitive.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Filter name
+    pub fn filter_metadata(&self, name: &str) -> Option<&Metadata> {
+        let name = name.to_uppercase();
+        if self.filters.contains_key(&name) {
+            self.filter_metadata.get(&name)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn function_determinism(&self, name: &str) -> Determinism {
+        self.function_metadata
+            .get(name)
+            .map(|x| x.determinism)
+            .unwrap_or(Determinism::ContextDependent)
+    }
+
+    pub(crate) fn filter_determinism(&self, name: &str) -> Determinism {
+        self.filter_metadata
+            .get(name)
+            .map(|x| x.determinism)
+            .unwrap_or(Determinism::ContextDependent)
+    }
+
+    pub(crate) fn is_pure_function(&self, name: &str) -> bool {
+        self.function_determinism(name) == Determinism::Pure
+    }
+
+    pub(crate) fn is_pure_filter(&self, name: &str) -> bool {
+        self.filter_determinism(name) == Determinism::Pure
+    }
+
+    // Fails if the engine is reproducible and the function / filter is not pure
+    fn validate_determinism(&self, kind: &'static str, name: &str, determinism: Determinism) -> Result<()> {
+        if self.reproducible && determinism != Determinism::Pure {
+            return Err(Error::new(
+                ErrorKind::NonDeterministic,
+                "non-deterministic call in the reproducible mode",
+            )
+            .context(kind, name.to_string())
+            .context("determinism", determinism.to_string()));
+        }
+        Ok(())
+    }
+
     #[allow(dead_code)]
     pub(crate) fn eval_keyword(&self) -> &str {
         &self.eval_keyword
@@ -253,6 +564,53 @@ impl Engine {
         }
     }
 
+    fn call_function(
+        &self,
+        name: &str,
+        function: &Function,
+        args: &[Value],
+        kwargs: &HashMap<String, Value>,
+        context: &mut Context,
+    ) -> Result<Value> {
+        let determinism = self.function_determinism(name);
+        self.validate_determinism("function", name, determinism)?;
+        let call = Call {
+            engine: self.id,
+            kind: "function",
+            name,
+            input: None,
+            args,
+            kwargs,
+        };
+        call_memoized(call, determinism, context, |context| {
+            function.call(name, args, kwargs, context)
+        })
+    }
+
+    fn call_filter(
+        &self,
+        name: &str,
+        filter: &Filter,
+        input: &Value,
+        args: &[Value],
+        kwargs: &HashMap<String, Value>,
+        context: &mut Context,
+    ) -> Result<Value> {
+        let determinism = self.filter_determinism(name);
+        self.validate_determinism("filter", name, determinism)?;
+        let call = Call {
+            engine: self.id,
+            kind: "filter",
+            name,
+            input: Some(input),
+            args,
+            kwargs,
+        };
+        call_memoized(call, determinism, context, |context| {
+            filter.call(name, input, args, kwargs, context)
+        })
+    }
+
     fn eval_args(
         &self,
         args: &[Expression],
@@ -269,39 +627,130 @@ impl Engine {
         Ok(result)
     }
 
+    fn eval_kwargs(
+        &self,
+        kwargs: &BTreeMap<String, Expression>,
+        position: &Identifier,
+        data: &Value,
+        context: &mut Context,
+    ) -> Result<HashMap<String, Value>> {
+        let mut result = HashMap::new();
+
+        for (name, arg) in kwargs {
+            result.insert(
+                name.to_string(),
+                self.eval_expression(arg, position, data, context)?.into_owned(),
+            );
+        }
+
+        Ok(result)
+    }
+
+    // Evaluates a special form, placeholders are replaced with the input (if provided)
+    fn eval_special_form(
+        &self,
+        f: &BoxedSpecialFormFn,
+        call: &FunctionCall,
+        input: Option<&Value>,
+        position: &Identifier,
+        data: &Value,
+        context: &mut Context,
+    ) -> Result<Value> {
+        if !call.kwargs.is_empty() {
+            return Err(
+                Error::new(ErrorKind::InvalidCall, "special form does not accept named arguments")
+                    .context("function", call.name.to_string()),
+            );
+        }
+
+        let mut eval = |expression: &Expression| -> Result<Value> {
+            match input {
+                Some(input) if is_placeholder(expression) => Ok(input.clone()),
+                _ => Ok(self.eval_expression(expression, position, data, context)?.into_owned()),
+            }
+        };
+        f(&call.args, &mut eval)
+    }
+
     fn eval_function<'a>(
         &self,
-        name: &str,
-        args: &'a [Expression],
+        call: &'a FunctionCall,
         position: &Identifier,
         data: &Value,
         context: &mut Context,
     ) -> Result<Cow<'a, Value>> {
-        let args = self.eval_args(args, position, data, context)?;
+        let f = self.functions.get(&call.name).ok_or_else(|| {
+            Error::new(ErrorKind::UnknownFunction, "function not found").context("function", call.name.to_string())
+        })?;
 
-        if let Some(f) = self.functions.get(name) {
-            Ok(Cow::Owned(f(&args, context)?))
-        } else {
-            Err(Error::with_message("function not found").context("function", name.to_string()))
+        if let Function::SpecialForm(f) = f {
+            self.validate_determinism("function", &call.name, self.function_determinism(&call.name))?;
+            return Ok(Cow::Owned(
+                self.eval_special_form(f, call, None, position, data, context)?,
+            ));
         }
+
+        let args = self.eval_args(&call.args, position, data, context)?;
+        let kwargs = self.eval_kwargs(&call.kwargs, position, data, context)?;
+
+        Ok(Cow::Owned(self.call_function(&call.name, f, &args, &kwargs, context)?))
     }
 
     fn eval_filter<'a>(
         &self,
-        name: &str,
+        call: &'a FunctionCall,
         input: &Value,
-        args: &'a [Expression],
         position: &Identifier,
         data: &Value,
         context: &mut Context,
     ) -> Result<Cow<'a, Value>> {
-        let args = self.eval_args(args, position, data, context)?;
+        if call.args.iter().any(is_placeholder) {
+            return Ok(Cow::Owned(
+                self.eval_piped_function(call, input, position, data, context)?,
+            ));
+        }
+
+        let args = self.eval_args(&call.args, position, data, context)?;
+        let kwargs = self.eval_kwargs(&call.kwargs, position, data, context)?;
 
-        if let Some(f) = self.filters.get(name) {
-            Ok(Cow::Owned(f(input, &args, context)?))
+        if let Some(f) = self.filters.get(&call.name) {
+            Ok(Cow::Owned(
+                self.call_filter(&call.name, f, input, &args, &kwargs, context)?,
+            ))
         } else {
-            Err(Error::with_message("filter not found").context("filter", name.to_string()))
+            Err(Error::new(ErrorKind::UnknownFilter, "filter not found").context("filter", call.name.to_string()))
+        }
+    }
+
+    // Evaluates a function used in the filter position, placeholders are replaced with the input
+    fn eval_piped_function(
+        &self,
+        call: &FunctionCall,
+        input: &Value,
+        position: &Identifier,
+        data: &Value,
+        context: &mut Context,
+    ) -> Result<Value> {
+        let f = self.functions.get(&call.name).ok_or_else(|| {
+            Error::new(ErrorKind::UnknownFunction, "function not found").context("function", call.name.to_string())
+        })?;
+
+        if let Function::SpecialForm(f) = f {
+            self.validate_determinism("function", &call.name, self.function_determinism(&call.name))?;
+            return self.eval_special_form(f, call, Some(input), position, data, context);
+        }
+
+        let mut args = vec![];
+        for arg in call.args.iter() {
+            if is_placeholder(arg) {
+                args.push(input.clone());
+            } else {
+                args.push(self.eval_expression(arg, position, data, context)?.into_owned());
+            }
         }
+        let kwargs = self.eval_kwargs(&call.kwargs, position, data, context)?;
+
+        self.call_function(&call.name, f, &args, &kwargs, context)
     }
 
     fn eval_value_as_number(
@@ -319,7 +768,7 @@ impl Engine {
                 match value {
                     Value::Number(num) => num.clone(),
                     _ => {
-                        return Err(unable_to_evaluate_as_a_number_error().context("value", format!("{:?}", value)));
+                        return Err(unable_to_evaluate_as_a_number_error().context("value", value.to_string()));
                     }
                 }
             }
@@ -340,34 +789,35 @@ impl Engine {
                 let value = &*self.eval_ternary_expression(condition, truthy, falsy, position, data, context)?;
                 match value {
                     Value::Number(num) => num.clone(),
-                    _ => return Err(unable_to_evaluate_as_a_number_error().context("value", format!("{:?}", value))),
+                    _ => return Err(unable_to_evaluate_as_a_number_error().context("value", value.to_string())),
                 }
             }
-            ExpressionValue::FunctionCall(FunctionCall { ref name, ref args }) => {
-                let value = &*self.eval_function(name, args, position, data, context)?;
+            ExpressionValue::FunctionCall(ref call) => {
+                let value = &*self.eval_function(call, position, data, context)?;
                 match value {
                     Value::Number(num) => num.clone(),
                     _ => {
                         let error = unable_to_evaluate_as_a_number_error()
-                            .context("value", format!("{:?}", value))
-                            .context("function", name.to_string());
+                            .context("value", value.to_string())
+                            .context("function", call.name.to_string());
 
                         return Err(error);
                     }
                 }
             }
             ExpressionValue::Boolean(_) => {
-                return Err(unable_to_evaluate_as_a_number_error().context("value", format!("{:?}", value)));
+                return Err(unable_to_evaluate_as_a_number_error().context("value", value.to_string()));
             }
             ExpressionValue::String(_) => {
-                return Err(unable_to_evaluate_as_a_number_error().context("value", format!("{:?}", value)));
+                return Err(unable_to_evaluate_as_a_number_error().context("value", value.to_string()));
             }
             ExpressionValue::Logical(_) => {
-                return Err(unable_to_evaluate_as_a_number_error().context("value", format!("{:?}", value)));
+                return Err(unable_to_evaluate_as_a_number_error().context("value", value.to_string()));
             }
             ExpressionValue::StringConcat(_) => {
-                return Err(unable_to_evaluate_as_a_number_error().context("value", format!("{:?}", value)));
+                return Err(unable_to_evaluate_as_a_number_error().context("value", value.to_string()));
             }
+            ExpressionValue::Placeholder => return Err(placeholder_outside_of_filter_error()),
         };
 
         Ok(number)
@@ -388,15 +838,15 @@ impl Engine {
         )?));
 
         for filter in expression.filters.iter() {
-            result = self.eval_filter(&filter.name, &result, &filter.args, position, data, context)?;
+            result = self.eval_filter(filter, &result, position, data, context)?;
         }
 
         if let Value::Number(x) = result.into_owned() {
             Ok(x)
         } else {
-            Err(Error::with_message("unable to evaluated as number")
+            Err(Error::new(ErrorKind::TypeMismatch, "unable to evaluated as number")
                 .context("expected", "number")
-                .context("expression", format!("{:?}", expression)))
+                .context("expression", expression.to_string()))
         }
     }
 
@@ -431,18 +881,20 @@ impl Engine {
             ExpressionValue::Identifier(ref x) => {
                 Lookup::lookup_identifier(data, x, position, &self.eval_keyword)?.to_owned()
             }
-            ExpressionValue::Math(_) => {
-                Cow::Owned(Value::Number(self.eval_as_number(expression, position, data, context)?))
-            }
+            // Filters are applied below, not by `eval_as_number`
+            ExpressionValue::Math(_) => Cow::Owned(Value::Number(self.eval_value_as_number(
+                &expression.value,
+                position,
+                data,
+                context,
+            )?)),
             ExpressionValue::Logical(_) => Cow::Owned(Value::Bool(self.eval_value_as_bool(
                 &expression.value,
                 position,
                 data,
                 context,
             )?)),
-            ExpressionValue::FunctionCall(FunctionCall { ref name, ref args }) => {
-                self.eval_function(name, args, position, data, context)?
-            }
+            ExpressionValue::FunctionCall(ref call) => self.eval_function(call, position, data, context)?,
             ExpressionValue::Ternary(TernaryExpression {
                 ref condition,
                 ref truthy,
@@ -461,9 +913,9 @@ impl Engine {
                                 Value::String(ref x) => result.push_str(x),
                                 Value::Number(ref x) => result.push_str(&format!("{}", x)),
                                 _ => {
-                                    return Err(Error::with_message("unable to concatenate string")
+                                    return Err(Error::new(ErrorKind::TypeMismatch, "unable to concatenate string")
                                         .context("expected", "number")
-                                        .context("value", format!("{:?}", x)));
+                                        .context("value", x.to_string()));
                                 }
                             }
                         }
@@ -473,20 +925,21 @@ impl Engine {
 
                 Cow::Owned(Value::String(result))
             }
+            ExpressionValue::Placeholder => return Err(placeholder_outside_of_filter_error()),
         };
 
         for filter in expression.filters.iter() {
-            result = self.eval_filter(&filter.name, &result, &filter.args, position, data, context)?;
+            result = self.eval_filter(filter, &result, position, data, context)?;
         }
 
         if expression.negated {
             if let Value::Bool(x) = *result {
                 result = Cow::Owned(Value::Bool(!x));
             } else {
-                return Err(Error::with_message("unable to negate expression")
+                return Err(Error::new(ErrorKind::TypeMismatch, "unable to negate expression")
                     .context("expected", "bool")
                     .context("value", result.to_string())
-                    .context("expression", format!("{:?}", expression)));
+                    .context("expression", expression.to_string()));
             }
         }
 
@@ -506,8 +959,9 @@ impl Engine {
             | ExpressionValue::String(_)
             | ExpressionValue::Math(_)
             | ExpressionValue::StringConcat(_) => {
-                return Err(unable_to_evaluate_as_a_bool_error().context("value", format!("{:?}", value)));
+                return Err(unable_to_evaluate_as_a_bool_error().context("value", value.to_string()));
             }
+            ExpressionValue::Placeholder => return Err(placeholder_outside_of_filter_error()),
             ExpressionValue::Boolean(x) => *x,
             ExpressionValue::Identifier(identifier) => {
                 let value = Lookup::lookup_identifier(data, identifier, position, &self.eval_keyword)?;
@@ -516,7 +970,7 @@ impl Engine {
                 } else {
                     return Err(unable_to_evaluate_as_a_bool_error()
                         .context("value", value.to_string())
-                        .context("identifier", format!("{:?}", identifier)));
+                        .context("identifier", identifier.to_string()));
                 }
             }
             ExpressionValue::Ternary(TernaryExpression {
@@ -582,14 +1036,14 @@ impl Engine {
                     }
                 }
             },
-            ExpressionValue::FunctionCall(FunctionCall { ref name, ref args }) => {
-                let value = self.eval_function(name, args, position, data, context)?;
+            ExpressionValue::FunctionCall(ref call) => {
+                let value = self.eval_function(call, position, data, context)?;
                 if let Value::Bool(value) = value.as_ref() {
                     *value
                 } else {
                     return Err(unable_to_evaluate_as_a_bool_error()
-                        .context("value", format!("{:?}", value))
-                        .context("function", name.to_string()));
+                        .context("value", value.to_string())
+                        .context("function", call.name.to_string()));
                 }
             }
         };
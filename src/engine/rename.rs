@@ -0,0 +1,294 @@
+use serde_json::Value;
+
+use crate::{
+    ast::*,
+    engine::{
+        helper::{get_value, remove_value, set_value, Formulas},
+        Engine,
+    },
+    error::*,
+};
+
+/// Checks that the path can be renamed
+///
+/// Path must not contain indirect lookups and negative indexes and the last path value
+/// must be an object field name.
+fn validate_path(path: &Identifier) -> Result<Identifier> {
+    let canonical = path.canonicalize(&Identifier::default())?;
+
+    let valid = canonical.values.iter().all(|x| match x {
+        IdentifierValue::Name(_) => true,
+        IdentifierValue::Index(index) => *index >= 0,
+        _ => false,
+    });
+
+    if !valid {
+        return Err(Error::new(ErrorKind::InvalidUpdate, "unable to rename")
+            .context("reason", "indirect lookups and negative indexes are not allowed")
+            .context("path", path.to_string()));
+    }
+
+    match canonical.values.last() {
+        Some(IdentifierValue::Name(_)) => Ok(canonical),
+        _ => Err(Error::new(ErrorKind::InvalidUpdate, "unable to rename")
+            .context("reason", "path must refer to an object field")
+            .context("path", path.to_string())),
+    }
+}
+
+/// Replaces the `from` prefix of identifier values with the `to` values
+///
+/// Returns `None` if identifier values do not start with `from`.
+fn rename_values(values: &[IdentifierValue], from: &Identifier, to: &Identifier) -> Option<Vec<IdentifierValue>> {
+    if !values.starts_with(&from.values) {
+        return None;
+    }
+
+    Some(
+        to.values
+            .iter()
+            .chain(values[from.values.len()..].iter())
+            .cloned()
+            .collect(),
+    )
+}
+
+/// Resolves identifier values against the position
+///
+/// Unlike [`Identifier::canonicalize`], nested identifiers are kept as they are. Returns
+/// `None` if the identifier can't be resolved.
+///
+/// [`Identifier::canonicalize`]: ../../ast/struct.Identifier.html#method.canonicalize
+fn resolve(identifier: &Identifier, position: &Identifier) -> Option<Vec<IdentifierValue>> {
+    let mut result = vec![];
+
+    if identifier.is_relative() {
+        if position.values.is_empty() || position.is_relative() {
+            return None;
+        }
+        result.extend(position.values.iter().cloned());
+    }
+
+    for value in identifier.values.iter() {
+        match value {
+            IdentifierValue::This => {}
+            IdentifierValue::Super => {
+                result.pop()?;
+            }
+            _ => result.push(value.clone()),
+        };
+    }
+
+    Some(result)
+}
+
+/// Creates a relative identifier referring to the target from the position
+fn relative_identifier(target: &[IdentifierValue], position: &Identifier) -> Identifier {
+    let common = position
+        .values
+        .iter()
+        .zip(target.iter())
+        .take_while(|(lhs, rhs)| lhs == rhs)
+        .count();
+
+    let mut values = if common == position.values.len() {
+        vec![IdentifierValue::This]
+    } else {
+        vec![IdentifierValue::Super; position.values.len() - common]
+    };
+    values.extend(target[common..].iter().cloned());
+
+    Identifier::new(values)
+}
+
+/// Formula rename context
+struct Rename<'a> {
+    /// Renamed path (canonical)
+    from: &'a Identifier,
+    /// New path (canonical)
+    to: &'a Identifier,
+    /// Formula position before rename
+    old_position: &'a Identifier,
+    /// Formula position after rename
+    new_position: &'a Identifier,
+}
+
+impl<'a> Rename<'a> {
+    /// Rewrites an identifier to refer to the same value after rename
+    ///
+    /// Identifier is kept untouched if it still refers to the same value. Otherwise it's
+    /// rewritten and the notation (absolute, relative) is preserved.
+    fn identifier(&self, identifier: &Identifier) -> Identifier {
+        let rewritten = Identifier::new(
+            identifier
+                .values
+                .iter()
+                .map(|value| match value {
+                    IdentifierValue::Identifier(ref nested) => IdentifierValue::Identifier(self.identifier(nested)),
+                    _ => value.clone(),
+                })
+                .collect(),
+        );
+
+        let target = match resolve(&rewritten, self.old_position) {
+            Some(values) => rename_values(&values, self.from, self.to).unwrap_or(values),
+            None => return rewritten,
+        };
+
+        if resolve(&rewritten, self.new_position).as_ref() == Some(&target) {
+            rewritten
+        } else if rewritten.is_relative() {
+            relative_identifier(&target, self.new_position)
+        } else {
+            Identifier::new(target)
+        }
+    }
+}
+
+impl<'a> VisitorMut for Rename<'a> {
+    // Rewrites all expression identifiers to refer to the same values after rename,
+    // nested identifiers are rewritten by the `identifier` method
+    fn visit_identifier_mut(&mut self, identifier: &mut Identifier) {
+        *identifier = self.identifier(identifier);
+    }
+}
+
+/// Renames an object field and rewrites all formulas referring to it
+///
+/// The value at the `from` path is moved to the `to` path. Formulas referring to the value
+/// (or to any nested value) are rewritten to refer to the new path. Formulas moved along with
+/// the value are rewritten as well if their relative identifiers (`super.hostname`) do not
+/// refer to the same values anymore.
+///
+/// Absolute identifiers stay absolute and relative identifiers stay relative. Formulas
+/// which do not need to be rewritten are kept untouched, rewritten formulas are printed
+/// in the canonical notation.
+///
+/// Indirect lookups (`networks[index]`) and negative indexes can't be resolved statically
+/// and are never rewritten. Formulas which can't be parsed are kept untouched.
+///
+/// # Arguments
+///
+/// * `data` - A JSON
+/// * `from` - A path to rename, the last value must be an object field name
+/// * `to` - A new path, the last value must be an object field name, parent object must exist
+///
+/// # Examples
+///
+/// ```rust
+/// use balena_temen::{ast::Identifier, rename};
+/// use serde_json::json;
+///
+/// let data = json!({
+///     "wifi": {
+///         "ssid": "Balena 5G",
+///         "id": {
+///             "$$formula": "super.ssid | SLUGIFY"
+///         }
+///     },
+///     "hostname": {
+///         "$$formula": "`balena-` ~ wifi[\"ssid\"]"
+///     }
+/// });
+///
+/// let from: Identifier = "wifi.ssid".parse().unwrap();
+/// let to: Identifier = "wifi.name".parse().unwrap();
+///
+/// assert_eq!(
+///     rename(data, &from, &to).unwrap(),
+///     json!({
+///         "wifi": {
+///             "name": "Balena 5G",
+///             "id": {
+///                 "$$formula": "super.name | SLUGIFY"
+///             }
+///         },
+///         "hostname": {
+///             "$$formula": "`balena-` ~ wifi.name"
+///         }
+///     })
+/// );
+/// ```
+pub fn rename(data: Value, from: &Identifier, to: &Identifier) -> Result<Value> {
+    rename_with_engine(data, from, to, &Engine::default())
+}
+
+/// Renames an object field and rewrites all formulas referring to it with custom [`Engine`]
+///
+/// Check the [`rename`] function for more details and examples.
+///
+/// # Arguments
+///
+/// * `data` - A JSON
+/// * `from` - A path to rename, the last value must be an object field name
+/// * `to` - A new path, the last value must be an object field name, parent object must exist
+/// * `engine` - An engine to use (evaluation keyword)
+///
+/// [`rename`]: fn.rename.html
+/// [`Engine`]: struct.Engine.html
+pub fn rename_with_engine(data: Value, from: &Identifier, to: &Identifier, engine: &Engine) -> Result<Value> {
+    let from = validate_path(from)?;
+    let to = validate_path(to)?;
+
+    if from == to {
+        return Ok(data);
+    }
+
+    let error = || {
+        Error::new(ErrorKind::InvalidUpdate, "unable to rename")
+            .context("from", from.to_string())
+            .context("to", to.to_string())
+    };
+
+    if to.values.starts_with(&from.values) {
+        return Err(error().context("reason", "new path is nested in the renamed path"));
+    }
+    if get_value(&data, &from).is_none() {
+        return Err(error().context("reason", "path does not exist"));
+    }
+    if get_value(&data, &to).is_some() {
+        return Err(error().context("reason", "new path already exists"));
+    }
+    match get_value(&data, &to.clone().pop()?) {
+        Some(Value::Object(_)) => {}
+        _ => return Err(error().context("reason", "new path parent is not an object")),
+    };
+
+    let keyword = engine.eval_keyword();
+    let formulas = Formulas::new(&data, keyword)?;
+
+    let mut rewritten = vec![];
+    for idx in 0..formulas.len() {
+        let old_position = formulas.position(idx);
+        let new_position = rename_values(&old_position.values, &from, &to)
+            .map(Identifier::new)
+            .unwrap_or_else(|| old_position.clone());
+
+        if let Some(expression) = formulas.expression(idx) {
+            let mut rename = Rename {
+                from: &from,
+                to: &to,
+                old_position,
+                new_position: &new_position,
+            };
+
+            let mut new_expression = expression.clone();
+            rename.visit_expression_mut(&mut new_expression);
+
+            if &new_expression != expression {
+                rewritten.push((new_position.name(keyword), new_expression.to_string()));
+            }
+        }
+    }
+
+    let mut data = data;
+    // Can't fail, existence checked above
+    let value = remove_value(&mut data, &from).ok_or_else(error)?;
+    set_value(&mut data, value, &to)?;
+
+    for (position, formula) in rewritten {
+        set_value(&mut data, Value::String(formula), &position)?;
+    }
+
+    Ok(data)
+}
@@ -43,11 +43,25 @@
 //!     └ context:
 //!        └ expression: 1 = `23`
 //! ```
+//!
+//! Every error has a kind, which allows callers to match on the failure category instead of
+//! the message.
+//!
+//! ```rust
+//! use balena_temen::{error::ErrorKind, Engine};
+//!
+//! let engine = Engine::default();
+//!
+//! assert_eq!(engine.check("UNKNOWN()").unwrap_err().kind(), ErrorKind::UnknownFunction);
+//! assert_eq!(engine.check("1 +").unwrap_err().kind(), ErrorKind::Syntax);
+//! ```
 use std::borrow::Cow;
 use std::error;
 use std::fmt;
 use std::result;
 
+use crate::ast::Identifier;
+
 /// Standard library result wrapper
 pub type Result<T> = result::Result<T, Error>;
 
@@ -137,7 +151,86 @@ impl<T> ResultExt<T> for Result<T> {
     }
 }
 
+/// A category of an error
+///
+/// New kinds can be added in the future, match with a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// An expression or an identifier can't be parsed
+    Syntax,
+    /// A function is not registered
+    UnknownFunction,
+    /// A filter is not registered
+    UnknownFilter,
+    /// An identifier can't be canonicalized or its value doesn't exist
+    LookupFailed,
+    /// A value (argument, filter input, operand, ...) is of an unexpected type
+    TypeMismatch,
+    /// A value is of the expected type, but it's not valid (unknown time zone, ...)
+    InvalidValue,
+    /// A function or a filter is called with an invalid number of arguments, with unknown
+    /// named arguments, ...
+    InvalidCall,
+    /// An arithmetic operation results to NaN or infinity
+    Arithmetic,
+    /// Formulas depend on each other
+    CircularDependency,
+    /// A non-deterministic function or filter is called in the reproducible mode
+    NonDeterministic,
+    /// A value can't be set, updated or renamed at the given position
+    InvalidUpdate,
+    /// One or more formulas (or their dependencies) can't be evaluated
+    ///
+    /// Visit the [`Error::failures`] method to get the list of failures.
+    ///
+    /// [`Error::failures`]: struct.Error.html#method.failures
+    Evaluation,
+    /// A builtin failed because of an internal failure (random generator, ...)
+    Internal,
+    /// An error created by a custom function or filter (see [`Error::with_message`])
+    ///
+    /// [`Error::with_message`]: struct.Error.html#method.with_message
+    Custom,
+}
+
+/// A formula evaluation failure
+///
+/// Visit the [`Error::failures`] method documentation for an example.
+///
+/// [`Error::failures`]: struct.Error.html#method.failures
+#[derive(Clone, Debug)]
+pub struct FormulaError {
+    /// A formula position
+    pub position: Identifier,
+    /// A formula expression (`$$formula` value)
+    pub expression: String,
+    /// An error describing why the formula evaluation failed
+    pub error: Error,
+}
+
+impl FormulaError {
+    /// Creates new formula evaluation failure
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - A formula position
+    /// * `expression` - A formula expression
+    /// * `error` - An evaluation error
+    pub fn new<S>(position: Identifier, expression: S, error: Error) -> FormulaError
+    where
+        S: Into<String>,
+    {
+        FormulaError {
+            position,
+            expression: expression.into(),
+            error,
+        }
+    }
+}
+
 /// Error type
+#[derive(Clone)]
 pub struct Error {
     // Box is not really required here, but we'd like to keep
     // Result as small as possible. Inner can be very huge
@@ -146,17 +239,132 @@ pub struct Error {
 }
 
 impl Error {
+    /// Creates new error of the given kind
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - An error kind
+    /// * `message` - An error message
+    pub fn new<M>(kind: ErrorKind, message: M) -> Error
+    where
+        M: Into<Display>,
+    {
+        let inner = Inner::new(kind, message);
+        Error { inner: Box::new(inner) }
+    }
+
     /// Creates new error with message
     ///
+    /// The error kind is [`ErrorKind::Custom`].
+    ///
     /// # Arguments
     ///
     /// * `message` - An error message
+    ///
+    /// [`ErrorKind::Custom`]: enum.ErrorKind.html#variant.Custom
     pub fn with_message<M>(message: M) -> Error
     where
         M: Into<Display>,
     {
-        let inner = Inner::new(message);
-        Error { inner: Box::new(inner) }
+        Error::new(ErrorKind::Custom, message)
+    }
+
+    /// Creates new error with the list of formula evaluation failures
+    ///
+    /// # Arguments
+    ///
+    /// * `failures` - List of formula evaluation failures
+    pub fn with_failures(failures: Vec<FormulaError>) -> Error {
+        let count = failures.len();
+        let mut inner = Inner::new(ErrorKind::Evaluation, "unable to evaluate");
+        inner.failures = failures;
+        Error { inner: Box::new(inner) }.context("failures", format!("{}", count))
+    }
+
+    /// Returns the list of formula evaluation failures
+    ///
+    /// The list is empty if the error is not a document evaluation error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{ast::Identifier, evaluate};
+    /// use serde_json::json;
+    ///
+    /// let data = json!({
+    ///     "id": {
+    ///         "$$formula": "super.missing | SLUGIFY"
+    ///     },
+    ///     "name": {
+    ///         "$$formula": "UNKNOWN()"
+    ///     },
+    ///     "valid": {
+    ///         "$$formula": "1 + 2"
+    ///     }
+    /// });
+    ///
+    /// let error = evaluate(data).unwrap_err();
+    /// let failures = error.failures();
+    ///
+    /// assert_eq!(failures.len(), 2);
+    /// assert_eq!(failures[0].position, Identifier::default().name("id"));
+    /// assert_eq!(failures[0].expression, "super.missing | SLUGIFY");
+    /// assert_eq!(failures[1].position, Identifier::default().name("name"));
+    /// assert_eq!(failures[1].expression, "UNKNOWN()");
+    /// ```
+    pub fn failures(&self) -> &[FormulaError] {
+        &self.inner.failures
+    }
+
+    /// Returns the error kind
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::{ast::Identifier, error::ErrorKind, Context, Engine, Value};
+    ///
+    /// let engine = Engine::default();
+    /// let error = engine
+    ///     .eval("missing + 1", &Identifier::default(), &Value::Null, &mut Context::default())
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(error.kind(), ErrorKind::LookupFailed);
+    /// assert_eq!(error.message(), "unable to lookup identifier");
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        self.inner.kind
+    }
+
+    /// Returns the error message
+    pub fn message(&self) -> &str {
+        &self.inner.message
+    }
+
+    /// Returns the first context value with the given key
+    ///
+    /// All frames are searched, from the first one to the last one.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A context key
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use balena_temen::error::*;
+    ///
+    /// let error = Error::new(ErrorKind::UnknownFunction, "function not found").context("function", "FOO");
+    ///
+    /// assert_eq!(error.context_value("function"), Some("FOO"));
+    /// assert_eq!(error.context_value("filter"), None);
+    /// ```
+    pub fn context_value(&self, key: &str) -> Option<&str> {
+        self.inner
+            .frames
+            .iter()
+            .flat_map(|x| x.context().iter())
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_ref())
     }
 
     /// Appends key, value pair to context of the last frame
@@ -242,6 +450,23 @@ impl fmt::Display for Error {
                 }
             }
         }
+
+        if !self.inner.failures.is_empty() {
+            writeln!(f, " failures:")?;
+            let last_failure_idx = self.inner.failures.len() - 1;
+            for (failure_idx, failure) in self.inner.failures.iter().enumerate() {
+                let (failure_indent, error_indent) = if last_failure_idx == failure_idx {
+                    (" └", "   ")
+                } else {
+                    (" ├", " | ")
+                };
+
+                writeln!(f, "{} {}: {}", failure_indent, failure.position, failure.expression)?;
+                for line in failure.error.to_string().lines() {
+                    writeln!(f, "{} {}", error_indent, line)?;
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -258,23 +483,29 @@ impl error::Error for Error {
     }
 }
 
+#[derive(Clone)]
 struct Inner {
+    kind: ErrorKind,
     message: Display,
     frames: Vec<Frame>,
+    failures: Vec<FormulaError>,
 }
 
 impl Inner {
-    fn new<M>(message: M) -> Inner
+    fn new<M>(kind: ErrorKind, message: M) -> Inner
     where
         M: Into<Display>,
     {
         Inner {
+            kind,
             message: message.into(),
             frames: vec![Frame::new()],
+            failures: vec![],
         }
     }
 }
 
+#[derive(Clone)]
 struct Frame {
     name: Option<Display>,
     context: Vec<(Display, Display)>,
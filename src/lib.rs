@@ -5,7 +5,14 @@
 //! * parse an expression
 //! * evaluate an expression
 //! * evaluate an expression as a boolean (logical expressions)
-//! * register custom functions and filters
+//! * compile an expression once and evaluate it many times
+//! * pre-compute constant sub-expressions (constant folding)
+//! * list identifiers an expression depends on
+//! * walk and transform an expression AST (visitors)
+//! * register custom functions and filters (closures, typed arguments and result conversion)
+//! * declare functions and filters determinism (memoization, reproducible evaluation)
+//! * declare functions and filters signatures and check expressions without data
+//! * list registered functions and filters with their metadata
 //!
 //! [Expression language documentation].
 //!
@@ -24,6 +31,8 @@
 //! ### JSON evaluation
 //!
 //! ```rust
+//! # #[cfg(feature = "slugify")]
+//! # {
 //! use balena_temen::{evaluate, Value};
 //! use serde_json::json;
 //!
@@ -42,6 +51,7 @@
 //!     }
 //! });
 //! assert_eq!(evaluate(data).unwrap(), evaluated);
+//! # }
 //! ```
 //!
 //! ### JSON with custom evaluation keyword
@@ -84,6 +94,7 @@
 //!     json!(5)
 //! );
 //!
+//! # #[cfg(feature = "slugify")]
 //! assert_eq!(
 //!     engine.eval("`Balena templating engine!` | SLUGIFY", &position, &data, &mut ctx).unwrap(),
 //!     json!("balena-templating-engine")
@@ -193,11 +204,24 @@
 pub use serde_json::Value;
 
 pub use crate::{
-    builtin::{filter::FilterFn, function::FunctionFn},
+    builtin::{
+        filter::{FilterFn, FilterKwargsFn},
+        function::{FunctionFn, FunctionKwargsFn, SpecialFormFn},
+        Argument, BuiltinGroup, Determinism, FromValue, IntoValue, Metadata, Signature, TypedFilter, TypedFunction,
+        ValueType,
+    },
     context::Context,
     engine::{
         builder::EngineBuilder,
-        helper::{evaluate, evaluate_with_engine},
+        compiled::CompiledExpression,
+        evaluator::Evaluator,
+        format::{format_document, format_document_with_engine, format_expression},
+        graph::{DependencyGraph, DependencyNode},
+        helper::{
+            dependents, dependents_with_engine, evaluate, evaluate_partial, evaluate_partial_with_engine,
+            evaluate_with_engine,
+        },
+        rename::{rename, rename_with_engine},
         Engine,
     },
 };
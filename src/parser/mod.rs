@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use lazy_static::lazy_static;
 use pest::{
     iterators::Pair,
@@ -47,26 +49,69 @@ fn parse_arg(pair: Pair<Rule>) -> Result<Expression> {
     Ok(value.expect("invalid grammar: no parg"))
 }
 
+//
+// kwarg = { identifier ~ "=" ~ !"=" ~ arg }
+//
+fn parse_kwarg(pair: Pair<Rule>) -> Result<(String, Expression)> {
+    let mut name = None;
+    let mut value = None;
+
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::identifier => name = Some(p.as_str().to_string()),
+            Rule::arg => value = Some(parse_arg(p)?),
+            _ => unreachable!("invalid grammar: {}", p.as_str()),
+        };
+    }
+
+    Ok((
+        name.expect("invalid grammar: no kwarg name"),
+        value.expect("invalid grammar: no kwarg value"),
+    ))
+}
+
 fn parse_function_call(pair: Pair<Rule>) -> Result<FunctionCall> {
     let mut name = None;
     let mut args = vec![];
+    let mut kwargs = BTreeMap::new();
 
     for p in pair.into_inner() {
         match p.as_rule() {
             Rule::function_identifier => name = Some(p.as_span().as_str().to_string()),
-            Rule::arg => args.push(parse_arg(p)?),
+            Rule::arg | Rule::placeholder => {
+                if !kwargs.is_empty() {
+                    return Err(Error::new(ErrorKind::Syntax, "unable to parse function call")
+                        .context("reason", "positional argument follows named argument")
+                        .context("argument", p.as_str().to_string()));
+                }
+                if p.as_rule() == Rule::placeholder {
+                    args.push(Expression::new(ExpressionValue::Placeholder));
+                } else {
+                    args.push(parse_arg(p)?);
+                }
+            }
+            Rule::kwarg => {
+                let (kwarg_name, value) = parse_kwarg(p)?;
+                if kwargs.contains_key(&kwarg_name) {
+                    return Err(Error::new(ErrorKind::Syntax, "unable to parse function call")
+                        .context("reason", "duplicate named argument")
+                        .context("argument", kwarg_name));
+                }
+                kwargs.insert(kwarg_name, value);
+            }
             _ => unreachable!("invalid grammar: {}", p.as_str()),
         };
     }
 
-    Ok(FunctionCall::new(
+    Ok(FunctionCall::new_with_kwargs(
         name.expect("invalid grammar: no function name"),
         args,
+        kwargs,
     ))
 }
 
 //
-// filter  = { "|" ~ (function_call | identifier) }
+// filter  = { "|" ~ (filter_function_call | function_identifier) }
 //
 fn parse_filter(pair: Pair<Rule>) -> Result<FunctionCall> {
     let mut name = None;
@@ -75,9 +120,8 @@ fn parse_filter(pair: Pair<Rule>) -> Result<FunctionCall> {
         match p.as_rule() {
             Rule::function_identifier => name = Some(p.as_span().as_str().to_string()),
             Rule::arg => args.push(parse_arg(p)?),
-            Rule::function_call => {
-                return parse_function_call(p);
-            }
+            // `_` inside an argument expression is a regular identifier
+            Rule::filter_function_call => return parse_function_call(p),
             _ => unreachable!("invalid grammar: {}", p.as_str()),
         };
     }
@@ -112,11 +156,11 @@ fn parse_basic_expression(pair: Pair<Rule>) -> Result<ExpressionValue> {
         Rule::integer => ExpressionValue::Integer(
             pair.as_str()
                 .parse()
-                .map_err(|_| Error::with_message("unable to parse i64").context("value", pair.to_string()))?,
+                .map_err(|_| Error::new(ErrorKind::Syntax, "unable to parse i64").context("value", pair.to_string()))?,
         ),
         Rule::float => {
             ExpressionValue::Float(validate_f64(pair.as_str().parse().map_err(|_| {
-                Error::with_message("unable to parse f64").context("value", pair.to_string())
+                Error::new(ErrorKind::Syntax, "unable to parse f64").context("value", pair.to_string())
             })?)?)
         }
         Rule::boolean => match pair.as_str() {
@@ -285,7 +329,7 @@ fn parse_dotted_square_bracket_identifier_value(pair: Pair<Rule>) -> Result<Iden
             Rule::integer | Rule::positive_integer => IdentifierValue::Index(
                 p.as_str()
                     .parse()
-                    .map_err(|_| Error::with_message("unable to parse i64").context("value", p.to_string()))?,
+                    .map_err(|_| Error::new(ErrorKind::Syntax, "unable to parse i64").context("value", p.to_string()))?,
             ),
             Rule::dotted_square_bracket_identifier => {
                 IdentifierValue::Identifier(parse_dotted_square_bracket_identifier_value(p)?)
@@ -316,11 +360,11 @@ fn parse_string_concat(pair: Pair<Rule>) -> Result<ExpressionValue> {
             Rule::integer => ExpressionValue::Integer(
                 p.as_str()
                     .parse()
-                    .map_err(|_| Error::with_message("unable to parse i64").context("value", p.to_string()))?,
+                    .map_err(|_| Error::new(ErrorKind::Syntax, "unable to parse i64").context("value", p.to_string()))?,
             ),
             Rule::float => {
                 ExpressionValue::Float(validate_f64(p.as_str().parse().map_err(|_| {
-                    Error::with_message("unable to parse f64").context("value", p.to_string())
+                    Error::new(ErrorKind::Syntax, "unable to parse f64").context("value", p.to_string())
                 })?)?)
             }
             Rule::dotted_square_bracket_identifier => parse_dotted_square_bracket_identifier(p)?,
@@ -407,12 +451,12 @@ fn parse_content(pair: Pair<Rule>) -> Result<Expression> {
 
 pub(crate) fn parse(expression: &str) -> Result<Expression> {
     let mut pairs = ExpressionParser::parse(Rule::content, expression).map_err(|e| {
-        Error::with_message("unable to parse expression")
+        Error::new(ErrorKind::Syntax, "unable to parse expression")
             .context("expression", expression.to_string())
             .context("pest", e.to_string())
     })?;
     let next = pairs.next().ok_or_else(|| {
-        Error::with_message("unable to parse expression").context("expression", expression.to_string())
+        Error::new(ErrorKind::Syntax, "unable to parse expression").context("expression", expression.to_string())
     })?;
     parse_content(next)
 }
@@ -14,11 +14,11 @@ use crate::error::*;
 /// * `number` - A number to check
 pub fn validate_f64(number: f64) -> Result<f64> {
     if number.is_nan() {
-        return Err(Error::with_message("NaN f64 not supported"));
+        return Err(Error::new(ErrorKind::Arithmetic, "NaN f64 not supported"));
     }
 
     if number.is_infinite() {
-        return Err(Error::with_message("infinite f64 not supported"));
+        return Err(Error::new(ErrorKind::Arithmetic, "infinite f64 not supported"));
     }
 
     Ok(number)
@@ -0,0 +1,165 @@
+use serde_json::json;
+
+use crate::{test_eval_eq, test_eval_err};
+
+use balena_temen::{error::*, BuiltinGroup, Context, Determinism, Engine, EngineBuilder, Value};
+
+fn names(registrations: Vec<(&str, &balena_temen::Metadata)>) -> Vec<String> {
+    registrations.into_iter().map(|(name, _)| name.to_string()).collect()
+}
+
+// Filters out builtins disabled by cargo features
+fn compiled(names: &[&str]) -> Vec<String> {
+    let features: [(&[&str], bool); 3] = [
+        (&["TIME", "DATE", "DATETIME", "NOW"], cfg!(feature = "datetime")),
+        (&["UUIDV4"], cfg!(feature = "uuid")),
+        (&["SLUGIFY"], cfg!(feature = "slugify")),
+    ];
+
+    names
+        .iter()
+        .filter(|name| {
+            features
+                .iter()
+                .all(|(builtins, enabled)| *enabled || !builtins.contains(name))
+        })
+        .map(|name| name.to_string())
+        .collect()
+}
+
+#[test]
+fn empty_builder() {
+    let engine: Engine = EngineBuilder::new().into();
+
+    assert!(engine.functions().is_empty());
+    assert!(engine.filters().is_empty());
+    test_eval_eq!(engine, "1 + 2", json!(3));
+    test_eval_err!(engine, "POW(1, 2)");
+    test_eval_err!(engine, "`a` | UPPER");
+}
+
+#[test]
+fn builtin_groups() {
+    let groups = [
+        (BuiltinGroup::Strings, vec![], vec!["LOWER", "SLUGIFY", "TRIM", "UPPER"]),
+        (BuiltinGroup::Math, vec!["LOG10", "MAX", "MIN", "POW"], vec![]),
+        (BuiltinGroup::Datetime, vec!["NOW"], vec!["DATE", "DATETIME", "TIME"]),
+        (BuiltinGroup::Random, vec!["UUIDV4"], vec![]),
+        (BuiltinGroup::ControlFlow, vec!["COALESCE", "IF", "TRY"], vec![]),
+    ];
+
+    for (group, functions, filters) in groups.iter() {
+        let engine: Engine = EngineBuilder::new().builtins(*group).into();
+        assert_eq!(names(engine.functions()), compiled(functions), "{:?}", group);
+        assert_eq!(names(engine.filters()), compiled(filters), "{:?}", group);
+    }
+}
+
+#[test]
+fn default_builder_registers_compiled_builtins() {
+    let engine = Engine::default();
+
+    assert_eq!(
+        names(engine.functions()),
+        compiled(&["COALESCE", "IF", "LOG10", "MAX", "MIN", "NOW", "POW", "TRY", "UUIDV4"])
+    );
+    assert_eq!(
+        names(engine.filters()),
+        compiled(&["DATE", "DATETIME", "LOWER", "SLUGIFY", "TIME", "TRIM", "UPPER"])
+    );
+}
+
+#[test]
+#[cfg(not(any(feature = "datetime", feature = "uuid", feature = "slugify")))]
+fn default_builder_without_optional_builtins() {
+    let engine = Engine::default();
+
+    assert_eq!(
+        names(engine.functions()),
+        vec!["COALESCE", "IF", "LOG10", "MAX", "MIN", "POW", "TRY"]
+    );
+    assert_eq!(names(engine.filters()), vec!["LOWER", "TRIM", "UPPER"]);
+
+    test_eval_err!(engine, "NOW()");
+    test_eval_err!(engine, "UUIDV4()");
+    test_eval_err!(engine, "`a b` | SLUGIFY");
+    test_eval_eq!(engine, "`a` | UPPER", json!("A"));
+    assert!(engine.check("ts | DATE").is_err());
+}
+
+#[test]
+fn default_builder_registers_all_groups() {
+    let engine: Engine = BuiltinGroup::all()
+        .iter()
+        .fold(EngineBuilder::new(), |builder, group| builder.builtins(*group))
+        .into();
+    let default = Engine::default();
+
+    assert_eq!(engine.functions(), default.functions());
+    assert_eq!(engine.filters(), default.filters());
+}
+
+#[test]
+fn selected_groups_are_evaluated() {
+    let engine: Engine = EngineBuilder::new()
+        .builtins(BuiltinGroup::Strings)
+        .builtins(BuiltinGroup::Math)
+        .into();
+
+    test_eval_eq!(engine, "`a` | UPPER", json!("A"));
+    test_eval_eq!(engine, "POW(2, 3)", json!(8));
+    test_eval_err!(engine, "NOW()");
+    test_eval_err!(engine, "IF(true, 1, 2)");
+}
+
+#[test]
+fn remove_functions_and_filters() {
+    let engine: Engine = EngineBuilder::default()
+        .without_function("uuidv4")
+        .without_function("NOW")
+        .without_filter("Slugify")
+        .without_function("UNKNOWN")
+        .into();
+
+    test_eval_err!(engine, "UUIDV4()");
+    test_eval_err!(engine, "NOW()");
+    test_eval_err!(engine, "`a b` | SLUGIFY");
+    test_eval_eq!(engine, "`a` | UPPER", json!("A"));
+    assert!(engine.function_metadata("UUIDV4").is_none());
+    assert!(engine.filter_metadata("SLUGIFY").is_none());
+    assert!(!names(engine.functions()).contains(&"NOW".to_string()));
+}
+
+#[test]
+fn override_builtin() {
+    fn fixed_pow(_: &[Value], _: &mut Context) -> Result<Value> {
+        Ok(json!(0))
+    }
+
+    let engine: Engine = EngineBuilder::default().function("POW", fixed_pow).into();
+
+    test_eval_eq!(engine, "POW(2, 3)", json!(0));
+    // Metadata of the overwritten builtin do not apply
+    assert_eq!(
+        engine.function_metadata("POW").unwrap().determinism,
+        Determinism::ContextDependent
+    );
+
+    // Builtin is registered again with its group
+    let engine: Engine = EngineBuilder::default()
+        .function("POW", fixed_pow)
+        .builtins(BuiltinGroup::Math)
+        .into();
+    assert_eq!(engine.function_metadata("POW").unwrap().determinism, Determinism::Pure);
+    test_eval_eq!(engine, "POW(2, 3)", json!(8));
+}
+
+#[test]
+fn removed_builtin_can_be_registered_again() {
+    let engine: Engine = EngineBuilder::default()
+        .without_filter("UPPER")
+        .builtins(BuiltinGroup::Strings)
+        .into();
+
+    test_eval_eq!(engine, "`a` | UPPER", json!("A"));
+}
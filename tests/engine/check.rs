@@ -0,0 +1,315 @@
+use balena_temen::{error::*, Context, Determinism, Engine, EngineBuilder, Metadata, Signature, Value, ValueType};
+
+fn assert_check_error(engine: &Engine, expression: &str, error: Error) {
+    assert_eq!(
+        engine.check(expression).unwrap_err().to_string(),
+        error.to_string(),
+        "{}",
+        expression
+    );
+}
+
+#[test]
+fn valid_expressions() {
+    let engine = Engine::default();
+
+    #[allow(unused_mut)]
+    let mut expressions = vec![
+        "1 + 2",
+        "POW(2, 10) * MAX(1, 2, 3, a)",
+        "MIN(a)",
+        "IF(a == 1, `a`, 2)",
+        "COALESCE(a, b, c)",
+        "TRY(a, 1)",
+        "2 | POW(_, 3)",
+        "a | TRY(_, 1)",
+        "`a` ~ b == c and not d",
+        "(a > 1 ? POW(2, 3) : LOG10(100))",
+    ];
+    #[cfg(feature = "datetime")]
+    expressions.extend(&[
+        "ts | DATE",
+        "ts | DATE(`%Y`)",
+        "ts | DATETIME(format=`%Y`, tz=`Europe/Prague`)",
+        "ts | TIME(`%H`, tz=zone)",
+        "NOW()",
+        "NOW(timestamp=true)",
+    ]);
+    #[cfg(feature = "uuid")]
+    expressions.push("UUIDV4() | UPPER");
+    #[cfg(feature = "slugify")]
+    expressions.push("wifi.ssid | SLUGIFY");
+
+    for expression in expressions.iter() {
+        assert!(engine.check(expression).is_ok(), "{}", expression);
+    }
+}
+
+#[test]
+fn data_are_not_required() {
+    let engine = Engine::default();
+
+    // Passes the check, but fails during the evaluation (no data)
+    assert!(engine.check("wifi.ssid | UPPER").is_ok());
+    assert!(engine
+        .eval(
+            "wifi.ssid | UPPER",
+            &Default::default(),
+            &Value::Null,
+            &mut Context::default()
+        )
+        .is_err());
+}
+
+#[test]
+fn fail_on_parse_error() {
+    let engine = Engine::default();
+
+    assert!(engine.check("1 +").is_err());
+    assert!(engine.check("POW(1").is_err());
+}
+
+#[test]
+fn fail_on_unknown_function() {
+    let engine = Engine::default();
+
+    assert_check_error(
+        &engine,
+        "1 + FOO()",
+        Error::with_message("function not found").context("function", "FOO"),
+    );
+    assert_check_error(
+        &engine,
+        "POW(FOO(), 2)",
+        Error::with_message("function not found").context("function", "FOO"),
+    );
+    assert_check_error(
+        &engine,
+        "1 | FOO(_)",
+        Error::with_message("function not found").context("function", "FOO"),
+    );
+}
+
+#[test]
+fn fail_on_unknown_filter() {
+    let engine = Engine::default();
+
+    assert_check_error(
+        &engine,
+        "a | UPPER | FOO",
+        Error::with_message("filter not found").context("filter", "FOO"),
+    );
+    assert_check_error(
+        &engine,
+        "MAX(1, a | FOO(1))",
+        Error::with_message("filter not found").context("filter", "FOO"),
+    );
+    // POW is a function, not a filter
+    assert_check_error(
+        &engine,
+        "2 | POW(3)",
+        Error::with_message("filter not found").context("filter", "POW"),
+    );
+}
+
+#[test]
+fn fail_on_invalid_number_of_arguments() {
+    let engine = Engine::default();
+
+    assert_check_error(
+        &engine,
+        "POW(1, 2, 3)",
+        Error::with_message("invalid number of arguments")
+            .context("function", "POW")
+            .context("argument count", "3")
+            .context("expected", "2"),
+    );
+    assert_check_error(
+        &engine,
+        "POW(1)",
+        Error::with_message("missing argument")
+            .context("function", "POW")
+            .context("argument", "exponent"),
+    );
+    assert_check_error(
+        &engine,
+        "MAX()",
+        Error::with_message("missing argument")
+            .context("function", "MAX")
+            .context("argument", "value"),
+    );
+    assert_check_error(
+        &engine,
+        "IF(a, b)",
+        Error::with_message("missing argument")
+            .context("function", "IF")
+            .context("argument", "falsy"),
+    );
+    assert!(engine.check("a | UPPER(1)").is_err());
+    assert!(engine.check("UUIDV4(1)").is_err());
+}
+
+#[test]
+fn fail_on_invalid_named_arguments() {
+    let engine = Engine::default();
+
+    assert_check_error(
+        &engine,
+        "POW(base=1, exponent=2)",
+        Error::with_message("function does not accept named arguments").context("function", "POW"),
+    );
+    assert_check_error(
+        &engine,
+        "a | UPPER(x=1)",
+        Error::with_message("filter does not accept named arguments").context("filter", "UPPER"),
+    );
+    assert_check_error(
+        &engine,
+        "TRY(a, fallback=1)",
+        Error::with_message("special form does not accept named arguments").context("function", "TRY"),
+    );
+}
+
+#[test]
+fn fail_on_invalid_literal_argument_type() {
+    let engine = Engine::default();
+
+    assert_check_error(
+        &engine,
+        "POW(2, `10`)",
+        Error::with_message("invalid argument type")
+            .context("function", "POW")
+            .context("argument value", "\"10\"")
+            .context("expected", "number"),
+    );
+    assert_check_error(
+        &engine,
+        "MAX(1, 2, true)",
+        Error::with_message("invalid argument type")
+            .context("function", "MAX")
+            .context("argument value", "true")
+            .context("expected", "number"),
+    );
+    assert!(engine.check("IF(1, a, b)").is_err());
+
+    // Types of non-literal arguments are unknown
+    assert!(engine.check("POW(a, b | UPPER)").is_ok());
+}
+
+#[test]
+#[cfg(feature = "datetime")]
+fn datetime_signatures() {
+    let engine = Engine::default();
+
+    assert_check_error(
+        &engine,
+        "ts | DATE(`%Y`, `UTC`, 1)",
+        Error::with_message("invalid number of arguments")
+            .context("filter", "DATE")
+            .context("argument count", "3")
+            .context("expected", "2"),
+    );
+    assert_check_error(
+        &engine,
+        "ts | DATE(timezone=`UTC`)",
+        Error::with_message("unknown named argument")
+            .context("filter", "DATE")
+            .context("argument", "timezone")
+            .context("expected", "format, tz"),
+    );
+    assert_check_error(
+        &engine,
+        "ts | DATE(`%Y`, format=`%Y`)",
+        Error::with_message("argument passed by position and by name")
+            .context("filter", "DATE")
+            .context("argument", "format")
+            .context("position", "0"),
+    );
+    assert_check_error(
+        &engine,
+        "ts | DATE(tz=1)",
+        Error::with_message("invalid argument type")
+            .context("filter", "DATE")
+            .context("argument value", "1")
+            .context("expected", "string"),
+    );
+    assert!(engine.check("NOW(1)").is_err());
+    assert!(engine.check("NOW(not a)").is_ok());
+}
+
+#[test]
+fn placeholder_is_an_identifier_outside_of_filter() {
+    let engine = Engine::default();
+
+    assert!(engine.check("POW(_, 2)").is_ok());
+    assert!(engine.check("2 | POW(_, _)").is_ok());
+    // Nested `_` is an identifier
+    assert!(engine.check("2 | POW(_, MAX(_, 1))").is_ok());
+}
+
+#[test]
+#[cfg(all(feature = "datetime", feature = "uuid"))]
+fn fail_on_non_deterministic_call_in_reproducible_mode() {
+    let engine: Engine = EngineBuilder::default().reproducible(true).into();
+
+    assert!(engine.check("POW(2, 3) | DATE").is_ok());
+    assert_check_error(
+        &engine,
+        "1 + UUIDV4()",
+        Error::with_message("non-deterministic call in the reproducible mode")
+            .context("function", "UUIDV4")
+            .context("determinism", "random"),
+    );
+    assert!(engine.check("NOW()").is_err());
+}
+
+#[test]
+fn custom_signatures() {
+    fn repeat(input: &Value, _: &[Value], _: &mut Context) -> Result<Value> {
+        Ok(input.clone())
+    }
+
+    fn anything(_: &[Value], _: &mut Context) -> Result<Value> {
+        Ok(Value::Null)
+    }
+
+    let engine: Engine = EngineBuilder::default()
+        .filter("REPEAT", repeat)
+        .filter_metadata(
+            "REPEAT",
+            Metadata::new(Determinism::Pure)
+                .signature(
+                    Signature::new(ValueType::String)
+                        .input(ValueType::String)
+                        .optional_arg("count", ValueType::Number),
+                )
+                .description("Repeats a string"),
+        )
+        .unwrap()
+        .function("ANYTHING", anything)
+        .into();
+
+    assert!(engine.check("a | REPEAT").is_ok());
+    assert!(engine.check("a | REPEAT(2)").is_ok());
+    assert!(engine.check("a | REPEAT(`2`)").is_err());
+    assert!(engine.check("a | REPEAT(2, 3)").is_err());
+
+    // Functions without a signature are checked for existence only
+    assert!(engine.check("ANYTHING()").is_ok());
+    assert!(engine.check("ANYTHING(1, `a`, true)").is_ok());
+}
+
+#[test]
+fn signature_arity() {
+    let signature = Signature::new(ValueType::Any)
+        .arg("a", ValueType::Number)
+        .optional_arg("b", ValueType::String);
+    assert_eq!(signature.min_args(), 1);
+    assert_eq!(signature.max_args(), Some(2));
+    assert_eq!(signature.arg_type(1), Some(ValueType::String));
+    assert_eq!(signature.arg_type(2), None);
+
+    let signature = signature.variadic(ValueType::Boolean);
+    assert_eq!(signature.max_args(), None);
+    assert_eq!(signature.arg_type(5), Some(ValueType::Boolean));
+}
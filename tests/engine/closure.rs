@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use serde_json::json;
+
+use balena_temen::{
+    ast::{Expression, Identifier},
+    error::*,
+    Context, Engine, EngineBuilder, Value,
+};
+
+fn eval(engine: &Engine, expression: &str) -> Result<Value> {
+    engine.eval(
+        expression,
+        &Identifier::default(),
+        &Value::Null,
+        &mut Context::default(),
+    )
+}
+
+#[test]
+fn function_captures_state() {
+    let mut table = HashMap::new();
+    table.insert("a".to_string(), json!(1));
+    table.insert("b".to_string(), json!(2));
+
+    let engine: Engine = EngineBuilder::default()
+        .function("LOOKUP", move |args: &[Value], _: &mut Context| {
+            let key = args.first().and_then(Value::as_str).unwrap_or_default();
+            Ok(table.get(key).cloned().unwrap_or(Value::Null))
+        })
+        .into();
+
+    assert_eq!(eval(&engine, "LOOKUP(`a`) + LOOKUP(`b`)").unwrap(), json!(3));
+    assert_eq!(eval(&engine, "LOOKUP(`c`)").unwrap(), json!(null));
+}
+
+#[test]
+fn filter_captures_state() {
+    let prefix = "device-".to_string();
+
+    let engine: Engine = EngineBuilder::default()
+        .filter("PREFIX", move |input: &Value, _: &[Value], _: &mut Context| {
+            Ok(json!(format!("{}{}", prefix, input.as_str().unwrap_or_default())))
+        })
+        .filter_with_kwargs(
+            "SUFFIX",
+            |input: &Value, _: &[Value], kwargs: &HashMap<String, Value>, _: &mut Context| {
+                let suffix = kwargs.get("suffix").and_then(Value::as_str).unwrap_or("-1");
+                Ok(json!(format!("{}{}", input.as_str().unwrap_or_default(), suffix)))
+            },
+        )
+        .into();
+
+    assert_eq!(eval(&engine, "`pi` | PREFIX").unwrap(), json!("device-pi"));
+    assert_eq!(
+        eval(&engine, "`pi` | PREFIX | SUFFIX(suffix=`-2`)").unwrap(),
+        json!("device-pi-2")
+    );
+}
+
+#[test]
+fn boxed_closures() {
+    type BoxedFunction = Box<dyn Fn(&[Value], &mut Context) -> Result<Value> + Send + Sync>;
+
+    let functions: Vec<(&str, BoxedFunction)> = vec![
+        ("ONE", Box::new(|_: &[Value], _: &mut Context| Ok(json!(1)))),
+        ("TWO", Box::new(|_: &[Value], _: &mut Context| Ok(json!(2)))),
+    ];
+
+    let engine: Engine = functions
+        .into_iter()
+        .fold(EngineBuilder::default(), |builder, (name, function)| {
+            builder.function(name, function)
+        })
+        .into();
+
+    assert_eq!(eval(&engine, "ONE() + TWO()").unwrap(), json!(3));
+}
+
+#[test]
+fn shared_state() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+
+    let engine: Engine = EngineBuilder::default()
+        .function_with_kwargs(
+            "COUNT",
+            move |_: &[Value], _: &HashMap<String, Value>, _: &mut Context| {
+                Ok(json!(counter.fetch_add(1, Ordering::SeqCst)))
+            },
+        )
+        .into();
+
+    assert_eq!(eval(&engine, "COUNT()").unwrap(), json!(0));
+    assert_eq!(eval(&engine, "COUNT()").unwrap(), json!(1));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn special_form_captures_state() {
+    let default = json!("fallback");
+
+    let engine: Engine = EngineBuilder::default()
+        .special_form(
+            "OR_DEFAULT",
+            move |args: &[Expression], eval: &mut dyn FnMut(&Expression) -> Result<Value>| match args.first() {
+                Some(arg) => eval(arg).or_else(|_| Ok(default.clone())),
+                None => Ok(default.clone()),
+            },
+        )
+        .into();
+
+    assert_eq!(eval(&engine, "OR_DEFAULT(1)").unwrap(), json!(1));
+    assert_eq!(eval(&engine, "OR_DEFAULT(missing)").unwrap(), json!("fallback"));
+}
+
+#[test]
+fn engine_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    let engine = Engine::default();
+    assert_send_sync(&engine);
+
+    let engine = Arc::new(engine);
+    let handle = {
+        let engine = engine.clone();
+        std::thread::spawn(move || eval(&engine, "POW(2, 3)").unwrap())
+    };
+    assert_eq!(handle.join().unwrap(), json!(8));
+}
@@ -0,0 +1,65 @@
+use serde_json::json;
+
+use balena_temen::{ast::Identifier, CompiledExpression, Context, Engine};
+
+#[test]
+fn compiled_expression_keeps_source() {
+    let compiled: CompiledExpression = "1 + 2 * a".parse().unwrap();
+    assert_eq!(compiled.as_str(), "1 + 2 * a");
+    assert_eq!(compiled.expression(), &"1 + 2 * a".parse().unwrap());
+}
+
+#[test]
+fn fail_on_invalid_syntax() {
+    assert!(CompiledExpression::new("1 +").is_err());
+}
+
+#[test]
+fn compiled_expression_with_different_data() {
+    let engine = Engine::default();
+    let mut context = Context::default();
+    let compiled = CompiledExpression::new("super.value * 2").unwrap();
+
+    for i in 0..5 {
+        let data = json!({"value": i, "double": {}});
+        let position = Identifier::default().name("double");
+        assert_eq!(
+            engine.eval_compiled(&compiled, &position, &data, &mut context).unwrap(),
+            json!(i * 2)
+        );
+    }
+}
+
+#[test]
+fn compiled_expression_as_bool() {
+    let engine = Engine::default();
+    let mut context = Context::default();
+    let position = Identifier::default();
+    let compiled = CompiledExpression::new("value > 2").unwrap();
+
+    assert!(engine
+        .eval_compiled_as_bool(&compiled, &position, &json!({"value": 3}), &mut context)
+        .unwrap());
+    assert!(!engine
+        .eval_compiled_as_bool(&compiled, &position, &json!({"value": 1}), &mut context)
+        .unwrap());
+    assert!(engine
+        .eval_compiled_as_bool(&compiled, &position, &json!({"value": "a"}), &mut context)
+        .is_err());
+}
+
+#[test]
+fn compiled_expression_and_eval_results_are_equal() {
+    let engine = Engine::default();
+    let mut context = Context::default();
+    let position = Identifier::default();
+    let data = json!({"ssid": "Balena 5G"});
+
+    for expression in &["ssid | UPPER", "ssid ~ `!`", "POW(2, 10)", "ssid == `Balena 5G`"] {
+        let compiled = CompiledExpression::new(*expression).unwrap();
+        assert_eq!(
+            engine.eval_compiled(&compiled, &position, &data, &mut context).unwrap(),
+            engine.eval(expression, &position, &data, &mut context).unwrap()
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use serde_json::json;
+
+use balena_temen::{ast::Identifier, dependents, dependents_with_engine, Engine, EngineBuilder};
+
+fn find(data: &serde_json::Value, path: &str) -> Vec<String> {
+    dependents(data, &path.parse().unwrap())
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn direct_dependents() {
+    let data = json!({
+        "wifi": {
+            "ssid": "Balena",
+            "id": {
+                "$$formula": "super.ssid | SLUGIFY"
+            }
+        },
+        "name": {
+            "$$formula": "wifi.ssid | UPPER"
+        },
+        "other": {
+            "$$formula": "1 + 2"
+        }
+    });
+
+    assert_eq!(find(&data, "wifi.ssid"), vec!["name", "wifi.id"]);
+    assert!(find(&data, "other").is_empty());
+    assert!(find(&data, "missing").is_empty());
+}
+
+#[test]
+fn transitive_dependents() {
+    let data = json!({
+        "a": {
+            "$$formula": "ssid"
+        },
+        "b": {
+            "$$formula": "a ~ `-b`"
+        },
+        "c": {
+            "$$formula": "b ~ `-c`"
+        },
+        "d": {
+            "$$formula": "`d`"
+        }
+    });
+
+    assert_eq!(find(&data, "ssid"), vec!["a", "b", "c"]);
+    assert_eq!(find(&data, "b"), vec!["c"]);
+}
+
+#[test]
+fn parent_and_nested_values_are_dependencies() {
+    let data = json!({
+        "whole": {
+            "$$formula": "wifi"
+        },
+        "nested": {
+            "$$formula": "wifi.ssid.first"
+        },
+        "sibling": {
+            "$$formula": "wifi.id"
+        }
+    });
+
+    assert_eq!(find(&data, "wifi.ssid"), vec!["nested", "whole"]);
+}
+
+#[test]
+fn indirect_lookups_are_dependencies() {
+    let data = json!({
+        "selected": {
+            "$$formula": "networks[index].ssid"
+        },
+        "last": {
+            "$$formula": "networks[-1].ssid"
+        }
+    });
+
+    assert_eq!(find(&data, "networks[1].ssid"), vec!["last", "selected"]);
+    assert_eq!(find(&data, "index"), vec!["selected"]);
+}
+
+#[test]
+fn array_positions() {
+    let data = json!({
+        "networks": [
+            {
+                "ssid": "a",
+                "id": {
+                    "$$formula": "super.ssid"
+                }
+            },
+            {
+                "ssid": "b",
+                "id": {
+                    "$$formula": "super.ssid"
+                }
+            }
+        ]
+    });
+
+    assert_eq!(find(&data, "networks[1].ssid"), vec!["networks[1].id"]);
+}
+
+#[test]
+fn relative_path_is_canonicalized() {
+    let data = json!({
+        "a": {
+            "$$formula": "ssid"
+        }
+    });
+    let path: Identifier = "wifi.super.ssid".parse().unwrap();
+
+    assert_eq!(dependents(&data, &path).unwrap(), vec![Identifier::default().name("a")]);
+}
+
+#[test]
+fn fail_on_invalid_path() {
+    let path: Identifier = "super.ssid".parse().unwrap();
+    assert!(dependents(&json!({}), &path).is_err());
+}
+
+#[test]
+fn dependents_with_custom_keyword() {
+    let engine: Engine = EngineBuilder::default().eval_keyword("evalMe").into();
+    let data = json!({
+        "a": {
+            "evalMe": "ssid"
+        },
+        "b": {
+            "$$formula": "ssid"
+        }
+    });
+    let path: Identifier = "ssid".parse().unwrap();
+
+    assert_eq!(
+        dependents_with_engine(&data, &path, &engine).unwrap(),
+        vec![Identifier::default().name("a")]
+    );
+}
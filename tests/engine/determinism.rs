@@ -0,0 +1,263 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde_json::json;
+
+use balena_temen::{
+    ast::{Expression, Identifier},
+    error::*,
+    Context, Determinism, Engine, EngineBuilder, Metadata, Value,
+};
+
+fn eval(engine: &Engine, expression: &str, context: &mut Context) -> Result<Value> {
+    engine.eval(expression, &Identifier::default(), &Value::Null, context)
+}
+
+#[test]
+fn memoize_pure_function_calls() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn count(args: &[Value], _: &mut Context) -> Result<Value> {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        Ok(args.first().cloned().unwrap_or(Value::Null))
+    }
+
+    let engine: Engine = EngineBuilder::default()
+        .function("COUNT", count)
+        .function_metadata("count", Metadata::new(Determinism::Pure))
+        .unwrap()
+        .into();
+
+    let mut context = Context::default();
+    assert_eq!(
+        eval(&engine, "COUNT(1) + COUNT(1) + COUNT(2)", &mut context).unwrap(),
+        json!(4)
+    );
+    assert_eq!(eval(&engine, "COUNT(1)", &mut context).unwrap(), json!(1));
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+
+    // Memoized results are not shared between contexts
+    let mut context = Context::default();
+    assert_eq!(eval(&engine, "COUNT(1)", &mut context).unwrap(), json!(1));
+    assert_eq!(CALLS.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn memoize_pure_filter_calls() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn count(input: &Value, _: &[Value], _: &mut Context) -> Result<Value> {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        Ok(input.clone())
+    }
+
+    let engine: Engine = EngineBuilder::default()
+        .filter("COUNT", count)
+        .filter_metadata("COUNT", Metadata::new(Determinism::Pure))
+        .unwrap()
+        .into();
+
+    let mut context = Context::default();
+    assert_eq!(
+        eval(&engine, "MAX(1 | COUNT, 1 | COUNT, 2 | COUNT)", &mut context).unwrap(),
+        json!(2)
+    );
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn memoized_calls_are_not_shared_between_engines() {
+    fn a(_: &[Value], _: &mut Context) -> Result<Value> {
+        Ok(json!("A"))
+    }
+
+    fn b(_: &[Value], _: &mut Context) -> Result<Value> {
+        Ok(json!("B"))
+    }
+
+    let first: Engine = EngineBuilder::default()
+        .function("F", a)
+        .function_metadata("F", Metadata::new(Determinism::Pure))
+        .unwrap()
+        .into();
+    let second: Engine = EngineBuilder::default()
+        .function("F", b)
+        .function_metadata("F", Metadata::new(Determinism::Pure))
+        .unwrap()
+        .into();
+
+    let mut context = Context::default();
+    assert_eq!(eval(&first, "F()", &mut context).unwrap(), json!("A"));
+    assert_eq!(eval(&second, "F()", &mut context).unwrap(), json!("B"));
+    assert_eq!(eval(&first, "F()", &mut context).unwrap(), json!("A"));
+}
+
+#[test]
+fn do_not_memoize_context_dependent_calls() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn count(_: &[Value], _: &mut Context) -> Result<Value> {
+        Ok(json!(CALLS.fetch_add(1, Ordering::SeqCst)))
+    }
+
+    // Custom functions are context dependent by default
+    let engine: Engine = EngineBuilder::default().function("COUNT", count).into();
+
+    let mut context = Context::default();
+    assert_eq!(eval(&engine, "COUNT() + COUNT()", &mut context).unwrap(), json!(1));
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn metadata_are_reset_on_registration() {
+    fn one(_: &[Value], _: &mut Context) -> Result<Value> {
+        Ok(json!(1))
+    }
+
+    let engine: Engine = EngineBuilder::default()
+        .function("ONE", one)
+        .function_metadata("ONE", Metadata::new(Determinism::Pure))
+        .unwrap()
+        .function("ONE", one)
+        .into();
+
+    let mut expression: Expression = "ONE() + 1".parse().unwrap();
+    engine.fold_constants(&mut expression);
+    assert_eq!(expression.to_string(), "ONE() + 1");
+}
+
+#[test]
+fn fail_on_unknown_function_metadata() {
+    let error = EngineBuilder::default()
+        .function_metadata("unknown", Metadata::new(Determinism::Pure))
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::UnknownFunction);
+    assert_eq!(
+        error.to_string(),
+        Error::with_message("function not found")
+            .context("function", "UNKNOWN")
+            .to_string()
+    );
+}
+
+#[test]
+fn fail_on_unknown_filter_metadata() {
+    let error = EngineBuilder::default()
+        .filter_metadata("unknown", Metadata::new(Determinism::Pure))
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::UnknownFilter);
+    assert_eq!(
+        error.to_string(),
+        Error::with_message("filter not found")
+            .context("filter", "UNKNOWN")
+            .to_string()
+    );
+}
+
+#[test]
+fn typed_closures_are_context_dependent() {
+    let calls = AtomicUsize::new(1);
+
+    let engine: Engine = EngineBuilder::default()
+        .typed_function("NEXT", move || Ok(calls.fetch_add(1, Ordering::SeqCst) as i64))
+        .typed_filter("ID", |input: i64| Ok(input))
+        .into();
+    let mut context = Context::default();
+
+    let mut expression: Expression = "NEXT() + 0".parse().unwrap();
+    engine.fold_constants(&mut expression);
+    assert_eq!(expression.to_string(), "NEXT() + 0");
+
+    assert_eq!(eval(&engine, "NEXT() + NEXT()", &mut context).unwrap(), json!(3));
+    assert_eq!(eval(&engine, "NEXT() | ID", &mut context).unwrap(), json!(3));
+}
+
+#[test]
+fn typed_pure_functions_and_filters() {
+    fn double(x: i64) -> Result<i64> {
+        Ok(x * 2)
+    }
+
+    let engine: Engine = EngineBuilder::default()
+        .typed_pure_function("DOUBLE", double)
+        .typed_pure_filter("DOUBLE", double)
+        .typed_function("DICE", || Ok(4i64))
+        .reproducible(true)
+        .into();
+    let mut context = Context::default();
+
+    assert_eq!(eval(&engine, "DOUBLE(1) + 2 | DOUBLE", &mut context).unwrap(), json!(8));
+    assert!(eval(&engine, "DICE()", &mut context).is_err());
+
+    let mut expression: Expression = "DOUBLE(1) + DICE()".parse().unwrap();
+    engine.fold_constants(&mut expression);
+    assert_eq!(expression.to_string(), "2 + DICE()");
+}
+
+#[test]
+fn reproducible_engine_refuses_non_deterministic_calls() {
+    fn one(_: &[Value], _: &mut Context) -> Result<Value> {
+        Ok(json!(1))
+    }
+
+    fn first(args: &[Expression], eval: &mut dyn FnMut(&Expression) -> Result<Value>) -> Result<Value> {
+        eval(&args[0])
+    }
+
+    let engine: Engine = EngineBuilder::default()
+        .function("ONE", one)
+        .function("PURE_ONE", one)
+        .function_metadata("PURE_ONE", Metadata::new(Determinism::Pure))
+        .unwrap()
+        .special_form("FIRST", first)
+        .function_metadata("FIRST", Metadata::new(Determinism::Random))
+        .unwrap()
+        .reproducible(true)
+        .into();
+    let mut context = Context::default();
+
+    assert_eq!(
+        eval(&engine, "IF(PURE_ONE() == 1, `a`, ONE()) | UPPER", &mut context).unwrap(),
+        json!("A")
+    );
+
+    assert!(eval(&engine, "ONE()", &mut context).is_err());
+    assert!(eval(&engine, "FIRST(1)", &mut context).is_err());
+    // TRY does not hide non-deterministic calls
+    assert_eq!(
+        eval(&engine, "TRY(ONE(), `x`)", &mut context).unwrap_err().kind(),
+        ErrorKind::NonDeterministic
+    );
+}
+
+#[test]
+#[cfg(all(feature = "datetime", feature = "uuid"))]
+fn reproducible_engine_refuses_non_deterministic_builtins() {
+    let engine: Engine = EngineBuilder::default().reproducible(true).into();
+    let mut context = Context::default();
+
+    assert!(eval(&engine, "UUIDV4()", &mut context).is_err());
+    assert!(eval(&engine, "NOW(timestamp=true)", &mut context).is_err());
+    assert!(eval(&engine, "true | NOW(_)", &mut context).is_err());
+    assert!(eval(&engine, "TRY(UUIDV4(), `x`)", &mut context).is_err());
+
+    let error = eval(&engine, "UUIDV4()", &mut context).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        Error::with_message("non-deterministic call in the reproducible mode")
+            .context("function", "UUIDV4")
+            .context("determinism", "random")
+            .to_string()
+    );
+}
+
+#[test]
+#[cfg(all(feature = "datetime", feature = "uuid"))]
+fn default_engine_is_not_reproducible() {
+    let engine = Engine::default();
+    let mut context = Context::default();
+
+    assert!(eval(&engine, "UUIDV4()", &mut context).is_ok());
+    assert!(eval(&engine, "NOW()", &mut context).is_ok());
+}
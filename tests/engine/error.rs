@@ -0,0 +1,139 @@
+use serde_json::json;
+
+use balena_temen::{ast::Identifier, error::*, evaluate, rename, Context, Engine, EngineBuilder, Value};
+
+fn eval_kind(engine: &Engine, expression: &str) -> ErrorKind {
+    engine
+        .eval(
+            expression,
+            &Identifier::default(),
+            &json!({"number": 1, "text": "a", "list": [1, 2]}),
+            &mut Context::default(),
+        )
+        .unwrap_err()
+        .kind()
+}
+
+#[test]
+fn expression_error_kinds() {
+    let engine = Engine::default();
+
+    #[allow(unused_mut)]
+    let mut expressions = vec![
+        ("1 +", ErrorKind::Syntax),
+        ("UNKNOWN()", ErrorKind::UnknownFunction),
+        ("1 | UNKNOWN", ErrorKind::UnknownFilter),
+        ("missing", ErrorKind::LookupFailed),
+        ("list[5]", ErrorKind::LookupFailed),
+        ("number + text", ErrorKind::TypeMismatch),
+        ("number | UPPER", ErrorKind::TypeMismatch),
+        ("POW(2, `3`)", ErrorKind::TypeMismatch),
+        ("LOG10(-1)", ErrorKind::Arithmetic),
+        ("POW(1, 2, 3)", ErrorKind::InvalidCall),
+        ("POW(base=1, exponent=2)", ErrorKind::InvalidCall),
+    ];
+    #[cfg(feature = "datetime-tz")]
+    expressions.push(("number | DATE(tz=`Mars/Olympus`)", ErrorKind::InvalidValue));
+    #[cfg(all(feature = "datetime", not(feature = "datetime-tz")))]
+    expressions.push(("number | DATE(tz=`UTC`)", ErrorKind::InvalidCall));
+    #[cfg(feature = "slugify")]
+    expressions.push(("`!!!` | SLUGIFY", ErrorKind::InvalidValue));
+
+    for (expression, kind) in expressions.iter() {
+        assert_eq!(eval_kind(&engine, expression), *kind, "{}", expression);
+    }
+}
+
+#[test]
+fn check_error_kinds() {
+    let engine: Engine = EngineBuilder::default().reproducible(true).into();
+
+    assert_eq!(engine.check("1 +").unwrap_err().kind(), ErrorKind::Syntax);
+    assert_eq!(engine.check("FOO()").unwrap_err().kind(), ErrorKind::UnknownFunction);
+    assert_eq!(engine.check("a | FOO").unwrap_err().kind(), ErrorKind::UnknownFilter);
+    assert_eq!(engine.check("POW(1)").unwrap_err().kind(), ErrorKind::InvalidCall);
+    assert_eq!(
+        engine.check("POW(1, true)").unwrap_err().kind(),
+        ErrorKind::TypeMismatch
+    );
+    #[cfg(feature = "uuid")]
+    assert_eq!(
+        engine.check("UUIDV4()").unwrap_err().kind(),
+        ErrorKind::NonDeterministic
+    );
+}
+
+#[test]
+fn document_error_kinds() {
+    let data = json!({
+        "first": {
+            "$$formula": "second"
+        },
+        "second": {
+            "$$formula": "first"
+        },
+        "third": {
+            "$$formula": "UNKNOWN()"
+        }
+    });
+
+    let error = evaluate(data).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Evaluation);
+
+    let kinds: Vec<ErrorKind> = error.failures().iter().map(|x| x.error.kind()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ErrorKind::CircularDependency,
+            ErrorKind::CircularDependency,
+            ErrorKind::UnknownFunction
+        ]
+    );
+}
+
+#[test]
+fn update_error_kind() {
+    let data = json!({"a": 1});
+
+    let error = rename(data, &"x".parse().unwrap(), &"y".parse().unwrap()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidUpdate);
+}
+
+#[test]
+fn custom_error_kind() {
+    let engine: Engine = EngineBuilder::default()
+        .function("FAIL", |_: &[Value], _: &mut Context| {
+            Err(Error::with_message("custom failure").context("reason", "testing"))
+        })
+        .into();
+
+    let error = engine
+        .eval("FAIL()", &Identifier::default(), &Value::Null, &mut Context::default())
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::Custom);
+    assert_eq!(error.message(), "custom failure");
+    assert_eq!(error.context_value("reason"), Some("testing"));
+}
+
+#[test]
+fn accessors() {
+    let engine = Engine::default();
+    let error = engine
+        .eval(
+            "1 | UNKNOWN",
+            &Identifier::default(),
+            &Value::Null,
+            &mut Context::default(),
+        )
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::UnknownFilter);
+    assert_eq!(error.message(), "filter not found");
+    assert_eq!(error.context_value("filter"), Some("UNKNOWN"));
+    assert_eq!(error.context_value("function"), None);
+
+    let error = Error::new(ErrorKind::LookupFailed, "unable to lookup identifier");
+    assert_eq!(error.kind(), ErrorKind::LookupFailed);
+    assert!(error.to_string().starts_with("temen: unable to lookup identifier"));
+}
@@ -1,16 +1,20 @@
 use serde_json::json;
 
-use balena_temen::{error::*, Context, Engine, EngineBuilder, Value};
+use balena_temen::{ast::Identifier, error::*, Context, Engine, EngineBuilder, Value};
 
 use crate::{test_eval_eq, test_eval_err};
 
 #[test]
 fn default_filters_are_registered() {
     // All filters have unit tests and it's enough to test if they're called / registered / work
-    test_eval_eq!("1541485381 | TIME", json!("06:23:01"));
-    test_eval_eq!("1541485381 | DATE", json!("2018-11-06"));
-    test_eval_eq!("1541485381 | DATETIME", json!("2018-11-06T06:23:01+00:00"));
+    #[cfg(feature = "datetime")]
+    {
+        test_eval_eq!("1541485381 | TIME", json!("06:23:01"));
+        test_eval_eq!("1541485381 | DATE", json!("2018-11-06"));
+        test_eval_eq!("1541485381 | DATETIME", json!("2018-11-06T06:23:01+00:00"));
+    }
     test_eval_eq!("`A` | LOWER", json!("a"));
+    #[cfg(feature = "slugify")]
     test_eval_eq!("`A` | SLUGIFY", json!("a"));
     test_eval_eq!("`A` | TRIM", json!("A"));
     test_eval_eq!("`a` | UPPER", json!("A"));
@@ -42,3 +46,105 @@ fn custom_filter() {
     test_eval_eq!(engine, "`abc` | ATOB", json!("bbc"));
     test_eval_err!(engine, "true | ATOB");
 }
+
+#[test]
+#[cfg(feature = "datetime")]
+fn named_arguments() {
+    test_eval_eq!("1541485381 | DATE(format=`%Y`)", json!("2018"));
+    test_eval_err!("1541485381 | DATE(`%Y`, format=`%Y`)");
+    test_eval_err!("1541485381 | DATE(zone=`Europe/Prague`)");
+}
+
+#[test]
+#[cfg(feature = "datetime-tz")]
+fn time_zones() {
+    test_eval_eq!("1541485381 | TIME(tz=`Europe/Prague`)", json!("07:23:01"));
+    test_eval_eq!(
+        "1541485381 | DATETIME(`%Y-%m-%d %H:%M`, tz=`Europe/Prague`)",
+        json!("2018-11-06 07:23")
+    );
+    test_eval_err!("1541485381 | TIME(tz=`Europe/Atlantis`)");
+}
+
+#[test]
+#[cfg(all(feature = "datetime", not(feature = "datetime-tz")))]
+fn time_zones_require_feature() {
+    test_eval_err!("1541485381 | TIME(tz=`Europe/Prague`)");
+    test_eval_err!("1541485381 | DATETIME(`%Y`, `UTC`)");
+}
+
+#[test]
+fn fail_on_named_arguments_for_positional_filter() {
+    let cf = |input: &Value, _: &[Value], _: &mut Context| Ok(input.clone());
+
+    let engine: Engine = EngineBuilder::default().filter("SAME", cf).into();
+
+    test_eval_eq!(engine, "`a` | SAME(1)", json!("a"));
+    test_eval_err!(engine, "`a` | SAME(value=1)");
+}
+
+#[test]
+fn function_with_placeholder_as_filter() {
+    test_eval_eq!("3 | POW(2, _)", json!(8));
+    test_eval_eq!("3 | POW(_, 2)", json!(9));
+    test_eval_eq!("3 | POW(_, _)", json!(27));
+    test_eval_eq!("3 | MAX(_, 5) | POW(_, 2)", json!(25));
+    test_eval_err!("3 | UNKNOWN(2, _)");
+}
+
+#[test]
+fn filter_of_math_expression_is_applied_once() {
+    let increment = |input: &Value, _: &[Value], _: &mut Context| Ok(json!(input.as_i64().unwrap() + 1));
+
+    let engine: Engine = EngineBuilder::default().filter("INCREMENT", increment).into();
+
+    test_eval_eq!(engine, "1 + 2 | INCREMENT", json!(4));
+    test_eval_eq!(engine, "2 * 3 | INCREMENT | INCREMENT", json!(8));
+}
+
+#[test]
+fn function_with_placeholder_as_filter_of_math_expression() {
+    // Filters are applied to the math expression result once
+    test_eval_eq!("1 + 2 | POW(_, 2)", json!(9));
+    test_eval_eq!("2 * 3 | MAX(_, 1) | POW(_, 2)", json!(36));
+    test_eval_eq!("1 + 2 | POW(_, 2) | MAX(_, 10)", json!(10));
+}
+
+#[test]
+fn underscore_is_a_variable_inside_filter_argument_expression() {
+    let engine = Engine::default();
+    let position = Identifier::default();
+    let data = json!({"_": 2});
+
+    assert_eq!(
+        engine
+            .eval("3 | POW(_ + 1, _)", &position, &data, &mut Context::default())
+            .unwrap(),
+        json!(27)
+    );
+    assert_eq!(
+        engine
+            .eval("3 | IF(_ > 1, _, 0)", &position, &data, &mut Context::default())
+            .unwrap(),
+        json!(3)
+    );
+    test_eval_err!("3 | POW(_ + 1, 2)");
+}
+
+#[test]
+fn special_form_with_placeholder_as_filter() {
+    test_eval_eq!("false | IF(_, `yes`, `no`)", json!("no"));
+    test_eval_eq!("1 | IF(true, _, missing.field)", json!(1));
+}
+
+#[test]
+fn custom_function_with_placeholder_as_filter() {
+    let cf = |args: &[Value], _: &mut Context| {
+        let strings: Vec<&str> = args.iter().filter_map(Value::as_str).collect();
+        Ok(Value::String(strings.join(", ")))
+    };
+
+    let engine: Engine = EngineBuilder::default().function("JOIN", cf).into();
+
+    test_eval_eq!(engine, "`b` | JOIN(`a`, _, `c`)", json!("a, b, c"));
+}
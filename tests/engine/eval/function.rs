@@ -1,13 +1,22 @@
+use std::collections::HashMap;
+
 use serde_json::json;
 
-use balena_temen::{Context, Engine, EngineBuilder, Value};
+use balena_temen::{
+    ast::{Expression, Identifier},
+    error::*,
+    Context, Engine, EngineBuilder, Value,
+};
 
 use crate::{test_eval_eq, test_eval_err, test_eval_ok};
 
 #[test]
 fn default_functions_are_registered() {
     // All functions have unit tests and it's enough to test if they're called / registered / work
+    test_eval_ok!("COALESCE(1)");
+    #[cfg(feature = "uuid")]
     test_eval_ok!("UUIDV4()");
+    #[cfg(feature = "datetime")]
     test_eval_ok!("NOW()");
 }
 
@@ -31,3 +40,94 @@ fn custom_function() {
     test_eval_eq!(engine, "ECHO()", json!("no-value-passed"));
     test_eval_eq!(engine, "ECHO(`Zrzka`)", json!("Zrzka"));
 }
+
+#[test]
+#[cfg(feature = "datetime")]
+fn named_arguments() {
+    test_eval_ok!("NOW(timestamp=true)");
+    test_eval_err!("NOW(timestamp=`yes`)");
+    test_eval_err!("NOW(true, timestamp=true)");
+    test_eval_err!("NOW(foo=true)");
+}
+
+#[test]
+fn custom_function_with_named_arguments() {
+    let cf = |args: &[Value], kwargs: &HashMap<String, Value>, _: &mut Context| {
+        let prefix = kwargs.get("prefix").and_then(Value::as_str).unwrap_or("");
+        let name = args.first().and_then(Value::as_str).unwrap_or("no-value-passed");
+        Ok(Value::String(format!("{}{}", prefix, name)))
+    };
+
+    let engine: Engine = EngineBuilder::default().function_with_kwargs("ECHO", cf).into();
+
+    test_eval_eq!(engine, "ECHO()", json!("no-value-passed"));
+    test_eval_eq!(engine, "ECHO(`Zrzka`, prefix=`Hi `)", json!("Hi Zrzka"));
+}
+
+#[test]
+fn fail_on_named_arguments_for_positional_function() {
+    let cf = |_: &[Value], _: &mut Context| Ok(Value::Null);
+
+    let engine: Engine = EngineBuilder::default().function("NOTHING", cf).into();
+
+    test_eval_ok!(engine, "NOTHING(1)");
+    test_eval_err!(engine, "NOTHING(value=1)");
+}
+
+#[test]
+fn special_form_if() {
+    test_eval_eq!("IF(true, 1, 2)", json!(1));
+    test_eval_eq!("IF(1 > 2, 1, 2)", json!(2));
+    // Branch which is not taken is not evaluated
+    test_eval_eq!("IF(true, 1, missing.field)", json!(1));
+    test_eval_eq!("IF(false, missing.field, 2)", json!(2));
+    test_eval_err!("IF(true, missing.field, 2)");
+    test_eval_err!("IF(1, 1, 2)");
+    test_eval_err!("IF(true, 1)");
+    test_eval_err!("IF(true, 1, 2, 3)");
+    test_eval_err!("IF(condition=true, 1, 2)");
+}
+
+#[test]
+fn special_form_coalesce() {
+    let engine = Engine::default();
+    let mut context = Context::default();
+    let position = Identifier::default();
+    let data = json!({"none": null, "name": "Zrzka"});
+
+    let mut eval = |expression| engine.eval(expression, &position, &data, &mut context);
+
+    assert_eq!(eval("COALESCE(none, name, `Balena`)").unwrap(), json!("Zrzka"));
+    assert_eq!(eval("COALESCE(none, none)").unwrap(), Value::Null);
+    // Remaining arguments are not evaluated
+    assert_eq!(eval("COALESCE(name, missing.field)").unwrap(), json!("Zrzka"));
+    assert!(eval("COALESCE(none, missing.field)").is_err());
+    assert!(eval("COALESCE()").is_err());
+}
+
+#[test]
+fn special_form_try() {
+    test_eval_eq!("TRY(missing.field, `fallback`)", json!("fallback"));
+    test_eval_eq!("TRY(1 + 1, missing.field)", json!(2));
+    test_eval_eq!("TRY(`a` + 1, 3)", json!(3));
+    test_eval_eq!("TRY(1 | UPPER, 3)", json!(3));
+    test_eval_err!("TRY(missing.field, missing.field)");
+    test_eval_err!("TRY(1)");
+    // Mistakes in the expression are not caught
+    test_eval_err!("TRY(UNKNOWN(), 3)");
+    test_eval_err!("TRY(1 | UNKNOWN, 3)");
+}
+
+#[test]
+fn custom_special_form() {
+    fn first_ok(args: &[Expression], eval: &mut dyn FnMut(&Expression) -> Result<Value>) -> Result<Value> {
+        args.iter()
+            .find_map(|arg| eval(arg).ok())
+            .ok_or_else(|| Error::with_message("all arguments failed"))
+    }
+
+    let engine: Engine = EngineBuilder::default().special_form("FIRST_OK", first_ok).into();
+
+    test_eval_eq!(engine, "FIRST_OK(missing, 1 / 0, `ok`, missing)", json!("ok"));
+    test_eval_err!(engine, "FIRST_OK(missing)");
+}
@@ -0,0 +1,248 @@
+use serde_json::json;
+
+use balena_temen::{ast::Identifier, Context, Engine, Evaluator};
+
+fn position(position: &str) -> Identifier {
+    position.parse().unwrap()
+}
+
+#[test]
+fn initial_evaluation() {
+    let engine = Engine::default();
+    let mut context = Context::default();
+
+    let evaluator = Evaluator::new(
+        json!({
+            "a": 1,
+            "b": {
+                "$$formula": "super.a + 1"
+            },
+            "c": {
+                "$$formula": "super.missing"
+            }
+        }),
+        &engine,
+        &mut context,
+    )
+    .unwrap();
+
+    assert_eq!(
+        evaluator.result(),
+        &json!({
+            "a": 1,
+            "b": 2,
+            "c": {
+                "$$formula": "super.missing"
+            }
+        })
+    );
+    assert_eq!(evaluator.failures().len(), 1);
+    assert_eq!(evaluator.failures()[0].position, position("c"));
+}
+
+#[test]
+#[cfg(all(feature = "slugify", feature = "uuid"))]
+fn only_affected_formulas_are_evaluated() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use balena_temen::{EngineBuilder, Value};
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    // Counts how many times it was called
+    let cf = |_: &[Value], _: &mut Context| Ok(json!(CALLS.fetch_add(1, Ordering::SeqCst) + 1));
+
+    let engine: Engine = EngineBuilder::default().function("COUNT", cf).into();
+    let mut context = Context::default();
+
+    let mut evaluator = Evaluator::new(
+        json!({
+            "wifi": {
+                "ssid": "Balena",
+                "id": {
+                    "$$formula": "super.ssid | SLUGIFY"
+                },
+                "upper": {
+                    "$$formula": "super.id | UPPER"
+                }
+            },
+            "uuid": {
+                "$$formula": "UUIDV4()"
+            },
+            "network": {
+                "$$formula": "super.wifi.ssid ~ `-` ~ super.port"
+            },
+            "count": {
+                "$$formula": "COUNT()"
+            },
+            "port": 80
+        }),
+        &engine,
+        &mut context,
+    )
+    .unwrap();
+
+    let uuid = evaluator.result()["uuid"].clone();
+    assert_eq!(evaluator.result()["network"], json!("Balena-80"));
+
+    evaluator
+        .update(vec![(position("wifi.ssid"), json!("Zrzka 5G"))], &mut context)
+        .unwrap();
+
+    assert_eq!(
+        evaluator.result(),
+        &json!({
+            "wifi": {
+                "ssid": "Zrzka 5G",
+                "id": "zrzka-5g",
+                "upper": "ZRZKA-5G"
+            },
+            "uuid": uuid,
+            "network": "Zrzka 5G-80",
+            "count": 1,
+            "port": 80
+        })
+    );
+
+    evaluator
+        .update(vec![(position("port"), json!(8080))], &mut context)
+        .unwrap();
+    assert_eq!(evaluator.result()["network"], json!("Zrzka 5G-8080"));
+    assert_eq!(evaluator.result()["uuid"], uuid);
+    assert_eq!(evaluator.template()["port"], json!(8080));
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn failures_are_updated() {
+    let engine = Engine::default();
+    let mut context = Context::default();
+
+    let mut evaluator = Evaluator::new(
+        json!({
+            "device": {},
+            "hostname": {
+                "$$formula": "`balena-` ~ super.device.uuid"
+            }
+        }),
+        &engine,
+        &mut context,
+    )
+    .unwrap();
+    assert_eq!(evaluator.failures().len(), 1);
+
+    evaluator
+        .update(vec![(position("device.uuid"), json!("abcd"))], &mut context)
+        .unwrap();
+    assert!(evaluator.failures().is_empty());
+    assert_eq!(evaluator.result()["hostname"], json!("balena-abcd"));
+
+    evaluator
+        .update(vec![(position("device"), json!({}))], &mut context)
+        .unwrap();
+    assert_eq!(evaluator.failures().len(), 1);
+    assert_eq!(
+        evaluator.result()["hostname"],
+        json!({"$$formula": "`balena-` ~ super.device.uuid"})
+    );
+}
+
+#[test]
+fn formula_update_evaluates_everything() {
+    let engine = Engine::default();
+    let mut context = Context::default();
+
+    let mut evaluator = Evaluator::new(
+        json!({
+            "a": {
+                "$$formula": "1 + 1"
+            },
+            "b": {
+                "$$formula": "super.a * 2"
+            }
+        }),
+        &engine,
+        &mut context,
+    )
+    .unwrap();
+    assert_eq!(evaluator.result(), &json!({"a": 2, "b": 4}));
+
+    evaluator
+        .update(vec![(position("a"), json!({"$$formula": "2 + 3"}))], &mut context)
+        .unwrap();
+    assert_eq!(evaluator.result(), &json!({"a": 5, "b": 10}));
+
+    evaluator.update(vec![(position("a"), json!(1))], &mut context).unwrap();
+    assert_eq!(evaluator.result(), &json!({"a": 1, "b": 2}));
+
+    evaluator
+        .update(vec![(position("c"), json!({"$$formula": "super.b + 1"}))], &mut context)
+        .unwrap();
+    assert_eq!(evaluator.result(), &json!({"a": 1, "b": 2, "c": 3}));
+}
+
+#[test]
+fn fail_on_invalid_update_position() {
+    let engine = Engine::default();
+    let mut context = Context::default();
+    let mut evaluator = Evaluator::new(json!({"a": [1, 2]}), &engine, &mut context).unwrap();
+
+    assert!(evaluator
+        .update(vec![(position("a[-1]"), json!(3))], &mut context)
+        .is_err());
+    assert!(evaluator
+        .update(vec![(position("a[b]"), json!(3))], &mut context)
+        .is_err());
+    assert!(evaluator
+        .update(vec![(position("a[5]"), json!(3))], &mut context)
+        .is_err());
+    assert!(evaluator
+        .update(vec![(position("b.c"), json!(3))], &mut context)
+        .is_err());
+
+    evaluator
+        .update(vec![(position("a[1]"), json!(3))], &mut context)
+        .unwrap();
+    assert_eq!(evaluator.result(), &json!({"a": [1, 3]}));
+}
+
+#[test]
+fn failed_update_leaves_evaluator_untouched() {
+    let engine = Engine::default();
+    let mut context = Context::default();
+    let data = json!({
+        "ssid": "A",
+        "id": {
+            "$$formula": "super.ssid | LOWER"
+        }
+    });
+    let mut evaluator = Evaluator::new(data.clone(), &engine, &mut context).unwrap();
+
+    // The first change is valid, the second one fails
+    assert!(evaluator
+        .update(
+            vec![(position("ssid"), json!("B")), (position("x.y"), json!(1))],
+            &mut context
+        )
+        .is_err());
+    assert_eq!(evaluator.template(), &data);
+    assert_eq!(evaluator.result(), &json!({"ssid": "A", "id": "a"}));
+
+    // Same with a change which evaluates everything again
+    assert!(evaluator
+        .update(
+            vec![
+                (position("ssid"), json!({"$$formula": "`C`"})),
+                (position("x.y"), json!(1))
+            ],
+            &mut context
+        )
+        .is_err());
+    assert_eq!(evaluator.template(), &data);
+    assert_eq!(evaluator.result(), &json!({"ssid": "A", "id": "a"}));
+
+    evaluator
+        .update(vec![(position("ssid"), json!("B"))], &mut context)
+        .unwrap();
+    assert_eq!(evaluator.result(), &json!({"ssid": "B", "id": "b"}));
+}
@@ -0,0 +1,110 @@
+use serde_json::json;
+
+use balena_temen::{
+    ast::{Expression, Identifier},
+    error::*,
+    Context, Engine, EngineBuilder, Value,
+};
+
+fn fold(engine: &Engine, expression: &str) -> String {
+    let mut expression: Expression = expression.parse().unwrap();
+    engine.fold_constants(&mut expression);
+    expression.to_string()
+}
+
+fn assert_fold(expression: &str, folded: &str) {
+    assert_eq!(fold(&Engine::default(), expression), folded);
+}
+
+#[test]
+fn fold_literals() {
+    assert_fold("1 + 2 * 3", "7");
+    assert_fold("POW(2, 10) * 3", "3072");
+    assert_fold("5 / 2", "2.5");
+    assert_fold("`a` ~ `b` ~ 1", "`ab1`");
+    assert_fold("`Balena` | LOWER", "`balena`");
+    assert_fold("not 1 > 2", "true");
+    assert_fold("1 < 2 and 2 < 3", "true");
+    assert_fold("1 + 2 | POW(_, 2)", "9");
+    assert_fold("POW(10, 20)", "100000000000000000000.0");
+}
+
+#[test]
+fn fold_sub_expressions() {
+    assert_fold("a + 2 * 3", "a + 6");
+    assert_fold("a | POW(2 + 1, _)", "a | POW(3, _)");
+    assert_fold("MAX(a, MIN(1, 2), LOG10(100))", "MAX(a, 1, 2.0)");
+    assert_fold("(a ? 1 + 1 : POW(2, 2))", "(a ? 2 : 4)");
+}
+
+#[test]
+fn fold_special_forms() {
+    assert_fold("IF(1 > 2, `a`, `b`)", "`b`");
+    assert_fold("TRY(1 / 0, 2)", "2");
+    assert_fold("IF(a, 1 + 1, 2)", "IF(a, 2, 2)");
+}
+
+#[test]
+fn do_not_fold_impure_functions() {
+    assert_fold("UUIDV4()", "UUIDV4()");
+    assert_fold("UUIDV4() | UPPER", "UUIDV4() | UPPER");
+    assert_fold("NOW(timestamp=true) + 1 * 2", "NOW(timestamp=true) + 2");
+    assert_fold("1 | DATE(format=NOW())", "1 | DATE(format=NOW())");
+}
+
+#[test]
+fn do_not_fold_custom_functions() {
+    fn one(_: &[Value], _: &mut Context) -> Result<Value> {
+        Ok(json!(1))
+    }
+
+    let engine: Engine = EngineBuilder::default().function("ONE", one).into();
+    assert_eq!(fold(&engine, "ONE() + 1"), "ONE() + 1");
+
+    // Overwritten builtin is not pure anymore
+    let engine: Engine = EngineBuilder::default().function("POW", one).into();
+    assert_eq!(fold(&engine, "POW(2, 2) + 1"), "POW(2, 2) + 1");
+}
+
+#[test]
+fn do_not_fold_failures_and_non_literals() {
+    assert_fold("1 / 0", "1 / 0");
+    assert_fold("UNKNOWN(1) + 1", "UNKNOWN(1) + 1");
+    assert_fold("`a` | UNKNOWN", "`a` | UNKNOWN");
+    assert_fold("1 + `a`", "1 + `a`");
+    assert_fold("COALESCE(1 / 0)", "COALESCE(1 / 0)");
+    // String literal can't contain all quote characters
+    assert_fold("`a` ~ `\"'` ~ \"`\"", "`a` ~ `\"'` ~ \"`\"");
+    assert_fold("`a` ~ `\"'`", "`a\"'`");
+}
+
+#[test]
+fn folded_expression_evaluates_to_the_same_value() {
+    let engine = Engine::default();
+    let position = Identifier::default();
+    let data = json!({"a": 2, "b": "Balena"});
+
+    let expressions = [
+        "a * (2 + 3) - POW(2, 3) | POW(_, 2)",
+        "b ~ `-` ~ 1 | SLUGIFY",
+        "(a > 1 ? POW(a, 2) : 1 + 1) == 4",
+        "IF(a == 2, 1 + 2 | MAX(_, 5), 0)",
+        "1 + 2 | POW(_, 2) + a",
+        "TRY(1 / 0 + a, 10 % 3) * 2",
+        "not a == 1 + 1 or 1 > 2",
+        "1 / 0 + a",
+    ];
+
+    for expression in expressions.iter() {
+        let compiled = engine.compile(*expression).unwrap();
+        assert_eq!(compiled.as_str(), *expression);
+
+        let expected = engine.eval(expression, &position, &data, &mut Context::default());
+        let folded = engine.eval_compiled(&compiled, &position, &data, &mut Context::default());
+        match (expected, folded) {
+            (Ok(expected), Ok(folded)) => assert_eq!(expected, folded, "{}", expression),
+            (Err(_), Err(_)) => {}
+            (expected, folded) => panic!("{}: {:?} != {:?}", expression, expected, folded),
+        };
+    }
+}
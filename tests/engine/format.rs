@@ -0,0 +1,127 @@
+use serde_json::json;
+
+use balena_temen::{format_document, format_document_with_engine, format_expression, Engine, EngineBuilder};
+
+#[test]
+fn whitespace_is_normalized() {
+    assert_eq!(format_expression("1+2*  3").unwrap(), "1 + 2 * 3");
+    assert_eq!(format_expression(" a==b   and not c ").unwrap(), "a == b and not c");
+    assert_eq!(format_expression("POW( 2 ,x=3 )").unwrap(), "POW(2, x=3)");
+}
+
+#[test]
+fn quotes_are_normalized() {
+    assert_eq!(format_expression("'a' ~ \"b\"").unwrap(), "`a` ~ `b`");
+    assert_eq!(format_expression("'a`b'").unwrap(), "\"a`b\"");
+    assert_eq!(
+        format_expression("wifi['ssid'][\"first network\"]").unwrap(),
+        "wifi.ssid[`first network`]"
+    );
+}
+
+#[test]
+fn redundant_parentheses_are_removed() {
+    assert_eq!(format_expression("(1 * 2) + (3)").unwrap(), "1 * 2 + 3");
+    assert_eq!(format_expression("(1 + 2) * 3").unwrap(), "(1 + 2) * 3");
+}
+
+#[test]
+fn filter_names_are_uppercased() {
+    assert_eq!(format_expression("a | Slugify | Upper").unwrap(), "a | SLUGIFY | UPPER");
+    assert_eq!(
+        format_expression("IF(a | Lower, (b | Upper ? c : 1), x=d | Trim)").unwrap(),
+        "IF(a | LOWER, (b | UPPER ? c : 1), x=d | TRIM)"
+    );
+    assert_eq!(format_expression("3 | Pow(2, _)").unwrap(), "3 | POW(2, _)");
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let formatted = format_expression("( a+1 )*2|Filter( 'x' ) ==3 or b").unwrap();
+    assert_eq!(format_expression(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn fail_on_invalid_expression() {
+    assert!(format_expression("1 +").is_err());
+}
+
+#[test]
+fn format_all_formulas() {
+    let data = json!({
+        "number": 1,
+        "wifi": {
+            "id": {
+                "$$formula": "super.ssid|Slugify"
+            }
+        },
+        "list": [
+            "a",
+            {
+                "$$formula": "1+1"
+            }
+        ]
+    });
+
+    assert_eq!(
+        format_document(data).unwrap(),
+        json!({
+            "number": 1,
+            "wifi": {
+                "id": {
+                    "$$formula": "super.ssid | SLUGIFY"
+                }
+            },
+            "list": [
+                "a",
+                {
+                    "$$formula": "1 + 1"
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn report_all_invalid_formulas() {
+    let data = json!({
+        "a": {
+            "$$formula": "1 +"
+        },
+        "b": {
+            "$$formula": "1+1"
+        },
+        "c": {
+            "$$formula": "(("
+        }
+    });
+
+    let error = format_document(data).unwrap_err();
+    let failures: Vec<String> = error.failures().iter().map(|x| x.position.to_string()).collect();
+    assert_eq!(failures, vec!["a", "c"]);
+}
+
+#[test]
+fn format_with_custom_keyword() {
+    let engine: Engine = EngineBuilder::default().eval_keyword("evalMe").into();
+    let data = json!({
+        "a": {
+            "evalMe": "1+1"
+        },
+        "b": {
+            "$$formula": "1+1"
+        }
+    });
+
+    assert_eq!(
+        format_document_with_engine(data, &engine).unwrap(),
+        json!({
+            "a": {
+                "evalMe": "1 + 1"
+            },
+            "b": {
+                "$$formula": "1+1"
+            }
+        })
+    );
+}
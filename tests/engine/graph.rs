@@ -0,0 +1,140 @@
+use serde_json::json;
+
+use balena_temen::{ast::Identifier, DependencyGraph, Engine, EngineBuilder};
+
+#[test]
+fn nodes_are_in_document_order() {
+    let data = json!({
+        "b": {
+            "$$formula": "a"
+        },
+        "a": {
+            "$$formula": "1"
+        },
+        "networks": [
+            {
+                "id": {
+                    "$$formula": "super.ssid"
+                }
+            }
+        ]
+    });
+
+    let graph = DependencyGraph::new(&data, &Engine::default()).unwrap();
+    let positions: Vec<String> = graph.nodes().iter().map(|x| x.position.to_string()).collect();
+
+    assert_eq!(positions, vec!["a", "b", "networks[0].id"]);
+    assert_eq!(graph.nodes()[1].dependencies, vec![0]);
+}
+
+#[test]
+fn cycles_are_exported() {
+    let data = json!({
+        "a": {
+            "$$formula": "b"
+        },
+        "b": {
+            "$$formula": "a"
+        }
+    });
+
+    let graph = DependencyGraph::new(&data, &Engine::default()).unwrap();
+
+    assert_eq!(
+        graph.to_json(),
+        json!({
+            "a": {
+                "expression": "b",
+                "dependencies": ["b"],
+                "possibleDependencies": []
+            },
+            "b": {
+                "expression": "a",
+                "dependencies": ["a"],
+                "possibleDependencies": []
+            }
+        })
+    );
+    assert!(graph.to_dot().contains("n0 -> n1;\n    n1 -> n0;\n"));
+}
+
+#[test]
+fn possible_dependencies() {
+    let data = json!({
+        "selected": {
+            "$$formula": "networks[index]"
+        },
+        "networks": [
+            {
+                "$$formula": "1"
+            },
+            {
+                "$$formula": "2"
+            }
+        ]
+    });
+
+    let graph = DependencyGraph::new(&data, &Engine::default()).unwrap();
+    let node = &graph.nodes()[2];
+
+    assert_eq!(node.position, Identifier::default().name("selected"));
+    assert!(node.dependencies.is_empty());
+    assert_eq!(node.possible_dependencies, vec![0, 1]);
+    assert_eq!(
+        graph.to_json()["selected"]["possibleDependencies"],
+        json!(["networks[0]", "networks[1]"])
+    );
+}
+
+#[test]
+fn invalid_formulas_have_no_dependencies() {
+    let data = json!({
+        "a": {
+            "$$formula": "b +"
+        },
+        "b": {
+            "$$formula": "super.super.a"
+        }
+    });
+
+    let graph = DependencyGraph::new(&data, &Engine::default()).unwrap();
+
+    assert_eq!(graph.nodes().len(), 2);
+    assert!(graph.nodes().iter().all(|x| x.dependencies.is_empty()));
+    assert_eq!(graph.nodes()[0].expression, "b +");
+}
+
+#[test]
+fn dot_labels_are_escaped() {
+    let data = json!({
+        "wifi": {
+            "first network": {
+                "$$formula": "\"a\\b\""
+            }
+        }
+    });
+
+    let graph = DependencyGraph::new(&data, &Engine::default()).unwrap();
+
+    assert!(graph
+        .to_dot()
+        .contains(r#"n0 [label="wifi[`first network`]\n\"a\\b\""];"#));
+}
+
+#[test]
+fn graph_with_custom_keyword() {
+    let engine: Engine = EngineBuilder::default().eval_keyword("evalMe").into();
+    let data = json!({
+        "a": {
+            "evalMe": "1"
+        },
+        "b": {
+            "$$formula": "a"
+        }
+    });
+
+    let graph = DependencyGraph::new(&data, &engine).unwrap();
+
+    assert_eq!(graph.nodes().len(), 1);
+    assert_eq!(graph.nodes()[0].position, Identifier::default().name("a"));
+}
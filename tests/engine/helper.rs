@@ -1,6 +1,8 @@
 use serde_json::json;
 
-use balena_temen::{evaluate, evaluate_with_engine, Context, Engine, EngineBuilder};
+use balena_temen::{
+    evaluate, evaluate_partial, evaluate_partial_with_engine, evaluate_with_engine, Context, Engine, EngineBuilder,
+};
 
 #[test]
 fn primitive_types_pass_through() {
@@ -85,3 +87,221 @@ fn custom_keyword() {
         json!(3)
     );
 }
+
+#[test]
+fn fail_on_invalid_syntax() {
+    let data = json!({
+        "valid": {
+            "$$formula": "1 + 2"
+        },
+        "invalid": {
+            "$$formula": "1 +"
+        }
+    });
+
+    assert!(evaluate(data).is_err());
+}
+
+#[test]
+fn dependencies_are_evaluated_first() {
+    let data = json!({
+        "a": {
+            "$$formula": "b ~ `-` ~ c"
+        },
+        "b": {
+            "$$formula": "c | UPPER"
+        },
+        "c": "zrzka"
+    });
+
+    assert_eq!(
+        evaluate(data).unwrap(),
+        json!({"a": "ZRZKA-zrzka", "b": "ZRZKA", "c": "zrzka"})
+    );
+}
+
+#[test]
+fn indirect_dependencies() {
+    let data = json!({
+        "names": [
+            {
+                "$$formula": "`Zrzka` | UPPER"
+            },
+            {
+                "$$formula": "names[0] | LOWER"
+            }
+        ],
+        "index": {
+            "$$formula": "1 + 0"
+        },
+        "selected": {
+            "$$formula": "names[index]"
+        },
+        "last": {
+            "$$formula": "names[-1]"
+        }
+    });
+
+    let evaluated = json!({
+        "names": ["ZRZKA", "zrzka"],
+        "index": 1,
+        "selected": "zrzka",
+        "last": "zrzka"
+    });
+
+    assert_eq!(evaluate(data).unwrap(), evaluated);
+}
+
+#[test]
+fn circular_dependency_path() {
+    let data = json!({
+        "a": {
+            "id": {
+                "$$formula": "super.super.b.name"
+            }
+        },
+        "b": {
+            "name": {
+                "$$formula": "super.super.a.id"
+            }
+        }
+    });
+
+    let error = evaluate(data).unwrap_err().to_string();
+    assert!(error.contains("circular dependency"));
+    assert!(error.contains("a.id -> b.name -> a.id"));
+}
+
+#[test]
+fn self_dependency() {
+    let data = json!({
+        "a": {
+            "$$formula": "this + 1"
+        }
+    });
+
+    let error = evaluate(data).unwrap_err().to_string();
+    assert!(error.contains("a -> a"));
+}
+
+#[test]
+fn error_contains_failing_formula() {
+    let data = json!({
+        "wifi": {
+            "ssid": {
+                "$$formula": "super.missing | UPPER"
+            }
+        }
+    });
+
+    let error = evaluate(data).unwrap_err().to_string();
+    assert!(error.contains("wifi.ssid"));
+    assert!(error.contains("super.missing | UPPER"));
+}
+
+#[test]
+fn all_failures_are_reported() {
+    let data = json!({
+        "a": {
+            "$$formula": "missing"
+        },
+        "b": {
+            "$$formula": "1 +"
+        },
+        "c": {
+            "$$formula": "super.a | UPPER"
+        },
+        "d": {
+            "$$formula": "super.e"
+        },
+        "e": {
+            "$$formula": "super.d"
+        },
+        "valid": {
+            "$$formula": "1 + 2"
+        }
+    });
+
+    let error = evaluate(data).unwrap_err();
+    let failures: Vec<(String, &str)> = error
+        .failures()
+        .iter()
+        .map(|x| (x.position.to_string(), x.expression.as_str()))
+        .collect();
+
+    assert_eq!(
+        failures,
+        vec![
+            ("a".to_string(), "missing"),
+            ("b".to_string(), "1 +"),
+            ("c".to_string(), "super.a | UPPER"),
+            ("d".to_string(), "super.e"),
+            ("e".to_string(), "super.d"),
+        ]
+    );
+
+    let message = error.to_string();
+    assert!(message.contains("unable to evaluate dependency"));
+    assert!(message.contains("d -> e -> d"));
+}
+
+#[test]
+fn partial_evaluation() {
+    let data = json!({
+        "a": {
+            "$$formula": "super.missing"
+        },
+        "b": {
+            "$$formula": "super.a ~ `!`"
+        },
+        "c": {
+            "$$formula": "1 + 2"
+        }
+    });
+
+    let (evaluated, failures) = evaluate_partial(data).unwrap();
+
+    assert_eq!(
+        evaluated,
+        json!({
+            "a": {
+                "$$formula": "super.missing"
+            },
+            "b": {
+                "$$formula": "super.a ~ `!`"
+            },
+            "c": 3
+        })
+    );
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[0].position.to_string(), "a");
+    assert_eq!(failures[1].position.to_string(), "b");
+}
+
+#[test]
+fn partial_evaluation_without_failures() {
+    let data = json!({
+        "c": {
+            "$$formula": "1 + 2"
+        }
+    });
+
+    let (evaluated, failures) = evaluate_partial(data).unwrap();
+    assert_eq!(evaluated, json!({"c": 3}));
+    assert!(failures.is_empty());
+}
+
+#[test]
+fn partial_evaluation_with_engine() {
+    let engine: Engine = EngineBuilder::default().eval_keyword("evalMePlease").into();
+    let mut context = Context::default();
+
+    let (evaluated, failures) = evaluate_partial_with_engine(
+        json!({"a": {"evalMePlease": "1 + 2"}, "b": {"evalMePlease": "missing"}}),
+        &engine,
+        &mut context,
+    )
+    .unwrap();
+    assert_eq!(evaluated, json!({"a": 3, "b": {"evalMePlease": "missing"}}));
+    assert_eq!(failures.len(), 1);
+}
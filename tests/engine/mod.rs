@@ -1,4 +1,18 @@
+mod builtins;
+mod check;
+mod closure;
+mod compiled;
+mod dependents;
+mod determinism;
+mod error;
 mod eval;
 mod eval_as_bool;
+mod evaluator;
+mod fold;
+mod format;
+mod graph;
 mod helper;
+mod registry;
 mod regressions;
+mod rename;
+mod typed;
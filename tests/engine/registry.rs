@@ -0,0 +1,100 @@
+use balena_temen::{error::*, Context, Determinism, Engine, EngineBuilder, Metadata, Value};
+
+fn one(_: &[Value], _: &mut Context) -> Result<Value> {
+    Ok(Value::from(1))
+}
+
+#[test]
+#[cfg(all(feature = "datetime", feature = "uuid"))]
+fn list_default_functions() {
+    let engine = Engine::default();
+    let names: Vec<&str> = engine.functions().iter().map(|(name, _)| *name).collect();
+
+    assert_eq!(
+        names,
+        vec!["COALESCE", "IF", "LOG10", "MAX", "MIN", "NOW", "POW", "TRY", "UUIDV4"]
+    );
+}
+
+#[test]
+#[cfg(all(feature = "datetime", feature = "slugify"))]
+fn list_default_filters() {
+    let engine = Engine::default();
+    let names: Vec<&str> = engine.filters().iter().map(|(name, _)| *name).collect();
+
+    assert_eq!(
+        names,
+        vec!["DATE", "DATETIME", "LOWER", "SLUGIFY", "TIME", "TRIM", "UPPER"]
+    );
+}
+
+#[test]
+fn all_builtins_are_documented() {
+    let engine = Engine::default();
+
+    for (name, metadata) in engine.functions().into_iter().chain(engine.filters()) {
+        assert!(metadata.description.is_some(), "{}", name);
+        assert!(metadata.signature.is_some(), "{}", name);
+    }
+}
+
+#[test]
+fn list_custom_functions() {
+    let engine: Engine = EngineBuilder::default()
+        .function("one", one)
+        .function("ONE_PURE", one)
+        .function_metadata("ONE_PURE", Metadata::new(Determinism::Pure).description("Returns one"))
+        .unwrap()
+        .into();
+
+    let functions = engine.functions();
+    assert!(functions.contains(&("ONE", &Metadata::default())));
+    assert!(functions.contains(&("ONE_PURE", &Metadata::new(Determinism::Pure).description("Returns one"))));
+}
+
+#[test]
+fn lookup_metadata() {
+    let engine = Engine::default();
+
+    #[cfg(feature = "uuid")]
+    assert_eq!(
+        engine.function_metadata("uuidv4").unwrap().determinism,
+        Determinism::Random
+    );
+    assert_eq!(
+        engine.filter_metadata("TRIM").unwrap().description.as_ref().unwrap(),
+        "Removes leading and trailing whitespace"
+    );
+    assert!(engine.function_metadata("TRIM").is_none());
+    assert!(engine.filter_metadata("POW").is_none());
+    assert!(engine.filter_metadata("FOO").is_none());
+}
+
+#[cfg(all(feature = "serde", feature = "datetime"))]
+#[test]
+fn serialize_metadata() {
+    use serde_json::json;
+
+    let engine = Engine::default();
+
+    assert_eq!(
+        serde_json::to_value(engine.filter_metadata("DATE").unwrap()).unwrap(),
+        json!({
+            "determinism": "pure",
+            "signature": {
+                "input": "number",
+                "args": [
+                    { "name": "format", "valueType": "string", "required": false },
+                    { "name": "tz", "valueType": "string", "required": false }
+                ],
+                "variadic": null,
+                "returnType": "string"
+            },
+            "description": "Formats a timestamp as a date"
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(engine.function_metadata("NOW").unwrap().determinism).unwrap(),
+        json!("context-dependent")
+    );
+}
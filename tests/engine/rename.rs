@@ -0,0 +1,264 @@
+use serde_json::{json, Value};
+
+use balena_temen::{ast::Identifier, evaluate, rename, rename_with_engine, Engine, EngineBuilder};
+
+fn rename_path(data: Value, from: &str, to: &str) -> Value {
+    rename(data, &from.parse().unwrap(), &to.parse().unwrap()).unwrap()
+}
+
+#[test]
+fn rename_field() {
+    let data = json!({
+        "ssid": "Balena",
+        "id": {
+            "$$formula": "ssid | SLUGIFY"
+        }
+    });
+
+    assert_eq!(
+        rename_path(data, "ssid", "name"),
+        json!({
+            "name": "Balena",
+            "id": {
+                "$$formula": "name | SLUGIFY"
+            }
+        })
+    );
+}
+
+#[test]
+fn rewrite_relative_identifiers() {
+    let data = json!({
+        "wifi": {
+            "ssid": "Balena",
+            "id": {
+                "$$formula": "super.ssid ~ this.super.ssid"
+            }
+        },
+        "upper": {
+            "$$formula": "this.super.wifi.ssid | UPPER"
+        }
+    });
+
+    assert_eq!(
+        rename_path(data, "wifi.ssid", "wifi.name"),
+        json!({
+            "wifi": {
+                "name": "Balena",
+                "id": {
+                    "$$formula": "super.name ~ super.name"
+                }
+            },
+            "upper": {
+                "$$formula": "super.wifi.name | UPPER"
+            }
+        })
+    );
+}
+
+#[test]
+fn rewrite_bracket_string_and_nested_identifiers() {
+    let data = json!({
+        "people": {
+            "boss": {
+                "id": "a"
+            }
+        },
+        "names": {
+            "a": "Robert"
+        },
+        "name": {
+            "$$formula": "names[people[\"boss\"].id] ~ ` ` ~ people['boss'][`id`]"
+        }
+    });
+
+    assert_eq!(
+        rename_path(data, "people.boss", "people.chief"),
+        json!({
+            "people": {
+                "chief": {
+                    "id": "a"
+                }
+            },
+            "names": {
+                "a": "Robert"
+            },
+            "name": {
+                "$$formula": "names[people.chief.id] ~ ` ` ~ people.chief.id"
+            }
+        })
+    );
+}
+
+#[test]
+fn rewrite_all_expression_parts() {
+    let data = json!({
+        "a": 1,
+        "b": {
+            "$$formula": "IF(a > 1, (a == 2 ? a : 3), a | F(a, x=a)) + a * (a - 1)"
+        }
+    });
+
+    assert_eq!(
+        rename_path(data, "a", "c")["b"],
+        json!({
+            "$$formula": "IF(c > 1, (c == 2 ? c : 3), c | F(c, x=c)) + c * (c - 1)"
+        })
+    );
+}
+
+#[test]
+fn moved_formulas_keep_relative_references() {
+    let data = json!({
+        "hostname": "balena",
+        "config": {},
+        "wifi": {
+            "ssid": "Balena",
+            "id": {
+                "$$formula": "super.ssid ~ super.super.hostname ~ hostname"
+            }
+        }
+    });
+
+    let renamed = rename_path(data, "wifi", "config.wifi");
+    assert_eq!(
+        renamed,
+        json!({
+            "hostname": "balena",
+            "config": {
+                "wifi": {
+                    "ssid": "Balena",
+                    "id": {
+                        "$$formula": "super.ssid ~ super.super.super.hostname ~ hostname"
+                    }
+                }
+            }
+        })
+    );
+    assert_eq!(
+        evaluate(renamed).unwrap()["config"]["wifi"]["id"],
+        json!("Balenabalenabalena")
+    );
+}
+
+#[test]
+fn untouched_formulas_keep_formatting() {
+    let data = json!({
+        "a": 1,
+        "b": 2,
+        "c": {
+            "$$formula": "b+b   |   F"
+        },
+        "d": {
+            "$$formula": "not valid ("
+        },
+        "e": {
+            "$$formula": "values[index]"
+        }
+    });
+
+    let renamed = rename_path(data.clone(), "a", "z");
+    assert_eq!(renamed["c"], data["c"]);
+    assert_eq!(renamed["d"], data["d"]);
+    assert_eq!(renamed["e"], data["e"]);
+}
+
+#[test]
+#[cfg(feature = "slugify")]
+fn evaluation_result_is_preserved() {
+    let data = json!({
+        "networks": [
+            {
+                "wifi": {
+                    "ssid": "Zrzka"
+                },
+                "id": {
+                    "$$formula": "super.wifi.ssid | SLUGIFY"
+                }
+            }
+        ],
+        "first": {
+            "$$formula": "networks[0].wifi.ssid ~ `-` ~ networks.0.id"
+        }
+    });
+
+    let expected = evaluate(data.clone()).unwrap();
+    let renamed = rename_path(data, "networks[0].wifi", "networks[0].network");
+    let evaluated = evaluate(renamed).unwrap();
+
+    assert_eq!(evaluated["first"], expected["first"]);
+    assert_eq!(evaluated["networks"][0]["id"], expected["networks"][0]["id"]);
+    assert_eq!(evaluated["networks"][0]["network"], expected["networks"][0]["wifi"]);
+}
+
+#[test]
+fn renamed_formulas_with_non_plain_names_are_evaluated() {
+    let data = json!({
+        "ssid": "Balena",
+        "id": {
+            "$$formula": "ssid | UPPER"
+        }
+    });
+
+    for name in &["[`order`]", "[`first network`]", "[`_`]", "[`notes`]"] {
+        let renamed = rename_path(data.clone(), "ssid", name);
+        assert_eq!(evaluate(renamed).unwrap()["id"], json!("BALENA"), "{}", name);
+    }
+
+    assert_eq!(
+        rename_path(data, "ssid", "[`order`]")["id"],
+        json!({
+            "$$formula": "[`order`] | UPPER"
+        })
+    );
+}
+
+#[test]
+fn rename_with_custom_keyword() {
+    let engine: Engine = EngineBuilder::default().eval_keyword("evalMe").into();
+    let data = json!({
+        "a": 1,
+        "b": {
+            "evalMe": "a + 1"
+        }
+    });
+    let from: Identifier = "a".parse().unwrap();
+    let to: Identifier = "c".parse().unwrap();
+
+    assert_eq!(
+        rename_with_engine(data, &from, &to, &engine).unwrap(),
+        json!({
+            "c": 1,
+            "b": {
+                "evalMe": "c + 1"
+            }
+        })
+    );
+}
+
+#[test]
+fn fail_on_invalid_paths() {
+    let data = json!({
+        "a": {
+            "b": 1
+        },
+        "c": 2,
+        "d": [1, 2]
+    });
+
+    let rename = |from: &str, to: &str| rename(data.clone(), &from.parse().unwrap(), &to.parse().unwrap());
+
+    // Does not exist
+    assert!(rename("x", "y").is_err());
+    // Already exists
+    assert!(rename("a", "c").is_err());
+    // Nested in the renamed path
+    assert!(rename("a", "a.b.c").is_err());
+    // Parent is not an object
+    assert!(rename("c", "d.e").is_err());
+    assert!(rename("c", "x.e").is_err());
+    // Not an object field
+    assert!(rename("d[0]", "e").is_err());
+    assert!(rename("a[c]", "e").is_err());
+    assert!(rename("super.a", "e").is_err());
+}
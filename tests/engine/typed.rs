@@ -0,0 +1,159 @@
+use serde_json::{json, Map};
+
+use balena_temen::{
+    ast::Identifier, error::*, Context, Engine, EngineBuilder, Signature, TypedFilter, Value, ValueType,
+};
+
+fn eval(engine: &Engine, expression: &str) -> Result<Value> {
+    engine.eval(
+        expression,
+        &Identifier::default(),
+        &json!({"names": ["a", "b"], "numbers": [1, 2.5]}),
+        &mut Context::default(),
+    )
+}
+
+fn greet(name: String, upper: Option<bool>) -> Result<String> {
+    let greeting = format!("Hello {}", name);
+    if upper.unwrap_or(false) {
+        Ok(greeting.to_uppercase())
+    } else {
+        Ok(greeting)
+    }
+}
+
+fn engine() -> Engine {
+    EngineBuilder::default()
+        .typed_function("greet", greet)
+        .typed_function("ANSWER", || Ok(42i64))
+        .typed_function("SUM", |values: Vec<f64>| Ok(values.iter().sum::<f64>()))
+        .typed_function("DIVIDE", |a: f64, b: f64| Ok(a / b))
+        .typed_function("KEYS", |object: Map<String, Value>| {
+            Ok(object.keys().cloned().collect::<Vec<String>>())
+        })
+        .typed_filter("REPEAT", |input: String, times: Option<u64>| {
+            Ok(input.repeat(times.unwrap_or(2) as usize))
+        })
+        .typed_filter("JOIN", |input: Vec<String>, separator: String| {
+            Ok(input.join(&separator))
+        })
+        .into()
+}
+
+#[test]
+fn call_typed_function() {
+    let engine = engine();
+
+    assert_eq!(eval(&engine, "GREET(`a`)").unwrap(), json!("Hello a"));
+    assert_eq!(eval(&engine, "GREET(`a`, true)").unwrap(), json!("HELLO A"));
+    assert_eq!(eval(&engine, "ANSWER() + 1").unwrap(), json!(43));
+    assert_eq!(eval(&engine, "`x` | GREET(_, false)").unwrap(), json!("Hello x"));
+    assert_eq!(eval(&engine, "SUM(numbers)").unwrap(), json!(3.5));
+}
+
+#[test]
+fn call_typed_filter() {
+    let engine = engine();
+
+    assert_eq!(eval(&engine, "`ab` | REPEAT").unwrap(), json!("abab"));
+    assert_eq!(eval(&engine, "`ab` | REPEAT(3)").unwrap(), json!("ababab"));
+    assert_eq!(eval(&engine, "names | JOIN(`, `)").unwrap(), json!("a, b"));
+}
+
+#[test]
+fn fail_on_invalid_argument_type() {
+    let engine = engine();
+
+    assert_eq!(
+        eval(&engine, "GREET(`a`, `yes`)").unwrap_err().to_string(),
+        Error::with_message("invalid argument type")
+            .context("function", "GREET")
+            .context("argument", "arg2")
+            .context("argument value", "\"yes\"")
+            .context("expected", "boolean")
+            .to_string()
+    );
+    assert!(eval(&engine, "`ab` | REPEAT(-1)").is_err());
+    assert!(eval(&engine, "KEYS(names)").is_err());
+    assert!(eval(&engine, "SUM(names)").is_err());
+}
+
+#[test]
+fn fail_on_invalid_input_type() {
+    let engine = engine();
+
+    assert_eq!(
+        eval(&engine, "1 | REPEAT").unwrap_err().to_string(),
+        Error::with_message("invalid input type")
+            .context("filter", "REPEAT")
+            .context("expected", "string")
+            .context("input", "1")
+            .to_string()
+    );
+    assert!(eval(&engine, "names | REPEAT").is_err());
+}
+
+#[test]
+fn fail_on_invalid_number_of_arguments() {
+    let engine = engine();
+
+    assert_eq!(
+        eval(&engine, "GREET()").unwrap_err().to_string(),
+        Error::with_message("missing argument")
+            .context("function", "GREET")
+            .context("argument", "arg1")
+            .to_string()
+    );
+    assert_eq!(
+        eval(&engine, "ANSWER(1)").unwrap_err().to_string(),
+        Error::with_message("invalid number of arguments")
+            .context("function", "ANSWER")
+            .context("argument count", "1")
+            .context("expected", "0")
+            .to_string()
+    );
+    assert!(eval(&engine, "names | JOIN").is_err());
+    assert!(eval(&engine, "GREET(greeting=`a`)").is_err());
+}
+
+#[test]
+fn fail_on_invalid_result() {
+    let engine = engine();
+
+    assert!(eval(&engine, "DIVIDE(1, 0)").is_err());
+    assert_eq!(eval(&engine, "DIVIDE(1, 2)").unwrap(), json!(0.5));
+}
+
+#[test]
+fn signature_is_derived_from_types() {
+    let engine = engine();
+
+    assert!(engine.check("GREET(`a`, true)").is_ok());
+    assert!(engine.check("GREET()").is_err());
+    assert!(engine.check("GREET(`a`, 1)").is_err());
+    assert!(engine.check("GREET(`a`, true, 1)").is_err());
+    assert!(engine.check("`a` | REPEAT(`b`)").is_err());
+    assert!(engine.check("names | JOIN").is_err());
+
+    let signature: Signature = <fn(Vec<String>, String) -> Result<String> as TypedFilter<_>>::signature();
+    assert_eq!(
+        signature,
+        Signature::new(ValueType::String)
+            .input(ValueType::Array)
+            .arg("arg1", ValueType::String)
+    );
+}
+
+#[test]
+#[should_panic(expected = "required argument `arg2` follows an optional argument")]
+fn fail_on_required_argument_after_optional_one() {
+    EngineBuilder::default().typed_function("F", |a: Option<i64>, b: i64| Ok(a.unwrap_or(0) + b));
+}
+
+#[test]
+#[should_panic(expected = "required argument `arg3` follows an optional argument")]
+fn fail_on_required_filter_argument_after_optional_one() {
+    EngineBuilder::default().typed_filter("F", |input: i64, a: i64, b: Option<i64>, c: i64| {
+        Ok(input + a + b.unwrap_or(0) + c)
+    });
+}
@@ -0,0 +1,64 @@
+use balena_temen::ast::*;
+
+fn dependencies(expression: &str, position: &str) -> Vec<String> {
+    let expression: Expression = expression.parse().unwrap();
+    let position = if position.is_empty() {
+        Identifier::default()
+    } else {
+        position.parse().unwrap()
+    };
+    expression
+        .dependencies(&position)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn literal_has_no_dependencies() {
+    assert!(dependencies("`a` ~ 1 + 2", "").is_empty());
+}
+
+#[test]
+fn relative_identifiers_are_canonicalized() {
+    assert_eq!(dependencies("super.ssid", "wifi.id"), vec!["wifi.ssid"]);
+    assert_eq!(dependencies("this.super.name", "people[1].id"), vec!["people[1].name"]);
+    assert_eq!(dependencies("names.this.id.super", "a"), vec!["names"]);
+}
+
+#[test]
+fn nested_identifiers_are_included() {
+    assert_eq!(
+        dependencies("people[boss[super.index]].name", "a.b"),
+        vec!["a.index", "boss[a.index]", "people[boss[a.index]].name"]
+    );
+}
+
+#[test]
+fn function_and_filter_arguments_are_included() {
+    assert_eq!(
+        dependencies("IF(flag, a, b) | DATETIME(format=fmt) | POW(exponent, _)", ""),
+        vec!["flag", "a", "b", "fmt", "exponent"]
+    );
+}
+
+#[test]
+fn logical_and_relational_operands_are_included() {
+    assert_eq!(
+        dependencies("not a and b > c or d == `x`", ""),
+        vec!["a", "b", "c", "d"]
+    );
+}
+
+#[test]
+fn dependencies_are_listed_once() {
+    assert_eq!(dependencies("a + a * b - super.a", "b"), vec!["a", "b"]);
+}
+
+#[test]
+fn fail_on_invalid_super() {
+    let expression: Expression = "super.super.a".parse().unwrap();
+    let position: Identifier = "a".parse().unwrap();
+    assert!(expression.dependencies(&position).is_err());
+}
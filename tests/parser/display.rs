@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+
+use balena_temen::ast::*;
+use proptest::{collection::vec, prelude::*};
+
+//
+// Expressions are generated in the same way as the grammar produces them. Some ASTs
+// can't be produced by the parser (`a == (b and c)`, filters on math operands which
+// can't be enclosed in parentheses, ...) and they're not generated.
+//
+
+const RESERVED: [&str; 7] = ["not", "or", "and", "true", "false", "this", "super"];
+
+fn name() -> impl Strategy<Value = String> {
+    "[a-zA-Z_][a-zA-Z0-9_]{0,4}".prop_filter("reserved keyword", |x| {
+        !RESERVED.iter().any(|keyword| x.starts_with(keyword))
+    })
+}
+
+fn keyword_name() -> impl Strategy<Value = String> {
+    (proptest::sample::select(RESERVED.to_vec()), "[a-z0-9_]{0,3}")
+        .prop_map(|(keyword, rest)| keyword.to_string() + &rest)
+}
+
+fn string() -> impl Strategy<Value = String> {
+    "[a-z0-9 .\\[\\]`'\"]{0,6}".prop_filter("all quote characters", |x| {
+        !(x.contains('`') && x.contains('\'') && x.contains('"'))
+    })
+}
+
+fn function_name() -> impl Strategy<Value = String> {
+    "[A-Z][A-Z0-9_]{0,4}"
+}
+
+fn float() -> impl Strategy<Value = f64> {
+    any::<f64>().prop_filter("finite", |x| x.is_finite())
+}
+
+fn identifier() -> impl Strategy<Value = Identifier> {
+    // Names which are not valid identifiers are printed in square brackets (`[`_`]`, `[`order`]`)
+    let first = prop_oneof![
+        4 => name().prop_map(IdentifierValue::Name),
+        1 => Just(IdentifierValue::Name("_".to_string())),
+        1 => keyword_name().prop_map(IdentifierValue::Name),
+        1 => string().prop_map(IdentifierValue::Name),
+        1 => Just(IdentifierValue::This),
+        1 => Just(IdentifierValue::Super),
+    ];
+
+    let leaf = (first.clone(), vec(first.clone(), 0..2)).prop_map(|(first, rest)| {
+        let mut values = vec![first];
+        values.extend(rest);
+        Identifier::new(values)
+    });
+
+    leaf.prop_recursive(2, 8, 3, move |nested| {
+        let value = prop_oneof![
+            name().prop_map(IdentifierValue::Name),
+            string().prop_map(IdentifierValue::Name),
+            any::<i32>().prop_map(|x| IdentifierValue::Index(x as isize)),
+            Just(IdentifierValue::This),
+            Just(IdentifierValue::Super),
+            nested.prop_map(IdentifierValue::Identifier),
+        ];
+        (first.clone(), vec(value, 0..3)).prop_map(|(first, rest)| {
+            let mut values = vec![first];
+            values.extend(rest);
+            Identifier::new(values)
+        })
+    })
+}
+
+fn math_operator() -> impl Strategy<Value = MathOperator> {
+    prop_oneof![
+        Just(MathOperator::Addition),
+        Just(MathOperator::Subtraction),
+        Just(MathOperator::Multiplication),
+        Just(MathOperator::Division),
+        Just(MathOperator::Modulo),
+    ]
+}
+
+fn relational_operator() -> impl Strategy<Value = LogicalOperator> {
+    prop_oneof![
+        Just(LogicalOperator::Equal),
+        Just(LogicalOperator::NotEqual),
+        Just(LogicalOperator::GreaterThan),
+        Just(LogicalOperator::GreaterThanOrEqual),
+        Just(LogicalOperator::LowerThan),
+        Just(LogicalOperator::LowerThanOrEqual),
+    ]
+}
+
+fn math_precedence(operator: MathOperator) -> u8 {
+    match operator {
+        MathOperator::Addition | MathOperator::Subtraction => 1,
+        _ => 2,
+    }
+}
+
+fn logical_precedence(operator: LogicalOperator) -> u8 {
+    match operator {
+        LogicalOperator::And => 1,
+        _ => 2,
+    }
+}
+
+// Builds left associative expression tree in the same way as the pest precedence climber
+fn climb<O: Copy>(
+    lhs: Expression,
+    rest: &mut std::iter::Peekable<std::vec::IntoIter<(O, Expression)>>,
+    min_precedence: u8,
+    precedence: fn(O) -> u8,
+    build: fn(Expression, O, Expression) -> Expression,
+) -> Expression {
+    let mut lhs = lhs;
+    while let Some((operator, _)) = rest.peek() {
+        let operator = *operator;
+        if precedence(operator) < min_precedence {
+            break;
+        }
+        let (_, mut rhs) = rest.next().unwrap();
+        while let Some(next) = rest.peek().map(|(x, _)| precedence(*x)) {
+            if next <= precedence(operator) {
+                break;
+            }
+            rhs = climb(rhs, rest, next, precedence, build);
+        }
+        lhs = build(lhs, operator, rhs);
+    }
+    lhs
+}
+
+fn math(lhs: Expression, operator: MathOperator, rhs: Expression) -> Expression {
+    Expression::new(ExpressionValue::Math(MathExpression::new(lhs, rhs, operator)))
+}
+
+fn logical(lhs: Expression, operator: LogicalOperator, rhs: Expression) -> Expression {
+    Expression::new(ExpressionValue::Logical(LogicalExpression::new(lhs, rhs, operator)))
+}
+
+fn function_call(args: BoxedStrategy<Expression>, filter: bool) -> impl Strategy<Value = FunctionCall> {
+    let arg = if filter {
+        prop_oneof![4 => args.clone(), 1 => Just(Expression::new(ExpressionValue::Placeholder))].boxed()
+    } else {
+        args.clone()
+    };
+
+    (
+        function_name(),
+        vec(arg, 0..3),
+        proptest::collection::btree_map(name(), args, 0..2),
+    )
+        .prop_map(
+            |(name, args, kwargs): (String, Vec<Expression>, BTreeMap<String, Expression>)| {
+                FunctionCall::new_with_kwargs(name, args, kwargs)
+            },
+        )
+}
+
+// basic_value
+fn basic_value(args: BoxedStrategy<Expression>) -> impl Strategy<Value = ExpressionValue> {
+    let concat_value = prop_oneof![
+        float().prop_map(ExpressionValue::Float),
+        any::<i64>().prop_map(ExpressionValue::Integer),
+        string().prop_map(ExpressionValue::String),
+        identifier().prop_map(ExpressionValue::Identifier),
+    ];
+    let concat_first = prop_oneof![
+        string().prop_map(ExpressionValue::String),
+        identifier().prop_map(ExpressionValue::Identifier),
+    ];
+
+    prop_oneof![
+        any::<i64>().prop_map(ExpressionValue::Integer),
+        float().prop_map(ExpressionValue::Float),
+        any::<bool>().prop_map(ExpressionValue::Boolean),
+        string().prop_map(ExpressionValue::String),
+        identifier().prop_map(ExpressionValue::Identifier),
+        function_call(args, false).prop_map(ExpressionValue::FunctionCall),
+        (concat_first, vec(concat_value, 1..3)).prop_map(|(first, rest)| {
+            let mut values = vec![first];
+            values.extend(rest);
+            ExpressionValue::StringConcat(StringConcat::new(values))
+        }),
+    ]
+}
+
+// basic_expression, parentheses allow any tree shape
+fn basic_expression(args: BoxedStrategy<Expression>) -> BoxedStrategy<ExpressionValue> {
+    let conditions = args.clone();
+    basic_value(args)
+        .prop_recursive(2, 8, 2, move |inner| {
+            prop_oneof![
+                (inner.clone(), math_operator(), inner.clone()).prop_map(|(lhs, operator, rhs)| {
+                    ExpressionValue::Math(MathExpression::new(
+                        Expression::new(lhs),
+                        Expression::new(rhs),
+                        operator,
+                    ))
+                }),
+                (conditions.clone(), inner.clone(), inner).prop_map(|(condition, truthy, falsy)| {
+                    ExpressionValue::Ternary(TernaryExpression::new(
+                        condition,
+                        Expression::new(truthy),
+                        Expression::new(falsy),
+                    ))
+                }),
+            ]
+        })
+        .boxed()
+}
+
+// basic_expression_filter
+fn basic_expression_filter(
+    args: BoxedStrategy<Expression>,
+    filters: std::ops::Range<usize>,
+) -> BoxedStrategy<Expression> {
+    (basic_expression(args.clone()), vec(function_call(args, true), filters))
+        .prop_map(|(value, filters)| Expression::new_with_filters(value, filters))
+        .boxed()
+}
+
+// comparison_value, all operands except the last one must have filters, otherwise
+// the basic expression consumes the following math operators
+fn comparison_value(args: BoxedStrategy<Expression>) -> impl Strategy<Value = Expression> {
+    (
+        vec((basic_expression_filter(args.clone(), 1..2), math_operator()), 0..3),
+        basic_expression_filter(args, 0..2),
+    )
+        .prop_map(|(operands, last)| {
+            let mut operands = operands.into_iter();
+            match operands.next() {
+                None => last,
+                Some((first, operator)) => {
+                    let mut rest = vec![];
+                    let mut operator = operator;
+                    for (operand, next) in operands {
+                        rest.push((operator, operand));
+                        operator = next;
+                    }
+                    rest.push((operator, last));
+                    climb(first, &mut rest.into_iter().peekable(), 0, math_precedence, math)
+                }
+            }
+        })
+}
+
+// logical_value = { logical_not? ~ comparison_expression }
+fn logical_value(args: BoxedStrategy<Expression>) -> impl Strategy<Value = Expression> {
+    (
+        any::<bool>(),
+        comparison_value(args.clone()),
+        vec((relational_operator(), comparison_value(args)), 0..2),
+    )
+        .prop_map(|(negated, first, rest)| {
+            let expression = rest
+                .into_iter()
+                .fold(first, |lhs, (operator, rhs)| logical(lhs, operator, rhs));
+            if negated {
+                expression.into_negated()
+            } else {
+                expression
+            }
+        })
+}
+
+fn expression() -> impl Strategy<Value = Expression> {
+    let leaf = prop_oneof![
+        any::<i64>().prop_map(ExpressionValue::Integer),
+        identifier().prop_map(ExpressionValue::Identifier),
+        string().prop_map(ExpressionValue::String),
+    ]
+    .prop_map(Expression::new);
+
+    leaf.prop_recursive(2, 16, 3, |inner| {
+        let args = inner.boxed();
+        (
+            logical_value(args.clone()),
+            vec(
+                (
+                    prop_oneof![Just(LogicalOperator::And), Just(LogicalOperator::Or)],
+                    logical_value(args),
+                ),
+                0..3,
+            ),
+        )
+            .prop_map(|(first, rest)| climb(first, &mut rest.into_iter().peekable(), 0, logical_precedence, logical))
+    })
+}
+
+proptest! {
+    #[test]
+    fn parse_display_roundtrip(expression in expression()) {
+        let printed = expression.to_string();
+        let parsed: Expression = printed.parse().unwrap_or_else(|e| panic!("{}: {}", printed, e));
+
+        // Parsed expression is equal and printed again in the same (canonical) way
+        prop_assert_eq!(parsed.to_string(), printed.as_str());
+        prop_assert_eq!(parsed, expression, "{}", printed);
+    }
+}
@@ -1,6 +1,8 @@
+use std::collections::BTreeMap;
+
 use balena_temen::ast::*;
 
-use crate::test_parse_eq;
+use crate::{test_parse_eq, test_parse_err};
 
 #[test]
 fn single_filter() {
@@ -22,3 +24,113 @@ fn chained_filter() {
     );
     test_parse_eq!("'Abc' | SLUGIFY | RUSTIFY", exp);
 }
+
+#[test]
+fn filter_with_named_arguments() {
+    let mut kwargs = BTreeMap::new();
+    kwargs.insert(
+        "format".to_string(),
+        Expression::new(ExpressionValue::String("%Y".to_string())),
+    );
+    let exp = Expression::new_with_filters(
+        ExpressionValue::Integer(1),
+        vec![FunctionCall::new_with_kwargs("DATE".to_string(), vec![], kwargs)],
+    );
+    test_parse_eq!("1 | DATE(format=`%Y`)", exp);
+}
+
+#[test]
+fn function_with_placeholder_as_filter() {
+    let exp = Expression::new_with_filters(
+        ExpressionValue::Integer(3),
+        vec![FunctionCall::new(
+            "POW".to_string(),
+            vec![
+                Expression::new(ExpressionValue::Integer(2)),
+                Expression::new(ExpressionValue::Placeholder),
+            ],
+        )],
+    );
+    test_parse_eq!("3 | POW(2, _)", exp);
+    test_parse_eq!("3 | POW( 2 , _ )", exp);
+}
+
+#[test]
+fn placeholder_is_an_identifier_outside_of_filter() {
+    let exp = Expression::new(ExpressionValue::FunctionCall(FunctionCall::new(
+        "POW".to_string(),
+        vec![
+            Expression::new(ExpressionValue::Integer(2)),
+            Expression::new(ExpressionValue::Identifier(Identifier::default().name("_"))),
+        ],
+    )));
+    test_parse_eq!("POW(2, _)", exp);
+}
+
+#[test]
+fn underscore_is_an_identifier_inside_filter_argument_expression() {
+    let underscore = || Expression::new(ExpressionValue::Identifier(Identifier::default().name("_")));
+
+    let exp = Expression::new_with_filters(
+        ExpressionValue::Integer(3),
+        vec![FunctionCall::new(
+            "POW".to_string(),
+            vec![
+                Expression::new(ExpressionValue::Math(MathExpression::new(
+                    underscore(),
+                    Expression::new(ExpressionValue::Integer(1)),
+                    MathOperator::Addition,
+                ))),
+                Expression::new(ExpressionValue::Placeholder),
+            ],
+        )],
+    );
+    test_parse_eq!("3 | POW(_ + 1, _)", exp);
+
+    let mut kwargs = BTreeMap::new();
+    kwargs.insert("x".to_string(), underscore());
+    let exp = Expression::new_with_filters(
+        ExpressionValue::Integer(3),
+        vec![FunctionCall::new_with_kwargs("FN".to_string(), vec![], kwargs)],
+    );
+    test_parse_eq!("3 | FN(x=_)", exp);
+
+    for expression in &[
+        "3 | FN(_.a)",
+        "3 | FN(_ == 1)",
+        "3 | IF(_ > 1, `big`, `small`)",
+        "3 | FN((_))",
+        "3 | FN(a[_])",
+        "3 | FN(POW(_, 2))",
+    ] {
+        assert!(expression.parse::<Expression>().is_ok(), "{}", expression);
+    }
+}
+
+#[test]
+fn placeholder_of_nested_filter() {
+    let exp = Expression::new_with_filters(
+        ExpressionValue::Integer(3),
+        vec![FunctionCall::new(
+            "FN".to_string(),
+            vec![Expression::new_with_filters(
+                ExpressionValue::Identifier(Identifier::default().name("a")),
+                vec![FunctionCall::new(
+                    "POW".to_string(),
+                    vec![
+                        Expression::new(ExpressionValue::Placeholder),
+                        Expression::new(ExpressionValue::Integer(2)),
+                    ],
+                )],
+            )],
+        )],
+    );
+    test_parse_eq!("3 | FN(a | POW(_, 2))", exp);
+    // `_` is a regular name if it's not the first identifier value
+    assert!("3 | FN(this._ == 1)".parse::<Expression>().is_ok());
+}
+
+#[test]
+fn fail_on_placeholder_after_named_argument() {
+    test_parse_err!("3 | POW(base=2, _)");
+}
@@ -1,6 +1,8 @@
+use std::collections::BTreeMap;
+
 use balena_temen::ast::*;
 
-use crate::test_parse_eq;
+use crate::{test_parse_eq, test_parse_err};
 
 #[test]
 fn without_arguments() {
@@ -33,3 +35,67 @@ fn with_arguments() {
         Expression::new(ExpressionValue::FunctionCall(FunctionCall::new("UUID", args)))
     );
 }
+
+#[test]
+fn with_named_arguments() {
+    let mut kwargs = BTreeMap::new();
+    kwargs.insert("timestamp".to_string(), Expression::new(ExpressionValue::Boolean(true)));
+
+    test_parse_eq!(
+        "NOW(timestamp=true)",
+        Expression::new(ExpressionValue::FunctionCall(FunctionCall::new_with_kwargs(
+            "NOW",
+            vec![],
+            kwargs
+        )))
+    );
+}
+
+#[test]
+fn with_positional_and_named_arguments() {
+    let args = vec![Expression::new(ExpressionValue::String("%Y".to_string()))];
+    let mut kwargs = BTreeMap::new();
+    kwargs.insert(
+        "tz".to_string(),
+        Expression::new(ExpressionValue::String("Europe/Prague".to_string())),
+    );
+    kwargs.insert(
+        "upper".to_string(),
+        Expression::new(ExpressionValue::Logical(LogicalExpression::new(
+            Expression::new(ExpressionValue::Integer(1)),
+            Expression::new(ExpressionValue::Integer(1)),
+            LogicalOperator::Equal,
+        ))),
+    );
+
+    test_parse_eq!(
+        "FORMAT(`%Y`, tz = `Europe/Prague`, upper=1 == 1)",
+        Expression::new(ExpressionValue::FunctionCall(FunctionCall::new_with_kwargs(
+            "FORMAT", args, kwargs
+        )))
+    );
+}
+
+#[test]
+fn equality_is_not_named_argument() {
+    let args = vec![Expression::new(ExpressionValue::Logical(LogicalExpression::new(
+        Expression::new(ExpressionValue::Identifier(Identifier::default().name("a"))),
+        Expression::new(ExpressionValue::Integer(1)),
+        LogicalOperator::Equal,
+    )))];
+
+    test_parse_eq!(
+        "FN(a == 1)",
+        Expression::new(ExpressionValue::FunctionCall(FunctionCall::new("FN", args)))
+    );
+}
+
+#[test]
+fn fail_on_positional_argument_after_named_argument() {
+    test_parse_err!("FN(a=1, 2)");
+}
+
+#[test]
+fn fail_on_duplicate_named_argument() {
+    test_parse_err!("FN(a=1, a=2)");
+}
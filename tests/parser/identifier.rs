@@ -113,6 +113,30 @@ fn indirect_index() {
     );
 }
 
+#[test]
+fn leading_square_brackets() {
+    test_parse_eq!(
+        "[`first network`].ssid",
+        Expression::new(ExpressionValue::Identifier(Identifier::new(vec![
+            IdentifierValue::Name("first network".to_string()),
+            IdentifierValue::Name("ssid".to_string())
+        ])))
+    );
+    test_parse_eq!(
+        "[`order`][0]",
+        Expression::new(ExpressionValue::Identifier(Identifier::new(vec![
+            IdentifierValue::Name("order".to_string()),
+            IdentifierValue::Index(0)
+        ])))
+    );
+    test_parse_eq!(
+        "[0]",
+        Expression::new(ExpressionValue::Identifier(Identifier::new(vec![
+            IdentifierValue::Index(0)
+        ])))
+    );
+}
+
 #[test]
 fn fail_on_float_index() {
     test_parse_err!("networks[2.3].wifi");
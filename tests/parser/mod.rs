@@ -1,3 +1,6 @@
+mod dependencies;
+#[cfg(not(target_arch = "wasm32"))]
+mod display;
 mod filter;
 mod function;
 mod identifier;
@@ -5,3 +8,6 @@ mod logical;
 mod math;
 mod primitive;
 mod relational;
+#[cfg(feature = "serde")]
+mod serde;
+mod visit;
@@ -0,0 +1,183 @@
+use balena_temen::ast::*;
+use serde_json::{json, Value};
+
+fn assert_json(expression: &str, json: Value) {
+    let expression: Expression = expression.parse().unwrap();
+    assert_eq!(serde_json::to_value(&expression).unwrap(), json);
+    assert_eq!(serde_json::from_value::<Expression>(json).unwrap(), expression);
+}
+
+fn integer(value: i64) -> Value {
+    json!({
+        "value": { "type": "integer", "value": value },
+        "negated": false,
+        "filters": []
+    })
+}
+
+#[test]
+fn primitives() {
+    assert_json("1", integer(1));
+    assert_json(
+        "not true",
+        json!({
+            "value": { "type": "boolean", "value": true },
+            "negated": true,
+            "filters": []
+        }),
+    );
+    assert_json(
+        "2.5",
+        json!({
+            "value": { "type": "float", "value": 2.5 },
+            "negated": false,
+            "filters": []
+        }),
+    );
+    assert_json(
+        "`a`",
+        json!({
+            "value": { "type": "string", "value": "a" },
+            "negated": false,
+            "filters": []
+        }),
+    );
+}
+
+#[test]
+fn identifier() {
+    assert_json(
+        "super.names[0][this.key]",
+        json!({
+            "value": {
+                "type": "identifier",
+                "value": {
+                    "values": [
+                        { "type": "super" },
+                        { "type": "name", "value": "names" },
+                        { "type": "index", "value": 0 },
+                        {
+                            "type": "identifier",
+                            "value": {
+                                "values": [
+                                    { "type": "this" },
+                                    { "type": "name", "value": "key" }
+                                ]
+                            }
+                        }
+                    ]
+                }
+            },
+            "negated": false,
+            "filters": []
+        }),
+    );
+}
+
+#[test]
+fn operators() {
+    assert_json(
+        "1 % 2",
+        json!({
+            "value": {
+                "type": "math",
+                "value": { "lhs": integer(1), "rhs": integer(2), "operator": "modulo" }
+            },
+            "negated": false,
+            "filters": []
+        }),
+    );
+    assert_json(
+        "1 >= 2",
+        json!({
+            "value": {
+                "type": "logical",
+                "value": { "lhs": integer(1), "rhs": integer(2), "operator": "greaterThanOrEqual" }
+            },
+            "negated": false,
+            "filters": []
+        }),
+    );
+}
+
+#[test]
+fn function_calls_and_filters() {
+    assert_json(
+        "POW(2, e=3) | F(1, _)",
+        json!({
+            "value": {
+                "type": "functionCall",
+                "value": {
+                    "name": "POW",
+                    "args": [integer(2)],
+                    "kwargs": { "e": integer(3) }
+                }
+            },
+            "negated": false,
+            "filters": [
+                {
+                    "name": "F",
+                    "args": [
+                        integer(1),
+                        {
+                            "value": { "type": "placeholder" },
+                            "negated": false,
+                            "filters": []
+                        }
+                    ],
+                    "kwargs": {}
+                }
+            ]
+        }),
+    );
+}
+
+#[test]
+fn ternary_and_concat() {
+    assert_json(
+        "(1 ? 2 : `a` ~ 3)",
+        json!({
+            "value": {
+                "type": "ternary",
+                "value": {
+                    "condition": integer(1),
+                    "truthy": integer(2),
+                    "falsy": {
+                        "value": {
+                            "type": "stringConcat",
+                            "value": {
+                                "values": [
+                                    { "type": "string", "value": "a" },
+                                    { "type": "integer", "value": 3 }
+                                ]
+                            }
+                        },
+                        "negated": false,
+                        "filters": []
+                    }
+                }
+            },
+            "negated": false,
+            "filters": []
+        }),
+    );
+}
+
+#[test]
+fn optional_fields() {
+    let json = json!({
+        "value": {
+            "type": "functionCall",
+            "value": { "name": "NOW" }
+        }
+    });
+    let expression: Expression = "NOW()".parse().unwrap();
+    assert_eq!(serde_json::from_value::<Expression>(json).unwrap(), expression);
+}
+
+#[test]
+fn invalid_shape() {
+    assert!(serde_json::from_value::<Expression>(json!({ "value": { "type": "unknown" } })).is_err());
+    assert!(serde_json::from_value::<Expression>(json!({ "value": { "type": "integer", "value": "1" } })).is_err());
+    assert!(serde_json::from_value::<Expression>(json!({ "negated": true })).is_err());
+}
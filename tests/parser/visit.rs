@@ -0,0 +1,124 @@
+use balena_temen::ast::{visit::*, *};
+
+// Records names of visited nodes
+#[derive(Default)]
+struct Trace {
+    nodes: Vec<String>,
+}
+
+impl<'ast> Visitor<'ast> for Trace {
+    fn visit_integer(&mut self, value: i64) {
+        self.nodes.push(format!("integer {}", value));
+    }
+
+    fn visit_float(&mut self, value: f64) {
+        self.nodes.push(format!("float {}", value));
+    }
+
+    fn visit_boolean(&mut self, value: bool) {
+        self.nodes.push(format!("boolean {}", value));
+    }
+
+    fn visit_string(&mut self, value: &'ast str) {
+        self.nodes.push(format!("string {}", value));
+    }
+
+    fn visit_placeholder(&mut self) {
+        self.nodes.push("placeholder".to_string());
+    }
+
+    fn visit_identifier(&mut self, identifier: &'ast Identifier) {
+        self.nodes.push(format!("identifier {}", identifier));
+        walk_identifier(self, identifier);
+    }
+
+    fn visit_math_operator(&mut self, operator: MathOperator) {
+        self.nodes.push(format!("math {}", operator));
+    }
+
+    fn visit_logical_operator(&mut self, operator: LogicalOperator) {
+        self.nodes.push(format!("logical {}", operator));
+    }
+
+    fn visit_function_call(&mut self, call: &'ast FunctionCall) {
+        self.nodes.push(format!("function {}", call.name));
+        walk_function_call(self, call);
+    }
+
+    fn visit_filter(&mut self, filter: &'ast FunctionCall) {
+        self.nodes.push(format!("filter {}", filter.name));
+        walk_filter(self, filter);
+    }
+
+    fn visit_string_concat(&mut self, concat: &'ast StringConcat) {
+        self.nodes.push("concat".to_string());
+        walk_string_concat(self, concat);
+    }
+
+    fn visit_ternary_expression(&mut self, ternary: &'ast TernaryExpression) {
+        self.nodes.push("ternary".to_string());
+        walk_ternary_expression(self, ternary);
+    }
+}
+
+fn trace(expression: &str) -> Vec<String> {
+    let expression: Expression = expression.parse().unwrap();
+    let mut trace = Trace::default();
+    trace.visit_expression(&expression);
+    trace.nodes
+}
+
+#[test]
+fn visit_all_nodes() {
+    assert_eq!(
+        trace("(1.5 > a[b.c] ? F(1, x=true) : `a` ~ 2) | G(_) and d or 1 + 2"),
+        vec![
+            "ternary",
+            "float 1.5",
+            "logical >",
+            "identifier a[b.c]",
+            "identifier b.c",
+            "function F",
+            "integer 1",
+            "boolean true",
+            "concat",
+            "string a",
+            "integer 2",
+            "filter G",
+            "placeholder",
+            "logical and",
+            "identifier d",
+            "logical or",
+            "integer 1",
+            "math +",
+            "integer 2",
+        ]
+    );
+}
+
+// Doubles all integers and uppercases all strings
+struct Transform;
+
+impl VisitorMut for Transform {
+    fn visit_integer_mut(&mut self, value: &mut i64) {
+        *value *= 2;
+    }
+
+    fn visit_string_mut(&mut self, value: &mut String) {
+        *value = value.to_uppercase();
+    }
+
+    fn visit_identifier_value_mut(&mut self, value: &mut IdentifierValue) {
+        match value {
+            IdentifierValue::Name(ref mut name) => *name = name.to_uppercase(),
+            _ => walk_identifier_value_mut(self, value),
+        };
+    }
+}
+
+#[test]
+fn transform_nodes() {
+    let mut expression: Expression = "a[b.c] + 1 | F(2, y=(3 ? `z` : 4)) == `x` ~ 5".parse().unwrap();
+    Transform.visit_expression_mut(&mut expression);
+    assert_eq!(expression.to_string(), "A[B.C] + 2 | F(4, y=(6 ? `Z` : 8)) == `X` ~ 10");
+}